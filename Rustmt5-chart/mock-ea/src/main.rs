@@ -0,0 +1,325 @@
+//+------------------------------------------------------------------+
+//|                                                          main.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+//
+// Offline stand-in for ZmqPublisher.mq5: replays recorded tick CSVs on the
+// PUB socket and answers the REP protocol with a simulated broker, so the
+// chart and the sum3api client can run without an MT5 terminal.
+
+use clap::Parser;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use sum3api::bars;
+use sum3api::history::{self, TickRow};
+use sum3api::sim::{self, SimBroker, SimSettings};
+use sum3api::{HistoryMode, OrderRequest, OrderResponse, Request, TradeError, CSV_DATA_MARKER};
+use zeromq::{Socket, SocketRecv, SocketSend};
+
+// ============================================================================
+// Command Line
+// ============================================================================
+
+#[derive(Debug, Parser)]
+#[command(name = "mock-ea", about = "Offline ZmqPublisher.mq5 simulator")]
+struct Args {
+    /// Tick CSVs to replay (default: Live_*.csv and *_TICKS_*.csv in ./output)
+    files: Vec<PathBuf>,
+
+    /// PUB address for ticks (EA InpPubAddress)
+    #[arg(long, default_value = "tcp://0.0.0.0:5555")]
+    pub_address: String,
+
+    /// REP address for orders (EA InpRepAddress)
+    #[arg(long, default_value = "tcp://0.0.0.0:5556")]
+    rep_address: String,
+
+    /// Symbol to publish (default: taken from the first file name)
+    #[arg(long)]
+    symbol: Option<String>,
+
+    /// Replay speed multiplier
+    #[arg(long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// Delay between ticks that share the same second (ms)
+    #[arg(long, default_value_t = 50)]
+    min_gap_ms: u64,
+
+    /// Longest pause between two ticks, so session gaps don't stall the feed (ms)
+    #[arg(long, default_value_t = 5000)]
+    max_gap_ms: u64,
+
+    /// Stop after one pass instead of looping
+    #[arg(long)]
+    once: bool,
+
+    /// Price decimals used in history downloads and spread points
+    #[arg(long, default_value_t = 3)]
+    digits: u32,
+
+    #[arg(long, default_value_t = 10_000.0)]
+    balance: f64,
+
+    #[arg(long, default_value_t = 100.0)]
+    leverage: f64,
+
+    #[arg(long, default_value_t = 100.0)]
+    contract_size: f64,
+
+    /// Fail every request of a type with an MT5 retcode, e.g. market_buy=10019
+    /// (use *=CODE for all trading requests). Repeatable.
+    #[arg(long = "inject", value_parser = parse_injection)]
+    injections: Vec<(String, u32)>,
+
+    /// Delay before each REP reply, to exercise client timeouts (ms)
+    #[arg(long, default_value_t = 0)]
+    reply_delay_ms: u64,
+}
+
+fn parse_injection(text: &str) -> Result<(String, u32), String> {
+    let (kind, code) = text
+        .split_once('=')
+        .ok_or_else(|| format!("expected TYPE=CODE, got \"{}\"", text))?;
+    let code = code.parse().map_err(|_| format!("invalid retcode \"{}\"", code))?;
+    Ok((kind.to_string(), code))
+}
+
+// ============================================================================
+// Main
+// ============================================================================
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    let files = if args.files.is_empty() { default_files(Path::new("output"))? } else { args.files.clone() };
+    if files.is_empty() {
+        return Err("no tick files given and none found in ./output".into());
+    }
+
+    let mut ticks: Vec<TickRow> = Vec::new();
+    for file in &files {
+        let rows = history::read_ticks(file)?;
+        println!("Loaded {} ticks from {}", rows.len(), file.display());
+        ticks.extend(rows);
+    }
+    if ticks.is_empty() {
+        return Err("tick files contain no rows".into());
+    }
+    ticks.sort_by_key(|t| t.time);
+    let ticks = Arc::new(ticks);
+
+    let symbol = args
+        .symbol
+        .clone()
+        .or_else(|| files.iter().find_map(|f| history::symbol_from_file_name(f)))
+        .unwrap_or_else(|| "XAUUSDc".to_string());
+
+    let broker = Arc::new(Mutex::new(SimBroker::new(SimSettings {
+        symbol: symbol.clone(),
+        initial_balance: args.balance,
+        contract_size: args.contract_size,
+        leverage: args.leverage,
+        point: 10f64.powi(-(args.digits as i32)),
+        ..SimSettings::default()
+    })));
+
+    let mut publisher = zeromq::PubSocket::new();
+    publisher.bind(&args.pub_address).await?;
+    println!("Tick Publisher bound to {}", args.pub_address);
+
+    let mut responder = zeromq::RepSocket::new();
+    responder.bind(&args.rep_address).await?;
+    println!("Order Responder bound to {}", args.rep_address);
+
+    tokio::spawn(replay_ticks(publisher, broker.clone(), ticks.clone(), ReplayPacing::from(&args)));
+
+    let injections: HashMap<String, u32> = args.injections.iter().cloned().collect();
+    loop {
+        let msg = responder.recv().await?;
+        let text = msg.get(0).and_then(|b| std::str::from_utf8(b).ok()).unwrap_or_default().to_string();
+        println!("Received order request: {}", text);
+
+        if args.reply_delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(args.reply_delay_ms)).await;
+        }
+
+        let response = match serde_json::from_str::<OrderRequest>(&text) {
+            Ok(request) => {
+                let mut response = handle_request(&request.request, &broker, &ticks, &injections, args.digits);
+                response.request_id = request.request_id;
+                response
+            }
+            Err(e) => OrderResponse::failure(format!("Unknown order type: {}", e)),
+        };
+
+        let json = serde_json::to_string(&response)?;
+        responder.send(json.clone().into()).await?;
+        println!("Sent response: {}", json);
+    }
+}
+
+fn default_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            let name = p.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            name.ends_with(".csv") && (name.starts_with("Live_") || name.contains("_TICKS_"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+// ============================================================================
+// Tick Replay (PUB)
+// ============================================================================
+
+#[derive(Clone, Copy)]
+struct ReplayPacing {
+    speed: f64,
+    min_gap: Duration,
+    max_gap: Duration,
+    once: bool,
+}
+
+impl From<&Args> for ReplayPacing {
+    fn from(args: &Args) -> Self {
+        Self {
+            speed: args.speed.max(0.001),
+            min_gap: Duration::from_millis(args.min_gap_ms),
+            max_gap: Duration::from_millis(args.max_gap_ms),
+            once: args.once,
+        }
+    }
+}
+
+async fn replay_ticks(
+    mut publisher: zeromq::PubSocket,
+    broker: Arc<Mutex<SimBroker>>,
+    ticks: Arc<Vec<TickRow>>,
+    pacing: ReplayPacing,
+) {
+    // Shift looped passes forward in time so the chart keeps moving right
+    let span = ticks.last().map(|t| t.time).unwrap_or(0) - ticks.first().map(|t| t.time).unwrap_or(0) + 1;
+    let mut offset = 0;
+
+    loop {
+        for (i, row) in ticks.iter().enumerate() {
+            let json = {
+                let mut broker = broker.lock().unwrap();
+                let closed_before = broker.closed_trades().len();
+                for (ticket, result) in broker.update_price(row.bid, row.ask, row.time + offset) {
+                    match result {
+                        Ok(()) => println!("Pending order #{} triggered", ticket),
+                        Err(e) => println!("Pending order #{} dropped: {}", ticket, e),
+                    }
+                }
+                for trade in &broker.closed_trades()[closed_before..] {
+                    println!("Position #{} closed by {:?} at {}", trade.ticket, trade.reason, trade.close_price);
+                }
+                serde_json::to_string(&broker.tick_data(row.volume)).unwrap_or_default()
+            };
+            if let Err(e) = publisher.send(json.into()).await {
+                eprintln!("Publish error: {}", e);
+            }
+
+            let gap = match ticks.get(i + 1) {
+                Some(next) if next.time > row.time => {
+                    Duration::from_secs_f64((next.time - row.time) as f64 / pacing.speed)
+                }
+                _ => pacing.min_gap,
+            };
+            tokio::time::sleep(gap.clamp(pacing.min_gap.min(pacing.max_gap), pacing.max_gap)).await;
+        }
+
+        if pacing.once {
+            println!("Replay finished");
+            return;
+        }
+        offset += span;
+    }
+}
+
+// ============================================================================
+// Order Handling (REP)
+// ============================================================================
+
+fn handle_request(
+    request: &Request,
+    broker: &Mutex<SimBroker>,
+    ticks: &[TickRow],
+    injections: &HashMap<String, u32>,
+    digits: u32,
+) -> OrderResponse {
+    let is_query = matches!(request, Request::GetPositions { .. } | Request::DownloadHistory { .. });
+    let injected = injections
+        .get(request.kind())
+        .or_else(|| if is_query { None } else { injections.get("*") });
+    if let Some(code) = injected.and_then(|c| TradeError::from_code(*c)) {
+        return sim::reject(code);
+    }
+
+    match request {
+        Request::DownloadHistory { timeframe, start, end, mode, .. } => {
+            let start = history::parse_time(start).unwrap_or(i64::MIN);
+            let end = history::parse_time(end).unwrap_or(i64::MAX);
+            let range = ticks.iter().filter(|t| t.time >= start && t.time <= end);
+            download_history(range, *timeframe, *mode, digits)
+        }
+        _ => broker.lock().unwrap().execute(request),
+    }
+}
+
+/// Builds the EA's `N records||CSV_DATA||row|NL|row...` reply.
+fn download_history<'a>(
+    ticks: impl Iterator<Item = &'a TickRow>,
+    timeframe: sum3api::Timeframe,
+    mode: HistoryMode,
+    digits: u32,
+) -> OrderResponse {
+    const NL: &str = "|NL|";
+    let d = digits as usize;
+    let mut csv = String::new();
+
+    let count = match mode {
+        HistoryMode::Ticks => {
+            csv.push_str("Time,Bid,Ask,Volume");
+            csv.push_str(NL);
+            let rows: Vec<&TickRow> = ticks.take(50_000).collect();
+            for t in &rows {
+                csv.push_str(&format!(
+                    "{},{:.d$},{:.d$},{}{}",
+                    history::format_time(t.time, true), t.bid, t.ask, t.volume, NL
+                ));
+            }
+            rows.len()
+        }
+        HistoryMode::Ohlc => {
+            csv.push_str("Time,Open,High,Low,Close,TickVol,Spread");
+            csv.push_str(NL);
+            let point = 10f64.powi(digits as i32);
+            let rates = bars::aggregate(timeframe, ticks.map(|t| (t.time, t.bid, t.ask)));
+            for b in rates.iter().take(100_000) {
+                csv.push_str(&format!(
+                    "{},{:.d$},{:.d$},{:.d$},{:.d$},{},{}{}",
+                    history::format_time(b.time, false),
+                    b.open, b.high, b.low, b.close,
+                    b.tick_volume,
+                    (b.spread * point).round() as i64,
+                    NL
+                ));
+            }
+            rates.len().min(100_000)
+        }
+    };
+
+    if count == 0 {
+        return OrderResponse::failure("No data found for period".to_string());
+    }
+    OrderResponse::success_message(format!("{} records{}{}", count, CSV_DATA_MARKER, csv))
+}
@@ -0,0 +1,149 @@
+//+------------------------------------------------------------------+
+//|                                               analytics_panel.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use eframe::egui;
+use egui_plot::{Bar, BarChart, Line, Plot, PlotPoints};
+use std::io;
+use std::path::{Path, PathBuf};
+use sum3api::analytics::{self, EquityPoint, PerformanceReport, TradeRecord};
+use sum3api::history;
+
+// ============================================================================
+// Report View
+// ============================================================================
+
+/// Metrics grid, equity curve and R-multiple histogram. `id` keeps the
+/// plots of several open reports apart.
+pub fn show_report(ui: &mut egui::Ui, id: &str, report: &PerformanceReport, equity: &[EquityPoint]) {
+    let r = report;
+    let profit_color = |value: f64| {
+        if value >= 0.0 { egui::Color32::from_rgb(100, 200, 100) } else { egui::Color32::from_rgb(255, 100, 100) }
+    };
+    egui::Grid::new(format!("{}_metrics", id)).num_columns(4).spacing([16.0, 4.0]).show(ui, |ui| {
+        ui.label("Net P&L:");
+        ui.colored_label(profit_color(r.net_profit), format!("{:+.2}", r.net_profit));
+        ui.label("Final equity:");
+        ui.label(format!("{:.2}", r.final_equity));
+        ui.end_row();
+        ui.label("Trades:");
+        ui.label(format!("{} ({}W / {}L)", r.trades, r.wins, r.losses));
+        ui.label("Win rate:");
+        ui.label(format!("{:.1}%", r.win_rate * 100.0));
+        ui.end_row();
+        ui.label("Profit factor:");
+        ui.label(r.profit_factor.map_or("∞".to_string(), |pf| format!("{:.2}", pf)));
+        ui.label("Expectancy:");
+        ui.colored_label(profit_color(r.expectancy), format!("{:+.2} / trade", r.expectancy));
+        ui.end_row();
+        ui.label("Avg win / loss:");
+        ui.label(format!("{:.2} / {:.2}", r.avg_win, r.avg_loss));
+        ui.label("Largest win / loss:");
+        ui.label(format!("{:.2} / {:.2}", r.largest_win, r.largest_loss));
+        ui.end_row();
+        ui.label("Max drawdown:");
+        ui.label(format!("{:.2} ({:.1}%)", r.max_drawdown, r.max_drawdown_pct * 100.0));
+        ui.label("DD duration:");
+        ui.label(analytics::format_duration(r.max_drawdown_duration));
+        ui.end_row();
+        ui.label("Sharpe:");
+        ui.label(format!("{:.2}", r.sharpe));
+        ui.label("Sortino:");
+        ui.label(format!("{:.2}", r.sortino));
+        ui.end_row();
+        ui.label("Average R:");
+        ui.label(format!("{:+.2}R", r.avg_r));
+        ui.label("With a stop:");
+        ui.label(format!("{} trades", r.r_trades));
+        ui.end_row();
+    });
+
+    ui.columns(2, |columns| {
+        let equity_points: PlotPoints = equity.iter().map(|p| [p.time as f64, p.equity]).collect();
+        let balance_points: PlotPoints = equity.iter().map(|p| [p.time as f64, p.balance]).collect();
+        Plot::new(format!("{}_equity", id))
+            .height(160.0)
+            .legend(egui_plot::Legend::default())
+            .x_axis_formatter(|mark, _width, _range| history::format_time(mark.value as i64, false))
+            .label_formatter(|name, point| {
+                format!("{}\n{}\n{:.2}", name, history::format_time(point.x as i64, false), point.y)
+            })
+            .show(&mut columns[0], |plot_ui| {
+                plot_ui.line(Line::new(equity_points).name("Equity").color(egui::Color32::from_rgb(100, 180, 255)));
+                plot_ui.line(Line::new(balance_points).name("Balance").color(egui::Color32::from_rgb(100, 200, 100)));
+            });
+
+        let bars: Vec<Bar> = r
+            .r_distribution
+            .iter()
+            .enumerate()
+            .map(|(i, bucket)| Bar::new(i as f64, bucket.count as f64).name(&bucket.label).width(0.8))
+            .collect();
+        let labels: Vec<String> = r.r_distribution.iter().map(|b| b.label.clone()).collect();
+        Plot::new(format!("{}_r", id))
+            .height(160.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .x_axis_formatter(move |mark, _width, _range| {
+                let i = mark.value.round();
+                match (i - mark.value).abs() < 1e-6 && i >= 0.0 {
+                    true => labels.get(i as usize).cloned().unwrap_or_default(),
+                    false => String::new(),
+                }
+            })
+            .show(&mut columns[1], |plot_ui| {
+                plot_ui.bar_chart(BarChart::new(bars).name("R-multiples").color(egui::Color32::from_rgb(180, 140, 255)));
+            });
+    });
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+/// A report with what it was computed from, ready to save.
+pub struct ReportExport<'a> {
+    /// File name part, e.g. the strategy or symbol
+    pub name: &'a str,
+    pub title: &'a str,
+    pub report: &'a PerformanceReport,
+    pub trades: &'a [TradeRecord],
+    pub equity: &'a [EquityPoint],
+}
+
+/// Writes `Analytics_<name>_<timestamp>.json` and `.html` into `dir`.
+pub fn export(dir: &Path, data: &ReportExport) -> io::Result<(PathBuf, PathBuf)> {
+    let stem = format!(
+        "Analytics_{}_{}",
+        data.name.replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    );
+    let json_path = dir.join(format!("{}.json", stem));
+    let html_path = dir.join(format!("{}.html", stem));
+    let json = analytics::to_json(data.title, data.report, data.trades, data.equity).map_err(io::Error::other)?;
+    std::fs::write(&json_path, json)?;
+    std::fs::write(&html_path, analytics::to_html(data.title, data.report, data.trades, data.equity))?;
+    Ok((json_path, html_path))
+}
+
+/// Export button; the outcome goes to `status`.
+pub fn export_buttons(ui: &mut egui::Ui, dir: &Path, data: &ReportExport, status: &mut Option<String>) {
+    ui.horizontal(|ui| {
+        if ui.button("💾 Export JSON + HTML").on_hover_text(format!("Into {}", dir.display())).clicked() {
+            *status = Some(match export(dir, data) {
+                Ok((json, html)) => format!("✓ Saved {} and {}", file_name(&json), file_name(&html)),
+                Err(e) => format!("✗ Export failed: {}", e),
+            });
+        }
+        if let Some(status) = status {
+            ui.weak(status.as_str());
+        }
+    });
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned())
+}
@@ -0,0 +1,257 @@
+//+------------------------------------------------------------------+
+//|                                                backtest_panel.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::analytics_panel::{self, ReportExport};
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use sum3api::backtest::{self, BacktestReport, BacktestSettings};
+use sum3api::history;
+use sum3api::sim::{CloseReason, SimSettings};
+use sum3api::strategy::{self, StrategyFactory};
+
+/// Trades listed in the window; the rest are only counted.
+const MAX_LISTED_TRADES: usize = 500;
+
+// ============================================================================
+// Test Account
+// ============================================================================
+
+/// Balance and trading costs of a simulated test, shared by the backtest
+/// and optimizer windows.
+pub struct TestAccount {
+    pub balance: f64,
+    // In points of the symbol
+    pub spread_points: f64,
+    pub slippage_points: f64,
+    pub commission_per_lot: f64,
+}
+
+impl TestAccount {
+    pub fn new(balance: f64) -> Self {
+        Self { balance, spread_points: 0.0, slippage_points: 0.0, commission_per_lot: 0.0 }
+    }
+
+    /// Grid rows; call inside a two-column grid.
+    pub fn show_rows(&mut self, ui: &mut egui::Ui) {
+        ui.label("Balance:");
+        ui.add(egui::DragValue::new(&mut self.balance).speed(100.0).clamp_range(1.0..=f64::MAX));
+        ui.end_row();
+        ui.label("Spread (points):").on_hover_text("For OHLC bars whose Spread column is 0");
+        ui.add(egui::DragValue::new(&mut self.spread_points).speed(1.0).clamp_range(0.0..=f64::MAX));
+        ui.end_row();
+        ui.label("Slippage (points):").on_hover_text("Against every fill, opening and closing");
+        ui.add(egui::DragValue::new(&mut self.slippage_points).speed(1.0).clamp_range(0.0..=f64::MAX));
+        ui.end_row();
+        ui.label("Commission / lot:").on_hover_text("Round turn, charged on close");
+        ui.add(egui::DragValue::new(&mut self.commission_per_lot).speed(0.1).clamp_range(0.0..=f64::MAX));
+        ui.end_row();
+    }
+
+    /// Settings for a test over `path`; `base` carries the symbol rules
+    /// (lots, point, contract size, leverage).
+    pub fn settings(&self, path: &Path, base: &SimSettings) -> BacktestSettings {
+        BacktestSettings {
+            sim: SimSettings {
+                symbol: history::symbol_from_file_name(path).unwrap_or_else(|| base.symbol.clone()),
+                initial_balance: self.balance,
+                slippage: self.slippage_points * base.point,
+                commission_per_lot: self.commission_per_lot,
+                ..base.clone()
+            },
+            timeframe: history::timeframe_from_file_name(path),
+            default_spread: self.spread_points * base.point,
+        }
+    }
+}
+
+/// File name of the selected CSV, for the window header.
+pub fn show_file(ui: &mut egui::Ui, path: Option<&Path>, hint: &str) {
+    let file = path.and_then(|p| p.file_name()).map(|n| n.to_string_lossy().into_owned());
+    ui.label(format!("File: {}", file.as_deref().unwrap_or(hint)));
+}
+
+// ============================================================================
+// Backtest Window
+// ============================================================================
+
+/// Runs a catalog strategy over a History/Live CSV on a background thread
+/// and shows the result.
+pub struct BacktestPanel {
+    pub open: bool,
+    path: Option<PathBuf>,
+    catalog: Vec<StrategyFactory>,
+    strategy: usize,
+    params: Vec<f64>,
+    account: TestAccount,
+    running: Option<mpsc::Receiver<Result<BacktestReport, String>>>,
+    report: Option<BacktestReport>,
+    error: Option<String>,
+    output_dir: PathBuf,
+    export_status: Option<String>,
+}
+
+impl BacktestPanel {
+    /// Reports are exported into `output_dir`.
+    pub fn new(balance: f64, output_dir: PathBuf) -> Self {
+        let catalog = strategy::catalog();
+        let params = catalog.first().map(|f| f.defaults()).unwrap_or_default();
+        Self {
+            open: false,
+            path: None,
+            catalog,
+            strategy: 0,
+            params,
+            account: TestAccount::new(balance),
+            running: None,
+            report: None,
+            error: None,
+            output_dir,
+            export_status: None,
+        }
+    }
+
+    /// Opens the window on a CSV file.
+    pub fn select_file(&mut self, path: &Path) {
+        self.path = Some(path.to_path_buf());
+        self.open = true;
+    }
+
+    /// `base` carries the symbol rules (lots, point, contract size, leverage)
+    /// the test runs with.
+    pub fn show(&mut self, ctx: &egui::Context, base: &SimSettings) {
+        if let Some(result) = self.running.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            self.running = None;
+            match result {
+                Ok(report) => {
+                    self.report = Some(report);
+                    self.error = None;
+                    self.export_status = None;
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+
+        let mut open = self.open;
+        egui::Window::new("🧪 Backtest")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                self.show_settings(ui, base);
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("✗ {}", error));
+                }
+                if let Some(report) = &self.report {
+                    ui.separator();
+                    show_report(ui, report, &self.output_dir, &mut self.export_status);
+                }
+            });
+        self.open = open;
+    }
+
+    fn show_settings(&mut self, ui: &mut egui::Ui, base: &SimSettings) {
+        show_file(ui, self.path.as_deref(), "none (use 🧪 on a file tab)");
+
+        egui::Grid::new("backtest_settings").num_columns(2).spacing([10.0, 4.0]).show(ui, |ui| {
+            ui.label("Strategy:");
+            let before = self.strategy;
+            egui::ComboBox::from_id_source("backtest_strategy")
+                .selected_text(self.catalog.get(self.strategy).map_or("-", |f| f.name))
+                .show_ui(ui, |ui| {
+                    for (i, factory) in self.catalog.iter().enumerate() {
+                        ui.selectable_value(&mut self.strategy, i, factory.name);
+                    }
+                });
+            if self.strategy != before {
+                self.params = self.catalog[self.strategy].defaults();
+            }
+            ui.end_row();
+
+            if let Some(factory) = self.catalog.get(self.strategy) {
+                for (spec, value) in factory.params.iter().zip(&mut self.params) {
+                    ui.label(format!("{}:", spec.name));
+                    ui.add(egui::DragValue::new(value).speed(spec.step).clamp_range(spec.min..=spec.max));
+                    ui.end_row();
+                }
+            }
+
+            self.account.show_rows(ui);
+        });
+
+        ui.horizontal(|ui| {
+            let ready = self.path.is_some() && self.running.is_none();
+            if ui.add_enabled(ready, egui::Button::new("▶ Run")).clicked() {
+                self.start(base);
+            }
+            if self.running.is_some() {
+                ui.spinner();
+                ui.label("Running...");
+            }
+        });
+    }
+
+    fn start(&mut self, base: &SimSettings) {
+        let (Some(path), Some(factory)) = (self.path.clone(), self.catalog.get(self.strategy).copied()) else {
+            return;
+        };
+        let settings = self.account.settings(&path, base);
+        let params = self.params.clone();
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = history::read_csv(&path)
+                .map(|data| backtest::run((factory.build)(&params), &data, &settings))
+                .map_err(|e| e.to_string());
+            let _ = tx.send(result);
+        });
+        self.running = Some(rx);
+        self.error = None;
+    }
+}
+
+fn show_report(ui: &mut egui::Ui, report: &BacktestReport, output_dir: &Path, export_status: &mut Option<String>) {
+    ui.heading(&report.strategy);
+    analytics_panel::show_report(ui, "backtest", &report.report, &report.equity);
+    ui.label(format!(
+        "Commission {:.2} · {} ticks · {} rejected requests",
+        report.commission, report.ticks, report.rejected
+    ));
+    let title = format!("Backtest {}", report.strategy);
+    let export = ReportExport {
+        name: &report.strategy,
+        title: &title,
+        report: &report.report,
+        trades: &report.records,
+        equity: &report.equity,
+    };
+    analytics_panel::export_buttons(ui, output_dir, &export, export_status);
+
+    ui.label(format!("Trades ({} shown)", report.trades.len().min(MAX_LISTED_TRADES)));
+    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+        egui::Grid::new("backtest_trades").num_columns(7).striped(true).show(ui, |ui| {
+            for header in ["Opened", "Side", "Lots", "Open", "Close", "Net", "Exit"] {
+                ui.strong(header);
+            }
+            ui.end_row();
+            for trade in report.trades.iter().take(MAX_LISTED_TRADES) {
+                ui.label(history::format_time(trade.open_time, true));
+                ui.label(trade.side.as_str());
+                ui.label(format!("{:.2}", trade.volume));
+                ui.label(format!("{:.5}", trade.open_price));
+                ui.label(format!("{:.5}", trade.close_price));
+                let net = trade.net_profit();
+                let color = if net >= 0.0 { egui::Color32::from_rgb(100, 200, 100) } else { egui::Color32::from_rgb(255, 100, 100) };
+                ui.colored_label(color, format!("{:+.2}", net));
+                ui.label(match trade.reason {
+                    CloseReason::Manual => "close",
+                    CloseReason::StopLoss => "SL",
+                    CloseReason::TakeProfit => "TP",
+                });
+                ui.end_row();
+            }
+        });
+    });
+}
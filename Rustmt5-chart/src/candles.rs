@@ -0,0 +1,117 @@
+//+------------------------------------------------------------------+
+//|                                                       candles.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::tick_store::Tick;
+use sum3api::bars::{Bar, BarAggregator};
+use sum3api::Timeframe;
+
+// ============================================================================
+// Live Candle Series
+// ============================================================================
+
+/// OHLC bars of one timeframe, built tick by tick from the live feed.
+/// Only the newest `keep` closed bars are held, like the tick store.
+pub struct CandleSeries {
+    aggregator: BarAggregator,
+    closed: Vec<Bar>,
+    keep: usize,
+}
+
+impl CandleSeries {
+    pub fn new(timeframe: Timeframe, keep: usize) -> Self {
+        Self { aggregator: BarAggregator::new(timeframe), closed: Vec::new(), keep: keep.max(1) }
+    }
+
+    /// Series for a timeframe, rebuilt from the ticks already received.
+    pub fn from_ticks<'a>(timeframe: Timeframe, keep: usize, ticks: impl Iterator<Item = &'a Tick>) -> Self {
+        let mut series = Self::new(timeframe, keep);
+        for tick in ticks {
+            series.push(tick);
+        }
+        series
+    }
+
+    pub fn timeframe(&self) -> Timeframe {
+        self.aggregator.timeframe()
+    }
+
+    /// Adds a tick; returns the bar it closed, if any.
+    pub fn push(&mut self, tick: &Tick) -> Option<Bar> {
+        let closed = self.aggregator.push(tick.time, tick.bid, tick.ask);
+        self.closed.extend(closed);
+        let excess = self.closed.len().saturating_sub(self.keep);
+        self.closed.drain(..excess);
+        closed
+    }
+
+    /// Starts over with no bars, keeping the timeframe.
+    pub fn clear(&mut self) {
+        *self = Self::new(self.timeframe(), self.keep);
+    }
+
+    /// Completed bars, oldest first.
+    pub fn closed(&self) -> &[Bar] {
+        &self.closed
+    }
+
+    /// The bar still being built from incoming ticks.
+    pub fn forming(&self) -> Option<&Bar> {
+        self.aggregator.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-01-26 00:00:00 UTC
+    const T0: i64 = 1769385600;
+
+    fn tick(time: i64, bid: f64) -> Tick {
+        Tick { time, bid, ask: bid + 0.5, volume: 1 }
+    }
+
+    #[test]
+    fn closes_bars_at_timeframe_boundaries() {
+        let mut series = CandleSeries::new(Timeframe::M5, 10);
+        assert_eq!(series.push(&tick(T0 + 10, 1.0)), None);
+        assert_eq!(series.push(&tick(T0 + 299, 2.0)), None);
+        assert!(series.closed().is_empty());
+        assert_eq!(series.forming().map(|b| b.close), Some(2.0));
+
+        let closed = series.push(&tick(T0 + 300, 3.0)).expect("bar closed");
+        assert_eq!((closed.time, closed.open, closed.close), (T0, 1.0, 2.0));
+        assert_eq!(series.closed(), &[closed]);
+        assert_eq!(series.forming().map(|b| (b.time, b.open)), Some((T0 + 300, 3.0)));
+    }
+
+    #[test]
+    fn late_tick_overwrites_forming_close() {
+        let mut series = CandleSeries::new(Timeframe::M1, 10);
+        series.push(&tick(T0, 1.0));
+        series.push(&tick(T0 + 60, 2.0));
+        assert_eq!(series.push(&tick(T0 + 30, 0.5)), None);
+
+        assert_eq!(series.closed().len(), 1);
+        assert_eq!(series.closed()[0].close, 1.0);
+        let forming = series.forming().unwrap();
+        assert_eq!((forming.time, forming.close, forming.low), (T0 + 60, 0.5, 0.5));
+    }
+
+    #[test]
+    fn keeps_only_newest_closed_bars() {
+        let ticks: Vec<Tick> = (0..6).map(|i| tick(T0 + i * 60, i as f64)).collect();
+        let mut series = CandleSeries::from_ticks(Timeframe::M1, 3, ticks.iter());
+        let opens: Vec<f64> = series.closed().iter().map(|b| b.open).collect();
+        assert_eq!(opens, vec![2.0, 3.0, 4.0]);
+        assert_eq!(series.forming().map(|b| b.open), Some(5.0));
+
+        series.clear();
+        assert!(series.closed().is_empty());
+        assert!(series.forming().is_none());
+        assert_eq!(series.timeframe(), Timeframe::M1);
+    }
+}
@@ -0,0 +1,250 @@
+//+------------------------------------------------------------------+
+//|                                                         chart.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::tick_store::Tick;
+use crate::time_axis::{DisplayZone, TimeAxis};
+use eframe::egui;
+use egui_plot::{Bar as PlotBar, BarChart, BoxElem, BoxPlot, BoxSpread, Line, Plot, PlotPoints, PlotUi};
+use sum3api::bars::Bar;
+use sum3api::indicators::{IndicatorPoint, IndicatorSpec};
+
+/// With "Hide gaps" on, pauses in the data longer than this (seconds) are
+/// squeezed out of the time axis; for candles at least two bars.
+const MIN_HIDDEN_GAP: i64 = 900;
+
+/// Width (seconds) a hidden gap keeps on the tick chart.
+const LINE_GAP_WIDTH: i64 = 60;
+
+/// Most tick points drawn per line; denser views are thinned out.
+const MAX_LINE_POINTS: usize = 5000;
+
+/// Height of each indicator panel under the price chart.
+const SUB_PANEL_HEIGHT: f32 = 110.0;
+
+/// The price chart keeps at least this height next to indicator panels.
+const MIN_PRICE_HEIGHT: f32 = 150.0;
+
+// ============================================================================
+// Chart Options
+// ============================================================================
+
+// What a chart draws
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChartMode {
+    Line,
+    Candles,
+}
+
+/// Display options shared by the live chart and file tabs.
+#[derive(Clone, Copy, Debug)]
+pub struct ChartView {
+    pub zone: DisplayZone,
+    pub hide_gaps: bool,
+    // Broker server time minus UTC
+    pub server_offset: i64,
+    // Off while the pointer is on a draggable trade line
+    pub allow_pan: bool,
+}
+
+/// Data for one chart: ticks for line mode, or bars of `bar_seconds` for
+/// candle mode (the last one still forming when `forming` is set).
+pub struct ChartData<'a> {
+    pub mode: ChartMode,
+    pub ticks: &'a [Tick],
+    pub bars: &'a [Bar],
+    pub bar_seconds: i64,
+    pub forming: bool,
+    pub indicators: &'a [IndicatorPlot<'a>],
+}
+
+/// An indicator computed over the chart's ticks or bars.
+pub struct IndicatorPlot<'a> {
+    pub spec: IndicatorSpec,
+    pub color: egui::Color32,
+    // On the price chart, or in a panel below it
+    pub overlay: bool,
+    pub points: &'a [IndicatorPoint],
+    // Output with the forming bar included, drawn after `points`
+    pub forming: Option<IndicatorPoint>,
+}
+
+impl ChartData<'_> {
+    /// First and last data time, or `None` when there is nothing to draw.
+    pub fn time_range(&self) -> Option<(i64, i64)> {
+        match self.mode {
+            ChartMode::Line => Some((self.ticks.first()?.time, self.ticks.last()?.time)),
+            ChartMode::Candles => Some((self.bars.first()?.time, self.bars.last()?.time + self.bar_seconds)),
+        }
+    }
+}
+
+// ============================================================================
+// Price Chart
+// ============================================================================
+
+/// Draws a price chart on a real time axis, with a linked panel below it
+/// for each non-overlay indicator. `overlays` adds extra items (positions,
+/// breaklines) positioned through the same axis.
+pub fn show_chart(
+    ui: &mut egui::Ui,
+    id: &str,
+    data: &ChartData,
+    view: ChartView,
+    overlays: impl FnOnce(&mut PlotUi, &TimeAxis),
+) {
+    let (times, bar_span, gap_width): (Vec<i64>, i64, i64) = match data.mode {
+        ChartMode::Line => (data.ticks.iter().map(|t| t.time).collect(), 0, LINE_GAP_WIDTH),
+        ChartMode::Candles => (data.bars.iter().map(|b| b.time).collect(), data.bar_seconds, data.bar_seconds),
+    };
+    let max_gap = view.hide_gaps.then_some(MIN_HIDDEN_GAP.max(2 * bar_span));
+    let axis = TimeAxis::new(&times, max_gap, gap_width, view.zone, view.server_offset);
+    // Candles sit in the middle of their period; indicator points follow them
+    let x_offset = bar_span as f64 / 2.0;
+
+    let panels: Vec<&IndicatorPlot> = data.indicators.iter().filter(|i| !i.overlay).collect();
+    let panel_space = panels.len() as f32 * (SUB_PANEL_HEIGHT + ui.spacing().item_spacing.y);
+    let price_height = (ui.available_height() - panel_space).max(MIN_PRICE_HEIGHT);
+
+    time_plot(id, id, &axis, view).height(price_height).show(ui, |plot_ui| {
+        match data.mode {
+            ChartMode::Line => draw_ticks(plot_ui, &axis, data.ticks),
+            ChartMode::Candles => draw_candles(plot_ui, &axis, data),
+        }
+        for indicator in data.indicators.iter().filter(|i| i.overlay) {
+            draw_indicator(plot_ui, &axis, x_offset, bar_span, indicator);
+        }
+
+        // Mark collapsed gaps
+        for x in axis.gaps() {
+            plot_ui.vline(
+                egui_plot::VLine::new(x)
+                    .color(egui::Color32::from_gray(90))
+                    .style(egui_plot::LineStyle::Dotted { spacing: 6.0 })
+            );
+        }
+
+        overlays(plot_ui, &axis);
+    });
+
+    for (i, indicator) in panels.into_iter().enumerate() {
+        time_plot(&format!("{}_indicator_{}", id, i), id, &axis, view)
+            .height(SUB_PANEL_HEIGHT)
+            .show(ui, |plot_ui| {
+                for level in indicator.spec.levels() {
+                    plot_ui.hline(
+                        egui_plot::HLine::new(*level)
+                            .color(egui::Color32::from_gray(90))
+                            .style(egui_plot::LineStyle::Dashed { length: 6.0 }),
+                    );
+                }
+                draw_indicator(plot_ui, &axis, x_offset, bar_span, indicator);
+            });
+    }
+}
+
+/// Plot on the chart's time axis. The price chart and its indicator
+/// panels pan, zoom and show the cursor together through `group`.
+fn time_plot(id: &str, group: &str, axis: &TimeAxis, view: ChartView) -> Plot {
+    let (label_axis, marks_axis, format_axis) = (axis.clone(), axis.clone(), axis.clone());
+    Plot::new(id)
+        .link_axis(egui::Id::new(group), true, false)
+        .link_cursor(egui::Id::new(group), true, false)
+        .legend(egui_plot::Legend::default())
+        .allow_boxed_zoom(true)
+        .allow_drag(view.allow_pan)
+        .allow_scroll(true)
+        .allow_zoom(true)
+        .x_grid_spacer(move |input| marks_axis.grid_marks(input))
+        .x_axis_formatter(move |mark, _width, _range| format_axis.format_mark(mark))
+        .label_formatter(move |name, point| {
+            let time = label_axis.format_time(label_axis.to_time(point.x));
+            if name.is_empty() {
+                format!("{}\n{:.5}", time, point.y)
+            } else {
+                format!("{}\n{}\n{:.5}", name, time, point.y)
+            }
+        })
+}
+
+fn draw_ticks(plot_ui: &mut PlotUi, axis: &TimeAxis, ticks: &[Tick]) {
+    // Only the ticks in view, thinned to a few thousand points
+    let bounds = plot_ui.plot_bounds();
+    let (lo, hi) = (axis.to_time(bounds.min()[0]), axis.to_time(bounds.max()[0]));
+    let start = ticks.partition_point(|t| t.time < lo).saturating_sub(1);
+    let end = (ticks.partition_point(|t| t.time <= hi) + 1).min(ticks.len());
+    let in_view = &ticks[start.min(end)..end];
+    let stride = (in_view.len() / MAX_LINE_POINTS).max(1);
+
+    let bid_points: PlotPoints = in_view.iter().step_by(stride).map(|t| [axis.to_x(t.time), t.bid]).collect();
+    let ask_points: PlotPoints = in_view.iter().step_by(stride).map(|t| [axis.to_x(t.time), t.ask]).collect();
+
+    plot_ui.line(Line::new(bid_points).name("Bid").color(egui::Color32::from_rgb(100, 200, 100)));
+    plot_ui.line(Line::new(ask_points).name("Ask").color(egui::Color32::from_rgb(200, 100, 100)));
+}
+
+/// Lines (bars for a histogram output) of the indicator points in view.
+fn draw_indicator(plot_ui: &mut PlotUi, axis: &TimeAxis, x_offset: f64, bar_span: i64, indicator: &IndicatorPlot) {
+    let bounds = plot_ui.plot_bounds();
+    let (lo, hi) = (axis.to_time(bounds.min()[0]), axis.to_time(bounds.max()[0]));
+    let points = indicator.points;
+    let start = points.partition_point(|p| p.time < lo).saturating_sub(1);
+    let end = (points.partition_point(|p| p.time <= hi) + 1).min(points.len());
+    let in_view = &points[start.min(end)..end];
+    let stride = (in_view.len() / MAX_LINE_POINTS).max(1);
+    let visible = || in_view.iter().step_by(stride).chain(indicator.forming.iter());
+
+    let outputs = indicator.spec.outputs();
+    let label = indicator.spec.label();
+    for (j, output) in outputs.iter().enumerate() {
+        let name = if outputs.len() == 1 { label.clone() } else { format!("{} {}", label, output) };
+        // The first output in full colour, the others (bands, signal) faded
+        let color = if j == 0 { indicator.color } else { indicator.color.gamma_multiply(0.55) };
+        if *output == "Histogram" {
+            let width = if bar_span > 0 { bar_span as f64 * 0.6 } else { 1.0 };
+            let bars: Vec<PlotBar> = visible()
+                .map(|p| PlotBar::new(axis.to_x(p.time) + x_offset, p.values[j]).width(width))
+                .collect();
+            plot_ui.bar_chart(BarChart::new(bars).name(name).color(color));
+        } else {
+            let line: PlotPoints = visible().map(|p| [axis.to_x(p.time) + x_offset, p.values[j]]).collect();
+            plot_ui.line(Line::new(line).name(name).color(color));
+        }
+    }
+}
+
+fn draw_candles(plot_ui: &mut PlotUi, axis: &TimeAxis, data: &ChartData) {
+    let forming_index = data.forming.then(|| data.bars.len().saturating_sub(1));
+    let half = data.bar_seconds as f64 / 2.0;
+    let boxes: Vec<BoxElem> = data.bars
+        .iter()
+        .enumerate()
+        .map(|(i, bar)| {
+            let mut color = if bar.close >= bar.open {
+                egui::Color32::from_rgb(100, 200, 100)
+            } else {
+                egui::Color32::from_rgb(255, 100, 100)
+            };
+            // The forming bar is drawn faded until it closes
+            if Some(i) == forming_index {
+                color = color.gamma_multiply(0.5);
+            }
+            let (body_low, body_high) = (bar.open.min(bar.close), bar.open.max(bar.close));
+            BoxElem::new(
+                axis.to_x(bar.time) + half,
+                BoxSpread::new(bar.low, body_low, bar.close, body_high, bar.high),
+            )
+            .name(format!(
+                "O {:.5}\nH {:.5}\nL {:.5}\nC {:.5}\nTicks {}",
+                bar.open, bar.high, bar.low, bar.close, bar.tick_volume
+            ))
+            .box_width(data.bar_seconds as f64 * 0.7)
+            .whisker_width(0.0)
+            .fill(color)
+            .stroke(egui::Stroke::new(1.0, color))
+        })
+        .collect();
+    plot_ui.box_plot(BoxPlot::new(boxes).name("Candles"));
+}
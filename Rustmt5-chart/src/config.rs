@@ -0,0 +1,482 @@
+//+------------------------------------------------------------------+
+//|                                                        config.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use clap::Parser;
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sum3api::risk::RiskLimits;
+use sum3api::sizing::RiskBase;
+use sum3api::{ClientSettings, FeedSettings};
+use crate::time_axis::DisplayZone;
+
+/// Config file picked up from the working directory when `--config` is not given.
+pub const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+// ============================================================================
+// Command Line
+// ============================================================================
+
+#[derive(Debug, Parser)]
+#[command(name = "Rustmt5-chart", about = "Rust + ZMQ + MT5 Trading Chart")]
+pub struct Cli {
+    /// Path to a TOML config file (default: ./config.toml if present)
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+
+    /// ZMQ SUB address of the EA tick publisher
+    #[arg(long)]
+    pub tick_address: Option<String>,
+
+    /// ZMQ REQ address of the EA order handler
+    #[arg(long)]
+    pub order_address: Option<String>,
+
+    /// Folder for live recordings and history downloads
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+
+    /// Number of most recent ticks drawn on the chart
+    #[arg(long)]
+    pub tick_window: Option<usize>,
+
+    /// Number of ticks kept in memory (oldest are dropped)
+    #[arg(long)]
+    pub tick_buffer: Option<usize>,
+
+    /// Initial lot size in the Trade Controls panel
+    #[arg(long)]
+    pub default_lot: Option<f64>,
+
+    /// Time zone of the chart axis labels
+    #[arg(long, value_enum)]
+    pub display_zone: Option<DisplayZone>,
+
+    /// Start in paper trading mode (orders go to the simulated broker)
+    #[arg(long)]
+    pub paper: bool,
+
+    /// Starting balance of the paper trading account
+    #[arg(long)]
+    pub paper_balance: Option<f64>,
+
+    /// Seconds without a tick before the feed is shown as stale
+    #[arg(long)]
+    pub stale_after_secs: Option<u64>,
+
+    /// Seconds without a tick before the subscriber reconnects
+    #[arg(long)]
+    pub reconnect_after_secs: Option<u64>,
+
+    /// Seconds to wait for the EA to answer an order request
+    #[arg(long)]
+    pub order_timeout_secs: Option<u64>,
+}
+
+// ============================================================================
+// Settings
+// ============================================================================
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppConfig {
+    pub tick_address: String,
+    pub order_address: String,
+    pub output_dir: PathBuf,
+    pub tick_window: usize,
+    pub tick_buffer: usize,
+    pub default_lot: f64,
+    pub risk_percent: f64,
+    pub risk_base: RiskBase,
+    pub display_zone: DisplayZone,
+    pub paper_trading: bool,
+    pub paper_balance: f64,
+    pub paper_leverage: f64,
+    pub paper_contract_size: f64,
+    pub max_order_lots: f64,
+    pub max_open_positions: usize,
+    pub max_symbol_lots: f64,
+    pub max_daily_loss: f64,
+    pub min_free_margin: f64,
+    pub stale_after_secs: u64,
+    pub reconnect_after_secs: u64,
+    pub order_timeout_secs: u64,
+
+    // Where the settings came from, shown in the chart header
+    #[serde(skip)]
+    pub source: String,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            tick_address: "tcp://127.0.0.1:5555".to_string(),
+            order_address: "tcp://127.0.0.1:5556".to_string(),
+            output_dir: PathBuf::from("output"),
+            tick_window: 2000,
+            tick_buffer: 1_000_000,
+            default_lot: 0.01,
+            risk_percent: 1.0,
+            risk_base: RiskBase::Balance,
+            display_zone: DisplayZone::Server,
+            paper_trading: false,
+            paper_balance: 10_000.0,
+            paper_leverage: 100.0,
+            paper_contract_size: 100.0,
+            max_order_lots: 0.0,
+            max_open_positions: 0,
+            max_symbol_lots: 0.0,
+            max_daily_loss: 0.0,
+            min_free_margin: 0.0,
+            stale_after_secs: 5,
+            reconnect_after_secs: 60,
+            order_timeout_secs: 10,
+            source: "built-in defaults".to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid TOML in {}: {}", path.display(), e),
+            ConfigError::Invalid(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl AppConfig {
+    /// Resolve settings: defaults, then the config file, then CLI overrides.
+    pub fn load(cli: &Cli) -> Result<Self, ConfigError> {
+        let mut config = match &cli.config {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => Self::default(),
+        };
+
+        let mut overridden = false;
+        if let Some(addr) = &cli.tick_address {
+            config.tick_address = addr.clone();
+            overridden = true;
+        }
+        if let Some(addr) = &cli.order_address {
+            config.order_address = addr.clone();
+            overridden = true;
+        }
+        if let Some(dir) = &cli.output_dir {
+            config.output_dir = dir.clone();
+            overridden = true;
+        }
+        if let Some(window) = cli.tick_window {
+            config.tick_window = window;
+            overridden = true;
+        }
+        if let Some(size) = cli.tick_buffer {
+            config.tick_buffer = size;
+            overridden = true;
+        }
+        if let Some(lot) = cli.default_lot {
+            config.default_lot = lot;
+            overridden = true;
+        }
+        if let Some(zone) = cli.display_zone {
+            config.display_zone = zone;
+            overridden = true;
+        }
+        if cli.paper {
+            config.paper_trading = true;
+            overridden = true;
+        }
+        if let Some(balance) = cli.paper_balance {
+            config.paper_balance = balance;
+            overridden = true;
+        }
+        if let Some(secs) = cli.stale_after_secs {
+            config.stale_after_secs = secs;
+            overridden = true;
+        }
+        if let Some(secs) = cli.reconnect_after_secs {
+            config.reconnect_after_secs = secs;
+            overridden = true;
+        }
+        if let Some(secs) = cli.order_timeout_secs {
+            config.order_timeout_secs = secs;
+            overridden = true;
+        }
+        if overridden {
+            config.source.push_str(" + CLI");
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+        let mut config: Self = toml::from_str(&text)
+            .map_err(|e| ConfigError::Parse(path.to_path_buf(), e))?;
+        config.source = path.display().to_string();
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        validate_endpoint("tick_address", &self.tick_address)?;
+        validate_endpoint("order_address", &self.order_address)?;
+        if self.tick_address == self.order_address {
+            return Err(ConfigError::Invalid(format!(
+                "tick_address and order_address must differ (both are {})",
+                self.tick_address
+            )));
+        }
+        if self.output_dir.as_os_str().is_empty() {
+            return Err(ConfigError::Invalid("output_dir must not be empty".to_string()));
+        }
+        if self.tick_window < 10 {
+            return Err(ConfigError::Invalid(format!(
+                "tick_window must be at least 10, got {}",
+                self.tick_window
+            )));
+        }
+        if self.tick_buffer < self.tick_window {
+            return Err(ConfigError::Invalid(format!(
+                "tick_buffer ({}) must be at least tick_window ({})",
+                self.tick_buffer, self.tick_window
+            )));
+        }
+        if !self.default_lot.is_finite() || self.default_lot <= 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "default_lot must be a positive number, got {}",
+                self.default_lot
+            )));
+        }
+        if !(self.risk_percent > 0.0 && self.risk_percent <= 100.0) {
+            return Err(ConfigError::Invalid(format!(
+                "risk_percent must be in (0, 100], got {}",
+                self.risk_percent
+            )));
+        }
+        for (key, value) in [
+            ("paper_balance", self.paper_balance),
+            ("paper_leverage", self.paper_leverage),
+            ("paper_contract_size", self.paper_contract_size),
+        ] {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(ConfigError::Invalid(format!("{} must be a positive number, got {}", key, value)));
+            }
+        }
+        for (key, value) in [
+            ("max_order_lots", self.max_order_lots),
+            ("max_symbol_lots", self.max_symbol_lots),
+            ("max_daily_loss", self.max_daily_loss),
+            ("min_free_margin", self.min_free_margin),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(ConfigError::Invalid(format!("{} must be 0 (off) or positive, got {}", key, value)));
+            }
+        }
+        if self.stale_after_secs == 0 {
+            return Err(ConfigError::Invalid("stale_after_secs must be at least 1".to_string()));
+        }
+        if self.reconnect_after_secs <= self.stale_after_secs {
+            return Err(ConfigError::Invalid(format!(
+                "reconnect_after_secs ({}) must be greater than stale_after_secs ({})",
+                self.reconnect_after_secs, self.stale_after_secs
+            )));
+        }
+        if self.order_timeout_secs == 0 {
+            return Err(ConfigError::Invalid("order_timeout_secs must be at least 1".to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn client_settings(&self) -> ClientSettings {
+        ClientSettings {
+            tick_address: self.tick_address.clone(),
+            order_address: self.order_address.clone(),
+            feed: FeedSettings {
+                stale_after: Duration::from_secs(self.stale_after_secs),
+                reconnect_after: Duration::from_secs(self.reconnect_after_secs),
+            },
+            order_timeout: Duration::from_secs(self.order_timeout_secs),
+        }
+    }
+
+    pub fn risk_limits(&self) -> RiskLimits {
+        RiskLimits {
+            max_order_lots: self.max_order_lots,
+            max_open_positions: self.max_open_positions,
+            max_symbol_lots: self.max_symbol_lots,
+            max_daily_loss: self.max_daily_loss,
+            min_free_margin: self.min_free_margin,
+        }
+    }
+}
+
+/// Accepts `tcp://host:port` (port 1-65535) and `ipc://path` endpoints.
+fn validate_endpoint(key: &str, addr: &str) -> Result<(), ConfigError> {
+    if let Some(rest) = addr.strip_prefix("tcp://") {
+        let valid = match rest.rsplit_once(':') {
+            Some((host, port)) => !host.is_empty() && port.parse::<u16>().is_ok_and(|p| p > 0),
+            None => false,
+        };
+        if valid {
+            return Ok(());
+        }
+        return Err(ConfigError::Invalid(format!(
+            "{} must look like tcp://host:port, got \"{}\"",
+            key, addr
+        )));
+    }
+    if addr.strip_prefix("ipc://").is_some_and(|p| !p.is_empty()) {
+        return Ok(());
+    }
+    Err(ConfigError::Invalid(format!(
+        "{} must start with tcp:// or ipc://, got \"{}\"",
+        key, addr
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid(config: AppConfig) -> String {
+        match config.validate() {
+            Err(ConfigError::Invalid(message)) => message,
+            other => panic!("expected an invalid config, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    fn config_file(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("rustmt5-{}-{}.toml", name, std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(AppConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_endpoints() {
+        let bad = |address: &str| invalid(AppConfig { tick_address: address.to_string(), ..Default::default() });
+        assert_eq!(bad("tcp://127.0.0.1"), "tick_address must look like tcp://host:port, got \"tcp://127.0.0.1\"");
+        assert_eq!(bad("tcp://:5555"), "tick_address must look like tcp://host:port, got \"tcp://:5555\"");
+        assert_eq!(bad("tcp://host:0"), "tick_address must look like tcp://host:port, got \"tcp://host:0\"");
+        assert_eq!(bad("tcp://host:70000"), "tick_address must look like tcp://host:port, got \"tcp://host:70000\"");
+        assert_eq!(bad("udp://host:1"), "tick_address must start with tcp:// or ipc://, got \"udp://host:1\"");
+        assert_eq!(bad("ipc://"), "tick_address must start with tcp:// or ipc://, got \"ipc://\"");
+        assert_eq!(
+            bad("tcp://127.0.0.1:5556"),
+            "tick_address and order_address must differ (both are tcp://127.0.0.1:5556)"
+        );
+        let ipc = AppConfig { tick_address: "ipc:///tmp/ticks".to_string(), ..Default::default() };
+        assert!(ipc.validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_zero_and_negative_values() {
+        assert_eq!(
+            invalid(AppConfig { default_lot: 0.0, ..Default::default() }),
+            "default_lot must be a positive number, got 0"
+        );
+        assert_eq!(
+            invalid(AppConfig { paper_leverage: -1.0, ..Default::default() }),
+            "paper_leverage must be a positive number, got -1"
+        );
+        assert_eq!(
+            invalid(AppConfig { max_daily_loss: -5.0, ..Default::default() }),
+            "max_daily_loss must be 0 (off) or positive, got -5"
+        );
+        assert_eq!(
+            invalid(AppConfig { risk_percent: 0.0, ..Default::default() }),
+            "risk_percent must be in (0, 100], got 0"
+        );
+        assert_eq!(
+            invalid(AppConfig { stale_after_secs: 0, ..Default::default() }),
+            "stale_after_secs must be at least 1"
+        );
+        assert_eq!(
+            invalid(AppConfig { order_timeout_secs: 0, ..Default::default() }),
+            "order_timeout_secs must be at least 1"
+        );
+        assert_eq!(
+            invalid(AppConfig { tick_window: 5, ..Default::default() }),
+            "tick_window must be at least 10, got 5"
+        );
+    }
+
+    #[test]
+    fn rejects_window_above_buffer_and_stale_after_reconnect() {
+        assert_eq!(
+            invalid(AppConfig { tick_window: 500, tick_buffer: 100, ..Default::default() }),
+            "tick_buffer (100) must be at least tick_window (500)"
+        );
+        assert_eq!(
+            invalid(AppConfig { stale_after_secs: 60, reconnect_after_secs: 60, ..Default::default() }),
+            "reconnect_after_secs (60) must be greater than stale_after_secs (60)"
+        );
+    }
+
+    #[test]
+    fn cli_overrides_the_file() {
+        let path = config_file(
+            "overrides",
+            "tick_address = \"tcp://10.0.0.1:6000\"\ntick_window = 300\ndefault_lot = 0.5\n",
+        );
+        let file_only = Cli::try_parse_from(["app", "--config", path.to_str().unwrap()]).unwrap();
+        let config = AppConfig::load(&file_only).unwrap();
+        assert_eq!(config.tick_address, "tcp://10.0.0.1:6000");
+        assert_eq!(config.tick_window, 300);
+        assert_eq!(config.source, path.display().to_string());
+
+        let cli = Cli::try_parse_from([
+            "app",
+            "--config",
+            path.to_str().unwrap(),
+            "--tick-address",
+            "tcp://10.0.0.2:7000",
+            "--tick-window",
+            "400",
+            "--paper",
+        ])
+        .unwrap();
+        let config = AppConfig::load(&cli).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(config.tick_address, "tcp://10.0.0.2:7000");
+        assert_eq!(config.tick_window, 400);
+        assert_eq!(config.default_lot, 0.5);
+        assert!(config.paper_trading);
+        assert_eq!(config.source, format!("{} + CLI", path.display()));
+    }
+
+    #[test]
+    fn file_errors() {
+        let path = config_file("unknown-key", "tick_windw = 300\n");
+        let cli = Cli::try_parse_from(["app", "--config", path.to_str().unwrap()]).unwrap();
+        let error = AppConfig::load(&cli).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(error, ConfigError::Parse(..)), "{}", error);
+
+        let cli = Cli::try_parse_from(["app", "--config", "/nonexistent/config.toml"]).unwrap();
+        assert!(matches!(AppConfig::load(&cli), Err(ConfigError::Read(..))));
+    }
+}
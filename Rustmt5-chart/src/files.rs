@@ -0,0 +1,184 @@
+//+------------------------------------------------------------------+
+//|                                                         files.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::chart::{ChartData, ChartMode, IndicatorPlot};
+use crate::indicator_panel::{IndicatorCache, IndicatorConfig};
+use crate::tick_store::Tick;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use sum3api::bars::{self, Bar};
+use sum3api::history::{self, CsvData};
+use sum3api::Timeframe;
+
+// ============================================================================
+// Output Folder Listing
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct FileEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// CSV files in `dir`, newest first.
+pub fn list_csv_files(dir: &Path) -> io::Result<Vec<FileEntry>> {
+    let mut files: Vec<FileEntry> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")))
+        .map(|e| {
+            let meta = e.metadata().ok();
+            FileEntry {
+                path: e.path(),
+                name: e.file_name().to_string_lossy().into_owned(),
+                size: meta.as_ref().map_or(0, |m| m.len()),
+                modified: meta.and_then(|m| m.modified().ok()),
+            }
+        })
+        .collect();
+    files.sort_by(|a, b| b.modified.cmp(&a.modified).then_with(|| a.name.cmp(&b.name)));
+    Ok(files)
+}
+
+// ============================================================================
+// File Chart Tab
+// ============================================================================
+
+/// A CSV from the output folder opened in its own chart tab.
+pub struct FileTab {
+    pub title: String,
+    pub path: PathBuf,
+    pub symbol: Option<String>,
+    pub mode: ChartMode,
+    ticks: Vec<Tick>,
+    bars: Vec<Bar>,
+    // Timeframe of an OHLC file; tick files are aggregated at `timeframe`
+    fixed_timeframe: Option<Timeframe>,
+    timeframe: Timeframe,
+    bar_seconds: i64,
+    indicators: IndicatorCache,
+}
+
+impl FileTab {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let title = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let symbol = history::symbol_from_file_name(path);
+
+        let (ticks, bars, fixed_timeframe) = match history::read_csv(path)? {
+            CsvData::Ticks(rows) => {
+                let mut ticks: Vec<Tick> = rows
+                    .iter()
+                    .map(|r| Tick { time: r.time, bid: r.bid, ask: r.ask, volume: r.volume })
+                    .collect();
+                ticks.sort_by_key(|t| t.time);
+                (ticks, Vec::new(), None)
+            }
+            CsvData::Bars(mut bars) => {
+                bars.sort_by_key(|b| b.time);
+                let timeframe = history::timeframe_from_file_name(path).or_else(|| bars::infer_timeframe(&bars));
+                (Vec::new(), bars, Some(timeframe.unwrap_or(Timeframe::M1)))
+            }
+        };
+        if ticks.is_empty() && bars.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}: no data rows", path.display())));
+        }
+
+        let mut tab = Self {
+            title,
+            path: path.to_path_buf(),
+            symbol,
+            mode: if fixed_timeframe.is_some() { ChartMode::Candles } else { ChartMode::Line },
+            ticks,
+            bars,
+            fixed_timeframe,
+            timeframe: fixed_timeframe.unwrap_or(Timeframe::M1),
+            bar_seconds: 0,
+            indicators: IndicatorCache::default(),
+        };
+        tab.rebuild_bars();
+        Ok(tab)
+    }
+
+    /// OHLC files only have bars; tick files can be drawn either way.
+    pub fn has_ticks(&self) -> bool {
+        self.fixed_timeframe.is_none()
+    }
+
+    pub fn timeframe(&self) -> Timeframe {
+        self.timeframe
+    }
+
+    /// Raw ticks of a tick file, oldest first (empty for OHLC files).
+    pub fn ticks(&self) -> &[Tick] {
+        &self.ticks
+    }
+
+    /// Re-aggregates a tick file at another timeframe (no-op for OHLC files).
+    pub fn set_timeframe(&mut self, timeframe: Timeframe) {
+        if self.has_ticks() && timeframe != self.timeframe {
+            self.timeframe = timeframe;
+            self.rebuild_bars();
+        }
+    }
+
+    fn rebuild_bars(&mut self) {
+        if self.has_ticks() {
+            self.bars = bars::aggregate(self.timeframe, self.ticks.iter().map(|t| (t.time, t.bid, t.ask)));
+        }
+        self.bar_seconds = self.timeframe.seconds();
+    }
+
+    pub fn chart_data<'a>(&'a self, indicators: &'a [IndicatorPlot<'a>]) -> ChartData<'a> {
+        ChartData {
+            mode: self.chart_mode(),
+            ticks: &self.ticks,
+            bars: &self.bars,
+            bar_seconds: self.bar_seconds,
+            forming: false,
+            indicators,
+        }
+    }
+
+    fn chart_mode(&self) -> ChartMode {
+        if self.has_ticks() { self.mode } else { ChartMode::Candles }
+    }
+
+    /// Recomputes the indicators if the setup, chart mode or timeframe
+    /// changed since last time.
+    pub fn update_indicators(&mut self, configs: &[IndicatorConfig], revision: u64) {
+        let mode = self.chart_mode();
+        self.indicators.update(configs, revision, mode, &self.ticks, &self.bars, self.bar_seconds);
+    }
+
+    /// Indicators over what the tab shows, as of `update_indicators`.
+    pub fn indicator_plots(&self, configs: &[IndicatorConfig]) -> Vec<IndicatorPlot<'_>> {
+        self.indicators.plots(configs)
+    }
+
+    /// One-line description for the tab header.
+    pub fn summary(&self) -> String {
+        let range = match self.has_ticks() {
+            true => self.ticks.first().zip(self.ticks.last()).map(|(a, b)| (a.time, b.time)),
+            false => self.bars.first().zip(self.bars.last()).map(|(a, b)| (a.time, b.time)),
+        };
+        let count = if self.has_ticks() {
+            format!("{} ticks", self.ticks.len())
+        } else {
+            format!("{} {} bars", self.bars.len(), self.timeframe.as_str())
+        };
+        match range {
+            Some((from, to)) => format!(
+                "{} | {} → {}",
+                count,
+                history::format_time(from, true),
+                history::format_time(to, true)
+            ),
+            None => count,
+        }
+    }
+}
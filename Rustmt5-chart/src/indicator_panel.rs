@@ -0,0 +1,317 @@
+//+------------------------------------------------------------------+
+//|                                               indicator_panel.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::chart::{ChartMode, IndicatorPlot};
+use crate::tick_store::Tick;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use sum3api::bars::Bar;
+use sum3api::indicators::{self, IndicatorSeries, IndicatorSpec};
+
+/// Indicator setup survives restarts in this file inside output_dir.
+const INDICATORS_FILE: &str = "indicators.json";
+
+/// Colours handed to newly added indicators, in turn.
+const PALETTE: [[u8; 3]; 6] = [
+    [255, 200, 60],
+    [90, 170, 255],
+    [230, 110, 230],
+    [80, 220, 200],
+    [255, 140, 80],
+    [180, 180, 255],
+];
+
+// ============================================================================
+// Indicator Setup
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct IndicatorConfig {
+    pub spec: IndicatorSpec,
+    pub color: [u8; 3],
+    // On the price chart, or in a panel below it
+    pub overlay: bool,
+    pub visible: bool,
+}
+
+impl IndicatorConfig {
+    fn plot<'a>(&self, series: &'a IndicatorSeries, forming: Option<&Bar>) -> IndicatorPlot<'a> {
+        let [r, g, b] = self.color;
+        IndicatorPlot {
+            spec: self.spec,
+            color: egui::Color32::from_rgb(r, g, b),
+            overlay: self.overlay,
+            points: series.points(),
+            forming: forming.and_then(|bar| series.preview(bar)),
+        }
+    }
+}
+
+/// The indicators drawn on every chart, and the window that edits them.
+pub struct IndicatorPanel {
+    pub open: bool,
+    configs: Vec<IndicatorConfig>,
+    // Bumped on every change, so computed series know to start over
+    revision: u64,
+    path: PathBuf,
+    // Kind picked for the next "Add"
+    new_kind: usize,
+}
+
+impl IndicatorPanel {
+    /// Setup saved in `output_dir`, or none.
+    pub fn load(output_dir: &Path) -> Self {
+        let path = output_dir.join(INDICATORS_FILE);
+        let configs = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self { open: false, configs, revision: 0, path, new_kind: 0 }
+    }
+
+    pub fn configs(&self) -> &[IndicatorConfig] {
+        &self.configs
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        let mut changed = false;
+        egui::Window::new("📊 Indicators")
+            .open(&mut open)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                let mut remove = None;
+                egui::Grid::new("indicator_list").num_columns(5).spacing([8.0, 4.0]).show(ui, |ui| {
+                    for (i, config) in self.configs.iter_mut().enumerate() {
+                        changed |= ui.checkbox(&mut config.visible, config.spec.name()).changed();
+                        ui.horizontal(|ui| changed |= edit_params(ui, &mut config.spec));
+                        changed |= ui.color_edit_button_srgb(&mut config.color).changed();
+                        ui.horizontal(|ui| {
+                            changed |= ui.selectable_value(&mut config.overlay, true, "Overlay").changed();
+                            changed |= ui.selectable_value(&mut config.overlay, false, "Panel").changed();
+                        });
+                        if ui.small_button("✕").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(i) = remove {
+                    self.configs.remove(i);
+                    changed = true;
+                }
+                if self.configs.is_empty() {
+                    ui.weak("No indicators yet");
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("indicator_new_kind")
+                        .selected_text(IndicatorSpec::DEFAULTS[self.new_kind].name())
+                        .show_ui(ui, |ui| {
+                            for (i, spec) in IndicatorSpec::DEFAULTS.iter().enumerate() {
+                                ui.selectable_value(&mut self.new_kind, i, spec.name());
+                            }
+                        });
+                    if ui.button("➕ Add").clicked() {
+                        let spec = IndicatorSpec::DEFAULTS[self.new_kind];
+                        self.configs.push(IndicatorConfig {
+                            spec,
+                            color: PALETTE[self.configs.len() % PALETTE.len()],
+                            overlay: spec.is_overlay(),
+                            visible: true,
+                        });
+                        changed = true;
+                    }
+                });
+                ui.weak("Line charts feed indicators bid ticks; candle charts feed closed bars");
+            });
+        self.open = open;
+        if changed {
+            self.revision += 1;
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        let saved = serde_json::to_string_pretty(&self.configs)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(&self.path, json).map_err(|e| e.to_string()));
+        if let Err(e) = saved {
+            eprintln!("Failed to save {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Parameter fields of one indicator; true when one changed.
+fn edit_params(ui: &mut egui::Ui, spec: &mut IndicatorSpec) -> bool {
+    let period = |ui: &mut egui::Ui, label: &str, value: &mut usize| {
+        ui.label(label);
+        ui.add(egui::DragValue::new(value).clamp_range(1..=10_000)).changed()
+    };
+    match spec {
+        IndicatorSpec::Sma { period: p }
+        | IndicatorSpec::Ema { period: p }
+        | IndicatorSpec::Wma { period: p }
+        | IndicatorSpec::Atr { period: p }
+        | IndicatorSpec::Rsi { period: p } => period(ui, "period", p),
+        IndicatorSpec::Bollinger { period: p, deviations } => {
+            let changed = period(ui, "period", p);
+            ui.label("σ");
+            changed | ui.add(egui::DragValue::new(deviations).speed(0.1).clamp_range(0.1..=10.0)).changed()
+        }
+        IndicatorSpec::Macd { fast, slow, signal } => {
+            period(ui, "fast", fast) | period(ui, "slow", slow) | period(ui, "signal", signal)
+        }
+        IndicatorSpec::Stochastic { k_period, d_period, slowing } => {
+            period(ui, "%K", k_period) | period(ui, "%D", d_period) | period(ui, "slowing", slowing)
+        }
+        IndicatorSpec::Vwap => {
+            ui.weak("daily");
+            false
+        }
+    }
+}
+
+/// Series for the visible indicators, tagged with their config index.
+fn build_series<'a>(configs: &[IndicatorConfig], bars: impl Iterator<Item = &'a Bar> + Clone) -> Vec<(usize, IndicatorSeries)> {
+    configs
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.visible)
+        .map(|(i, c)| {
+            let mut series = IndicatorSeries::new(c.spec);
+            for bar in bars.clone() {
+                series.push(bar);
+            }
+            (i, series)
+        })
+        .collect()
+}
+
+fn tick_bar(tick: &Tick) -> Bar {
+    indicators::price_bar(tick.time, tick.bid, tick.volume)
+}
+
+// ============================================================================
+// Live Chart Indicators
+// ============================================================================
+
+/// Indicators over the live chart, fed each tick and each closed candle
+/// as they arrive.
+pub struct LiveIndicators {
+    // Setup revision the series were built for; None before the first build
+    revision: Option<u64>,
+    // Outputs kept per series, as many as the chart holds ticks and bars
+    keep: usize,
+    ticks: Vec<(usize, IndicatorSeries)>,
+    bars: Vec<(usize, IndicatorSeries)>,
+}
+
+impl LiveIndicators {
+    pub fn new(keep: usize) -> Self {
+        Self { revision: None, keep: keep.max(1), ticks: Vec::new(), bars: Vec::new() }
+    }
+
+    /// Whether the series follow setup `revision`.
+    pub fn is_current(&self, revision: u64) -> bool {
+        self.revision == Some(revision)
+    }
+
+    /// Starts over from the ticks and closed candles already on the chart.
+    pub fn rebuild<'a>(
+        &mut self,
+        configs: &[IndicatorConfig],
+        revision: u64,
+        ticks: impl Iterator<Item = &'a Tick>,
+        bars: &[Bar],
+    ) {
+        let tick_bars: Vec<Bar> = ticks.map(tick_bar).collect();
+        self.revision = Some(revision);
+        self.ticks = build_series(configs, tick_bars.iter());
+        self.bars = build_series(configs, bars.iter());
+    }
+
+    pub fn push_tick(&mut self, tick: &Tick) {
+        let bar = tick_bar(tick);
+        for (_, series) in &mut self.ticks {
+            series.push(&bar);
+            if series.points().len() > self.keep * 2 {
+                series.trim(self.keep);
+            }
+        }
+    }
+
+    pub fn push_bar(&mut self, bar: &Bar) {
+        for (_, series) in &mut self.bars {
+            series.push(bar);
+            if series.points().len() > self.keep * 2 {
+                series.trim(self.keep);
+            }
+        }
+    }
+
+    /// What the chart draws in `mode`; the forming candle adds a last point.
+    pub fn plots(&self, configs: &[IndicatorConfig], mode: ChartMode, forming: Option<&Bar>) -> Vec<IndicatorPlot<'_>> {
+        let (series, forming) = match mode {
+            ChartMode::Line => (&self.ticks, None),
+            ChartMode::Candles => (&self.bars, forming),
+        };
+        series
+            .iter()
+            .filter_map(|(i, s)| configs.get(*i).map(|c| c.plot(s, forming)))
+            .collect()
+    }
+}
+
+// ============================================================================
+// File Tab Indicators
+// ============================================================================
+
+/// Indicators over a file tab, computed once per setup, chart mode and
+/// timeframe.
+#[derive(Default)]
+pub struct IndicatorCache {
+    key: Option<(u64, ChartMode, i64)>,
+    series: Vec<(usize, IndicatorSeries)>,
+}
+
+impl IndicatorCache {
+    pub fn update(
+        &mut self,
+        configs: &[IndicatorConfig],
+        revision: u64,
+        mode: ChartMode,
+        ticks: &[Tick],
+        bars: &[Bar],
+        bar_seconds: i64,
+    ) {
+        let key = Some((revision, mode, bar_seconds));
+        if self.key == key {
+            return;
+        }
+        self.key = key;
+        self.series = match mode {
+            ChartMode::Line => {
+                let tick_bars: Vec<Bar> = ticks.iter().map(tick_bar).collect();
+                build_series(configs, tick_bars.iter())
+            }
+            ChartMode::Candles => build_series(configs, bars.iter()),
+        };
+    }
+
+    pub fn plots(&self, configs: &[IndicatorConfig]) -> Vec<IndicatorPlot<'_>> {
+        self.series
+            .iter()
+            .filter_map(|(i, s)| configs.get(*i).map(|c| c.plot(s, None)))
+            .collect()
+    }
+}
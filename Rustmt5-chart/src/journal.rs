@@ -0,0 +1,127 @@
+//+------------------------------------------------------------------+
+//|                                                       journal.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use std::collections::HashMap;
+use sum3api::analytics::{EquityPoint, TradeRecord};
+use sum3api::{PositionData, Side};
+
+/// Seconds between equity snapshots.
+const SNAPSHOT_SECS: i64 = 60;
+
+// ============================================================================
+// Live Trade Journal
+// ============================================================================
+
+/// What the journal remembers of an open position.
+struct OpenTrade {
+    side: Side,
+    volume: f64,
+    open_price: f64,
+    open_time: i64,
+    /// Stop-loss when the position was first seen, 0 for none
+    initial_sl: f64,
+    last_profit: f64,
+}
+
+/// Closed trades and equity snapshots of the EA account, for analytics.
+///
+/// The EA only reports open positions, so a trade is booked when its
+/// ticket leaves the list (at its last reported profit), and a partial
+/// close when its volume shrinks.
+#[derive(Default)]
+pub struct TradeJournal {
+    open: HashMap<u64, OpenTrade>,
+    records: Vec<TradeRecord>,
+    equity: Vec<EquityPoint>,
+    initial_balance: Option<f64>,
+}
+
+impl TradeJournal {
+    pub fn records(&self) -> &[TradeRecord] {
+        &self.records
+    }
+
+    pub fn equity(&self) -> &[EquityPoint] {
+        &self.equity
+    }
+
+    /// Balance at the first snapshot, 0 before any.
+    pub fn initial_balance(&self) -> f64 {
+        self.initial_balance.unwrap_or(0.0)
+    }
+
+    /// Starts over, e.g. when the account shown changes.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Books the trades closed since the last tick. `bid`/`ask` stand in
+    /// for the close price, the EA does not report it.
+    pub fn update(&mut self, positions: &[PositionData], time: i64, bid: f64, ask: f64, contract_size: f64) {
+        let close_price = |side: Side| if side == Side::Buy { bid } else { ask };
+        for pos in positions {
+            let side = if pos.pos_type == "BUY" { Side::Buy } else { Side::Sell };
+            let trade = self.open.entry(pos.ticket).or_insert(OpenTrade {
+                side,
+                volume: pos.volume,
+                open_price: pos.price,
+                open_time: time,
+                initial_sl: pos.sl,
+                last_profit: pos.profit,
+            });
+            if pos.volume < trade.volume - 1e-9 {
+                let closed = trade.volume - pos.volume;
+                let profit = trade.last_profit * closed / trade.volume;
+                let record = trade.book(pos.ticket, closed, profit, time, close_price(trade.side), contract_size);
+                self.records.push(record);
+                trade.volume = pos.volume;
+            }
+            trade.last_profit = pos.profit;
+        }
+
+        let gone: Vec<u64> =
+            self.open.keys().filter(|ticket| !positions.iter().any(|p| p.ticket == **ticket)).copied().collect();
+        for ticket in gone {
+            if let Some(trade) = self.open.remove(&ticket) {
+                let price = close_price(trade.side);
+                self.records.push(trade.book(ticket, trade.volume, trade.last_profit, time, price, contract_size));
+            }
+        }
+    }
+
+    /// Adds an equity snapshot, at most one per minute.
+    pub fn snapshot(&mut self, time: i64, balance: f64, equity: f64) {
+        if balance <= 0.0 {
+            return;
+        }
+        self.initial_balance.get_or_insert(balance);
+        let bucket = time.div_euclid(SNAPSHOT_SECS);
+        match self.equity.last_mut() {
+            Some(last) if last.time.div_euclid(SNAPSHOT_SECS) == bucket => {
+                *last = EquityPoint { time, balance, equity };
+            }
+            _ => self.equity.push(EquityPoint { time, balance, equity }),
+        }
+    }
+}
+
+impl OpenTrade {
+    fn book(&self, ticket: u64, volume: f64, profit: f64, time: i64, close_price: f64, contract_size: f64) -> TradeRecord {
+        TradeRecord {
+            ticket,
+            side: self.side,
+            volume,
+            open_time: self.open_time,
+            close_time: time,
+            open_price: self.open_price,
+            close_price,
+            net_profit: profit,
+            risk: (self.initial_sl > 0.0)
+                .then(|| (self.open_price - self.initial_sl).abs() * volume * contract_size)
+                .filter(|risk| *risk > 0.0),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//+------------------------------------------------------------------+
+//|                                                        levels.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use eframe::egui;
+use egui_plot::{HLine, LineStyle, PlotPoint, PlotUi};
+
+/// How close (pixels) the pointer must be to grab a line.
+const GRAB_DISTANCE: f32 = 5.0;
+
+// ============================================================================
+// Trade Levels
+// ============================================================================
+
+/// Which price of a trade a line shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevelKind {
+    Entry,
+    StopLoss,
+    TakeProfit,
+}
+
+/// A line's trade: a position, or a pending order when `order` is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LevelId {
+    pub ticket: u64,
+    pub order: bool,
+    pub kind: LevelKind,
+}
+
+/// A horizontal trade line on the price chart.
+pub struct Level {
+    pub id: LevelId,
+    pub price: f64,
+    pub name: String,
+    pub color: egui::Color32,
+    pub style: LineStyle,
+    pub draggable: bool,
+}
+
+/// A line being dragged; `price` follows the pointer.
+#[derive(Clone, Copy, Debug)]
+pub struct LevelDrag {
+    pub id: LevelId,
+    pub price: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct LevelInteraction {
+    /// Pointer is over a draggable line (or dragging one); the caller turns
+    /// off chart panning for the next frame so the press grabs the line
+    pub hovered: bool,
+    /// A drag ended this frame at this price
+    pub released: Option<LevelDrag>,
+}
+
+/// Draws trade lines and lets the draggable ones be moved with the mouse.
+pub fn show_levels(plot_ui: &mut PlotUi, levels: &[Level], drag: &mut Option<LevelDrag>) -> LevelInteraction {
+    let response = plot_ui.response().clone();
+
+    let nearest = response.hover_pos().and_then(|pos| {
+        levels
+            .iter()
+            .filter(|l| l.draggable)
+            .map(|l| (l.id, (plot_ui.screen_from_plot(PlotPoint::new(0.0, l.price)).y - pos.y).abs()))
+            .filter(|(_, distance)| *distance <= GRAB_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id)
+    });
+    if drag.is_none()
+        && response.drag_started()
+        && let Some(level) = nearest.and_then(|id| levels.iter().find(|l| l.id == id))
+    {
+        *drag = Some(LevelDrag { id: level.id, price: level.price });
+    }
+
+    let mut released = None;
+    if let Some(current) = drag.as_mut() {
+        if let Some(pointer) = plot_ui.pointer_coordinate() {
+            current.price = pointer.y;
+        }
+        if !response.dragged() {
+            released = drag.take();
+        }
+    }
+
+    for level in levels {
+        let dragged = drag.filter(|d| d.id == level.id);
+        plot_ui.hline(
+            HLine::new(dragged.map_or(level.price, |d| d.price))
+                .color(level.color)
+                .name(&level.name)
+                .style(level.style)
+                .width(if dragged.is_some() { 2.0 } else { 1.0 }),
+        );
+    }
+
+    let hovered = nearest.is_some() || drag.is_some();
+    if hovered {
+        response.ctx.set_cursor_icon(egui::CursorIcon::ResizeVertical);
+    }
+    LevelInteraction { hovered, released }
+}
@@ -8,131 +8,216 @@
 //property version   "2.00"
 //property strict
 
+mod analytics_panel;
+mod backtest_panel;
+mod candles;
+mod chart;
+mod config;
+mod files;
+mod indicator_panel;
+mod journal;
+mod levels;
+mod optimizer_panel;
+mod replay;
+mod tick_store;
+mod time_axis;
+
+use clap::Parser;
+use analytics_panel::ReportExport;
+use backtest_panel::BacktestPanel;
+use candles::CandleSeries;
+use chart::{ChartData, ChartMode, ChartView};
+use config::{AppConfig, Cli};
+use files::{FileEntry, FileTab};
+use indicator_panel::{IndicatorPanel, LiveIndicators};
+use journal::TradeJournal;
+use levels::{Level, LevelDrag, LevelId, LevelKind};
+use optimizer_panel::OptimizerPanel;
+use replay::{Replay, TickPipeline};
+use tick_store::{Tick, TickStore};
+use time_axis::DisplayZone;
 use eframe::egui;
-use egui_plot::{Line, Plot, PlotPoints};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
-use zeromq::{Socket, SocketRecv, SocketSend};
+use sum3api::analytics::{self, TradeRecord};
+use sum3api::history;
+use sum3api::risk::{AccountState, RiskEngine};
+use sum3api::sim::{CloseReason, SimBroker, SimSettings};
+use sum3api::strategy::{SmaCross, StrategyRuntime};
+use sum3api::sizing::{self, PositionSize, RiskBase, SizeLimit, SymbolSpec};
+use sum3api::{
+    Client, FeedState, FeedStatus, HistoryData, HistoryMode, OrderRequest, OrderResponse,
+    CloseFilter, PendingOrderData, PositionData, Request, Side, StopLevel, TickData, Timeframe, ALL_SYMBOLS,
+};
+use tokio::sync::{mpsc, watch};
 use std::fs::{self, OpenOptions};
+use std::collections::HashMap;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 // ============================================================================
 // Data Structures
 // ============================================================================
 
-#[derive(Clone, Debug, Deserialize)]
-#[allow(dead_code)]
-struct PositionData {
-    ticket: u64,
-    #[serde(rename = "type")]
-    pos_type: String, // "BUY" or "SELL"
-    volume: f64,
-    price: f64,
-    profit: f64,
+// What an in-flight request was for, keyed by its request_id
+#[derive(Clone, Debug)]
+enum PendingAction {
+    // `strategy`: index of the strategy that sent it, None for the UI
+    Order { request: Request, strategy: Option<usize> },
+    History { symbol: String, timeframe: Timeframe, mode: HistoryMode },
 }
 
-#[derive(Clone, Debug, Deserialize)]
-#[allow(dead_code)]
-struct PendingOrderData {
-    ticket: u64,
-    #[serde(rename = "type")]
-    order_type: String, // "BUY LIMIT", "SELL STOP", etc.
-    volume: f64,
-    price: f64,
-}
+/// Most live candles drawn at once; older bars stay in the series.
+const MAX_VISIBLE_CANDLES: usize = 500;
 
-#[derive(Clone, Debug, Deserialize)]
-struct TickData {
-    symbol: String,
-    bid: f64,
-    ask: f64,
-    time: i64,
-    #[serde(default)]
-    volume: u64,
-    // Account info
-    #[serde(default)]
+// Account state from the latest tick, kept apart from the tick history
+#[derive(Clone, Copy, Debug)]
+struct AccountSnapshot {
     balance: f64,
-    #[serde(default)]
     equity: f64,
-    #[serde(default)]
     margin: f64,
-    #[serde(default)]
     free_margin: f64,
-    // Trading constraints
-    #[serde(default)]
     min_lot: f64,
-    #[serde(default)]
     max_lot: f64,
-    #[serde(default)]
     lot_step: f64,
-    
-    // Active trades
-    #[serde(default)]
-    positions: Vec<PositionData>,
-    #[serde(default)]
-    orders: Vec<PendingOrderData>,
+    // Price of one point, for SL/TP distances
+    point: f64,
+    // Value of one lot, for risk sizing
+    tick_value: f64,
+    tick_size: f64,
+    contract_size: f64,
 }
 
-#[derive(Clone, Debug, Serialize)]
-struct OrderRequest {
-    #[serde(rename = "type")]
-    order_type: String,
-    symbol: String,
-    volume: f64,
-    price: f64,
-    #[serde(default)]
-    ticket: u64, // For close/cancel
-    // History params
-    #[serde(skip_serializing_if = "Option::is_none")]
-    timeframe: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    start: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    end: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    mode: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    request_id: Option<u64>,  // Unique ID for history downloads
+impl AccountSnapshot {
+    fn spec(&self) -> SymbolSpec {
+        SymbolSpec {
+            tick_value: self.tick_value,
+            tick_size: self.tick_size,
+            contract_size: self.contract_size,
+            min_lot: self.min_lot,
+            max_lot: self.max_lot,
+            lot_step: self.lot_step,
+        }
+    }
 }
 
-#[derive(Clone, Debug, Deserialize)]
-struct OrderResponse {
-    success: bool,
-    ticket: Option<i64>,
-    error: Option<String>,
-    message: Option<String>,
+impl Default for AccountSnapshot {
+    fn default() -> Self {
+        Self {
+            balance: 0.0,
+            equity: 0.0,
+            margin: 0.0,
+            free_margin: 0.0,
+            min_lot: 0.01,
+            max_lot: 100.0,
+            lot_step: 0.01,
+            point: 0.001,
+            tick_value: 0.0,
+            tick_size: 0.0,
+            contract_size: 0.0,
+        }
+    }
 }
 
 // Struct for tracking order execution breaklines on chart
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 struct OrderBreakline {
-    index: usize,        // Data index where order was executed
-    order_type: String,  // "BUY" or "SELL" variant
+    symbol: String,
+    time: i64,           // Server time of the fill
+    price: f64,          // Fill price
+    side: Side,          // BUY or SELL
     ticket: i64,         // Order ticket number
 }
 
+// SL/TP (and price/expiry of a pending order) being edited in the side panel
+#[derive(Clone, Debug)]
+struct TradeEdit {
+    ticket: u64,
+    order: bool,
+    price: String,
+    sl: String,
+    tp: String,
+    expiry: String, // "yyyy.mm.dd HH:MM" server time, empty = good till cancelled
+}
+
+/// Breaklines survive restarts in this file inside output_dir.
+const BREAKLINES_FILE: &str = "breaklines.json";
+
+fn load_breaklines(output_dir: &Path) -> Vec<OrderBreakline> {
+    fs::read_to_string(output_dir.join(BREAKLINES_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Editable text for a price, empty when unset.
+fn format_price(price: f64) -> String {
+    if price > 0.0 { format!("{:.5}", price) } else { String::new() }
+}
+
+/// " SL x TP y" for the set stops, empty when neither is set.
+fn format_stops(sl: f64, tp: f64) -> String {
+    let mut text = String::new();
+    if sl > 0.0 {
+        text.push_str(&format!(" SL {:.5}", sl));
+    }
+    if tp > 0.0 {
+        text.push_str(&format!(" TP {:.5}", tp));
+    }
+    text
+}
+
+/// Readable failure text; MT5 trade errors get a suggested next step.
+fn describe_failure(response: &OrderResponse) -> String {
+    match response.trade_error() {
+        Some(err) => format!(
+            "✗ Failed: {}{}\n→ {}",
+            err,
+            if err.is_retryable() { " [retryable]" } else { "" },
+            err.suggestion()
+        ),
+        None => format!(
+            "✗ Failed: {}",
+            response.error.as_deref().unwrap_or("Unknown error")
+        ),
+    }
+}
+
+/// One line per ticket of a bulk action or reverse.
+fn format_ticket_results(results: &[sum3api::TicketResult]) -> String {
+    results
+        .iter()
+        .map(|r| match (r.success, r.error.as_deref()) {
+            (true, _) => format!("\n  ✓ #{}", r.ticket),
+            (false, error) => format!("\n  ✗ #{}: {}", r.ticket, error.unwrap_or("failed")),
+        })
+        .collect()
+}
+
 // ============================================================================
 // Application State
 // ============================================================================
 
 struct Mt5ChartApp {
-    // Tick data
-    tick_receiver: mpsc::Receiver<TickData>,
-    data: Vec<TickData>,
+    // Tick data, from the live feed or a replay
+    ticks_in: TickPipeline,
+    feed_status: watch::Receiver<FeedStatus>,
+    ticks: TickStore,
+    tick_window: usize,
     symbol: String,
+    chart_mode: ChartMode,
+    candles: CandleSeries,
+    display_zone: DisplayZone,
+    hide_gaps: bool,
+    // Broker server time minus UTC, from the feed
+    server_offset: i64,
     
     // Latest account info
-    balance: f64,
-    equity: f64,
-    margin: f64,
-    free_margin: f64,
-    min_lot: f64,
-    max_lot: f64,
-    lot_step: f64,
+    account: AccountSnapshot,
     
-    // Order handling
-    order_sender: mpsc::Sender<OrderRequest>,
+    // Order handling: requests go through the client, replies loop back here
+    client: Client,
+    response_sender: mpsc::Sender<OrderResponse>,
     response_receiver: mpsc::Receiver<OrderResponse>,
     
     // UI state for order panel
@@ -141,13 +226,20 @@ struct Mt5ChartApp {
     limit_price: String,
     #[allow(dead_code)]
     stop_price: String,
+    sl_input: String,
+    tp_input: String,
+    stops_in_points: bool,
+    // Risk sizing: lots from a % of balance/equity and the SL distance
+    risk_sizing: bool,
+    risk_percent_str: String,
+    risk_base: RiskBase,
     last_order_result: Option<String>,
     
     // History Download UI
     history_start_date: String,
     history_end_date: String,
-    history_tf: String,
-    history_mode: String,
+    history_tf: Timeframe,
+    history_mode: HistoryMode,
     
     // Live Recording
     is_recording: bool,
@@ -161,51 +253,100 @@ struct Mt5ChartApp {
     output_dir: PathBuf,
     request_counter: u64,
     
+    // Loaded settings, shown in the chart header
+    config: AppConfig,
+    
     // Order Breaklines for Chart
     order_breaklines: Vec<OrderBreakline>,
-    pending_order_type: Option<String>,  // Track what type of order is pending
     
-    // Pending history request info for CSV naming
-    pending_history_request: Option<(u64, String, String, String)>, // (id, symbol, tf, mode)
+    // Requests awaiting a response, so each reply reaches the action that sent it
+    in_flight: HashMap<u64, PendingAction>,
+    
+    // Pre-trade limits every order passes, and the kill switch lock
+    risk: RiskEngine,
+    
+    // Automated strategies; their orders take the same path as the buttons
+    strategies: StrategyRuntime,
+    
+    // Indicator setup, and its outputs over the live chart
+    indicators: IndicatorPanel,
+    live_indicators: LiveIndicators,
+    
+    // Offline strategy tests and parameter searches over output folder CSVs
+    backtest: BacktestPanel,
+    optimizer: OptimizerPanel,
+    
+    // Performance of the account shown: closed trades and equity snapshots
+    journal: TradeJournal,
+    show_analytics: bool,
+    analytics_status: Option<String>,
+    
+    // File browser and opened CSV tabs (active_tab None = live chart)
+    show_files: bool,
+    file_entries: Vec<FileEntry>,
+    file_tabs: Vec<FileTab>,
+    active_tab: Option<usize>,
+    
+    // Recorded ticks played through the live chart instead of the feed
+    replay: Option<Replay>,
+    
+    // Paper trading: orders go to this simulated broker instead of the EA
+    paper: Option<SimBroker>,
+    
+    // Modifying trades: panel editor and chart line being dragged
+    trade_edit: Option<TradeEdit>,
+    level_drag: Option<LevelDrag>,
+    level_hovered: bool,
 }
 
 impl Mt5ChartApp {
     fn new(
-        tick_receiver: mpsc::Receiver<TickData>,
-        order_sender: mpsc::Sender<OrderRequest>,
-        response_receiver: mpsc::Receiver<OrderResponse>,
+        config: AppConfig,
+        client: Client,
+        ticks_in: TickPipeline,
     ) -> Self {
         // Defaults dates to "yyyy.mm.dd"
         let now = chrono::Local::now();
         let today_str = now.format("%Y.%m.%d").to_string();
         
         // Ensure output directory exists
-        let output_dir = PathBuf::from("output");
+        let output_dir = config.output_dir.clone();
         fs::create_dir_all(&output_dir).ok();
         
-        Self {
-            tick_receiver,
-            data: Vec::new(),
+        let feed_status = client.feed_status();
+        let (response_sender, response_receiver) = mpsc::channel(100);
+        
+        let mut app = Self {
+            ticks_in,
+            feed_status,
+            ticks: TickStore::new(config.tick_buffer),
+            tick_window: config.tick_window,
             symbol: "Waiting for data...".to_string(),
-            balance: 0.0,
-            equity: 0.0,
-            margin: 0.0,
-            free_margin: 0.0,
-            min_lot: 0.01,
-            max_lot: 100.0,
-            lot_step: 0.01,
-            order_sender,
+            chart_mode: ChartMode::Line,
+            candles: CandleSeries::new(Timeframe::M1, config.tick_window),
+            display_zone: config.display_zone,
+            hide_gaps: true,
+            server_offset: 0,
+            account: AccountSnapshot::default(),
+            client,
+            response_sender,
             response_receiver,
-            lot_size: 0.01,
-            lot_size_str: "0.01".to_string(),
+            lot_size: config.default_lot,
+            lot_size_str: format!("{:.2}", config.default_lot),
             limit_price: "0.0".to_string(),
             stop_price: "0.0".to_string(),
+            sl_input: String::new(),
+            tp_input: String::new(),
+            stops_in_points: false,
+            risk_sizing: false,
+            risk_percent_str: format!("{:.2}", config.risk_percent),
+            risk_base: config.risk_base,
             last_order_result: None,
             
             history_start_date: today_str.clone(),
             history_end_date: today_str,
-            history_tf: "M1".to_string(),
-            history_mode: "OHLC".to_string(),
+            history_tf: Timeframe::M1,
+            history_mode: HistoryMode::Ohlc,
             
             is_recording: false,
             live_record_file: None,
@@ -214,83 +355,938 @@ impl Mt5ChartApp {
             pending_orders: Vec::new(),
             
             // Initialize new fields
+            order_breaklines: load_breaklines(&output_dir),
+            indicators: IndicatorPanel::load(&output_dir),
+            live_indicators: LiveIndicators::new(config.tick_window),
+            backtest: BacktestPanel::new(config.paper_balance, output_dir.clone()),
+            optimizer: OptimizerPanel::new(config.paper_balance),
             output_dir,
             request_counter: 0,
-            order_breaklines: Vec::new(),
-            pending_order_type: None,
-            pending_history_request: None,
+            risk: RiskEngine::new(config.risk_limits()),
+            journal: TradeJournal::default(),
+            show_analytics: false,
+            analytics_status: None,
+            strategies: {
+                let mut runtime = StrategyRuntime::new("");
+                runtime.add(Box::new(SmaCross::new(50, 200, config.default_lot)));
+                runtime
+            },
+            config,
+            in_flight: HashMap::new(),
+            
+            show_files: false,
+            file_entries: Vec::new(),
+            file_tabs: Vec::new(),
+            active_tab: None,
+            replay: None,
+            paper: None,
+            trade_edit: None,
+            level_drag: None,
+            level_hovered: false,
+        };
+        if app.config.paper_trading {
+            app.reset_paper();
         }
+        app
     }
     
-    fn send_order(&mut self, order_type: &str, price: Option<f64>, ticket: Option<u64>) {
-        let price_val = price.unwrap_or(0.0);
-        let ticket_val = ticket.unwrap_or(0);
-        
-        // Track order type for breakline visualization (only for market orders)
-        if order_type.contains("market") {
-            self.pending_order_type = Some(order_type.to_string());
+    fn send_order(&mut self, request: Request) {
+        self.send_order_from(request, None);
+    }
+    
+    /// Every order goes through the risk checks; blocked strategy orders
+    /// are reported back to the strategy.
+    fn send_order_from(&mut self, request: Request, strategy: Option<usize>) {
+        let account = AccountState {
+            equity: self.account.equity,
+            free_margin: self.account.free_margin,
+            positions: &self.positions,
+            orders: &self.pending_orders,
+        };
+        if let Err(breach) = self.risk.check(&request, &account) {
+            self.last_order_result = Some(match strategy {
+                Some(index) => {
+                    self.strategies.report_blocked(index, &request, breach.to_string());
+                    format!("⛔ Blocked ({}): {}", self.strategies.slots()[index].strategy.name(), breach)
+                }
+                None => format!("⛔ Blocked: {}", breach),
+            });
+            return;
+        }
+        let action = PendingAction::Order { request: request.clone(), strategy };
+        self.send_request_impl(request, action);
+    }
+    
+    fn send_strategy_requests(&mut self, requests: Vec<(usize, Request)>) {
+        for (index, request) in requests {
+            self.send_order_from(request, Some(index));
+        }
+    }
+    
+    /// Strategies trade live, or on replayed prices only into the paper account.
+    fn strategies_active(&self) -> bool {
+        self.replay.is_none() || self.paper.is_some()
+    }
+    
+    fn show_strategies(&mut self, ui: &mut egui::Ui) {
+        if !self.strategies_active() {
+            ui.weak("Strategies are paused during replay (switch on paper trading)");
+        }
+        for index in 0..self.strategies.slots().len() {
+            let slot = &self.strategies.slots()[index];
+            let mut enabled = slot.enabled;
+            let (name, status, sent, error) =
+                (slot.strategy.name().to_string(), slot.strategy.status(), slot.sent, slot.last_error.clone());
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut enabled, name).changed() {
+                    self.strategies.set_enabled(index, enabled);
+                }
+                ui.weak(format!("{} sent", sent));
+            });
+            if !status.is_empty() {
+                ui.weak(format!("   {}", status));
+            }
+            if let Some(error) = error {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("   {}", error));
+            }
+        }
+    }
+    
+    /// Flattens the account and locks trading until unlocked by hand.
+    fn kill_switch(&mut self) {
+        self.risk.lock();
+        self.send_order(Request::CancelAll { symbol: ALL_SYMBOLS.to_string() });
+        self.send_order(Request::CloseAll { symbol: ALL_SYMBOLS.to_string(), filter: CloseFilter::All });
+        self.last_order_result = Some("🛑 Kill switch: closing all positions and orders, trading locked".to_string());
+    }
+    
+    fn show_risk_status(&mut self, ui: &mut egui::Ui) {
+        let limits = *self.risk.limits();
+        let loss = self.risk.daily_loss(self.account.equity);
+        let mut text = format!("Today {:+.2}", -loss);
+        if limits.max_daily_loss > 0.0 {
+            text.push_str(&format!(" (limit -{:.2})", limits.max_daily_loss));
+        }
+        let breached = limits.max_daily_loss > 0.0 && loss >= limits.max_daily_loss;
+        ui.horizontal(|ui| {
+            if breached {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), text + " — new orders blocked");
+            } else {
+                ui.weak(text);
+            }
+        });
+        ui.horizontal(|ui| {
+            if self.risk.is_locked() {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), "🔒 Trading locked");
+                if ui.small_button("Unlock").on_hover_text("Allow new orders again").clicked() {
+                    self.risk.unlock();
+                    self.last_order_result = Some("🔓 Trading unlocked".to_string());
+                }
+            } else {
+                let kill = egui::Button::new(egui::RichText::new("🛑 KILL SWITCH").color(egui::Color32::WHITE))
+                    .fill(egui::Color32::from_rgb(160, 30, 30));
+                if ui.add(kill).on_hover_text("Close every position, cancel every order and lock trading").clicked() {
+                    self.kill_switch();
+                }
+            }
+        });
+    }
+    
+    /// Sends an order with the volume and SL/TP from Trade Controls, resolved
+    /// against `entry` (the pending price, or bid/ask for market orders), or
+    /// reports why they are invalid. `make` gets (volume, sl, tp).
+    fn send_order_with_stops(&mut self, side: Side, entry: Option<f64>, make: impl FnOnce(f64, f64, f64) -> Request) {
+        let order = self.order_stops(side, entry).and_then(|(sl, tp)| Ok((self.order_volume(side, entry)?, sl, tp)));
+        match order {
+            Ok((volume, sl, tp)) => self.send_order(make(volume, sl, tp)),
+            Err(e) => self.last_order_result = Some(format!("✗ Not sent: {}", e)),
+        }
+    }
+    
+    /// The pending price, or the bid/ask a market order on `side` fills at.
+    fn entry_price(&self, side: Side, entry: Option<f64>) -> Result<f64, String> {
+        let market = self.ticks.last().map(|t| if side == Side::Buy { t.ask } else { t.bid });
+        entry.or(market).ok_or_else(|| "no price yet to place SL/TP against".to_string())
+    }
+    
+    /// Volume of the next order: the Trade Controls lots, or sized from the
+    /// risk % and the SL distance in risk mode.
+    fn order_volume(&self, side: Side, entry: Option<f64>) -> Result<f64, String> {
+        match self.risk_sizing {
+            true => self.risk_size(side, entry).map(|size| size.lots),
+            false => Ok(self.lot_size),
+        }
+    }
+    
+    /// Account money the risk % stands for.
+    fn risk_budget(&self) -> Result<f64, String> {
+        let percent: f64 = self.risk_percent_str.trim().parse()
+            .map_err(|_| format!("risk % '{}' is not a number", self.risk_percent_str.trim()))?;
+        if !(percent > 0.0 && percent <= 100.0) {
+            return Err(format!("risk % must be in (0, 100], got {}", percent));
+        }
+        let base = match self.risk_base {
+            RiskBase::Balance => self.account.balance,
+            RiskBase::Equity => self.account.equity,
+        };
+        Ok(base * percent / 100.0)
+    }
+    
+    fn risk_size(&self, side: Side, entry: Option<f64>) -> Result<PositionSize, String> {
+        let (sl, _) = self.order_stops(side, entry)?;
+        if sl == 0.0 {
+            return Err("set an SL to size by risk".to_string());
+        }
+        let distance = (self.entry_price(side, entry)? - sl).abs();
+        sizing::size_for_risk(self.risk_budget()?, distance, &self.account.spec())
+    }
+    
+    /// Money lost at the SL with `lots`, None without a valid SL.
+    fn risk_at_stop(&self, side: Side, lots: f64) -> Option<f64> {
+        let (sl, _) = self.order_stops(side, None).ok()?;
+        let entry = self.entry_price(side, None).ok()?;
+        (sl != 0.0).then(|| self.account.spec().loss_per_lot((entry - sl).abs()).map(|loss| loss * lots))?
+    }
+    
+    /// Money at risk of a BUY and a SELL at market, shown above the buttons.
+    fn show_risk_preview(&self, ui: &mut egui::Ui) {
+        let base = match self.risk_base {
+            RiskBase::Balance => self.account.balance,
+            RiskBase::Equity => self.account.equity,
+        };
+        let percent = |money: f64| if base > 0.0 { money / base * 100.0 } else { 0.0 };
+        for side in [Side::Buy, Side::Sell] {
+            let name = if side == Side::Buy { "BUY" } else { "SELL" };
+            if !self.risk_sizing {
+                if let Some(risk) = self.risk_at_stop(side, self.lot_size) {
+                    ui.weak(format!("{} {:.2} lots risks {:.2} ({:.2}%) at SL", name, self.lot_size, risk, percent(risk)));
+                }
+                continue;
+            }
+            match self.risk_size(side, None) {
+                Ok(size) => {
+                    let text = format!("{} {:.2} lots risks {:.2} ({:.2}%)", name, size.lots, size.risk, percent(size.risk));
+                    match size.limit {
+                        Some(SizeLimit::BelowMinLot) => ui.colored_label(
+                            egui::Color32::from_rgb(255, 150, 50),
+                            format!("{} — min lot exceeds the {:.2} budget", text, size.budget),
+                        ),
+                        Some(SizeLimit::AboveMaxLot) => ui.label(format!("{} — capped at max lot", text)),
+                        None => ui.label(text),
+                    };
+                }
+                Err(e) => {
+                    ui.weak(format!("{}: {}", name, e));
+                }
+            }
+        }
+    }
+    
+    fn order_stops(&self, side: Side, entry: Option<f64>) -> Result<(f64, f64), String> {
+        let parse = |text: &str, name: &str| -> Result<Option<StopLevel>, String> {
+            let text = text.trim();
+            if text.is_empty() {
+                return Ok(None);
+            }
+            let value: f64 = text.parse().map_err(|_| format!("{} '{}' is not a number", name, text))?;
+            Ok((value != 0.0).then_some(if self.stops_in_points { StopLevel::Points(value) } else { StopLevel::Price(value) }))
+        };
+        let (sl, tp) = (parse(&self.sl_input, "SL")?, parse(&self.tp_input, "TP")?);
+        if sl.is_none() && tp.is_none() {
+            return Ok((0.0, 0.0));
         }
         
-        let request = OrderRequest {
-            order_type: order_type.to_string(),
+        let entry = self.entry_price(side, entry)?;
+        let point = self.account.point;
+        let resolve = |level: Option<StopLevel>, take_profit: bool| {
+            level.map_or(0.0, |l| (l.resolve(side, entry, point, take_profit) / point).round() * point)
+        };
+        let (sl, tp) = (resolve(sl, false), resolve(tp, true));
+        sum3api::check_stops(side, entry, sl, tp)?;
+        Ok((sl, tp))
+    }
+    
+    /// Validates new stops against the position's closing price and sends
+    /// `modify_position`. Returns whether it was sent.
+    fn modify_position(&mut self, ticket: u64, sl: f64, tp: f64) -> bool {
+        let Some(pos) = self.positions.iter().find(|p| p.ticket == ticket) else {
+            self.last_order_result = Some(format!("✗ Position #{} is no longer open", ticket));
+            return false;
+        };
+        let side = if pos.pos_type == "BUY" { Side::Buy } else { Side::Sell };
+        if let Some(last) = self.ticks.last() {
+            let close = if side == Side::Buy { last.bid } else { last.ask };
+            if let Err(e) = sum3api::check_stops(side, close, sl, tp) {
+                self.last_order_result = Some(format!("✗ Not sent: {}", e));
+                return false;
+            }
+        }
+        self.send_order(Request::ModifyPosition { symbol: self.symbol.clone(), ticket, sl, tp });
+        true
+    }
+    
+    /// Closes the Trade Controls lot size of a position, after checking it
+    /// against the lot step and what would be left open.
+    fn close_partial(&mut self, ticket: u64) {
+        let Some(pos) = self.positions.iter().find(|p| p.ticket == ticket) else {
+            self.last_order_result = Some(format!("✗ Position #{} is no longer open", ticket));
+            return;
+        };
+        let step = self.account.lot_step;
+        let volume = (self.lot_size / step).round() * step;
+        let remaining = ((pos.volume - volume) / step).round() * step;
+        let error = if volume <= 0.0 {
+            Some(format!("close volume {:.2} is below one lot step", self.lot_size))
+        } else if remaining < 0.0 {
+            Some(format!("close volume {:.2} exceeds the position's {:.2}", volume, pos.volume))
+        } else if remaining > 0.0 && remaining < self.account.min_lot {
+            Some(format!("{:.2} would be left open, below the minimum {:.2}", remaining, self.account.min_lot))
+        } else {
+            None
+        };
+        match error {
+            Some(e) => self.last_order_result = Some(format!("✗ Not sent: {}", e)),
+            None => self.send_order(Request::ClosePartial { symbol: self.symbol.clone(), ticket, volume }),
+        }
+    }
+    
+    /// Validates the stops against the new order price and sends `modify_order`.
+    fn modify_order(&mut self, ticket: u64, price: f64, sl: f64, tp: f64, expiration: i64) -> bool {
+        let Some(order) = self.pending_orders.iter().find(|o| o.ticket == ticket) else {
+            self.last_order_result = Some(format!("✗ Order #{} is no longer pending", ticket));
+            return false;
+        };
+        let side = if order.order_type.starts_with("BUY") { Side::Buy } else { Side::Sell };
+        if let Err(e) = sum3api::check_stops(side, price, sl, tp) {
+            self.last_order_result = Some(format!("✗ Not sent: {}", e));
+            return false;
+        }
+        self.send_order(Request::ModifyOrder { symbol: self.symbol.clone(), ticket, price, sl, tp, expiration });
+        true
+    }
+    
+    /// Sends the modification entered in the side panel editor.
+    fn apply_trade_edit(&mut self) {
+        let Some(edit) = self.trade_edit.clone() else { return };
+        let parse = |text: &str, name: &str| -> Result<f64, String> {
+            match text.trim() {
+                "" => Ok(0.0),
+                text => text.parse().map_err(|_| format!("{} '{}' is not a number", name, text)),
+            }
+        };
+        let values = parse(&edit.sl, "SL").and_then(|sl| Ok((sl, parse(&edit.tp, "TP")?)));
+        let (sl, tp) = match values {
+            Ok(values) => values,
+            Err(e) => {
+                self.last_order_result = Some(format!("✗ Not sent: {}", e));
+                return;
+            }
+        };
+        let sent = if !edit.order {
+            self.modify_position(edit.ticket, sl, tp)
+        } else {
+            let price = match parse(&edit.price, "Price") {
+                Ok(price) if price > 0.0 => price,
+                _ => {
+                    self.last_order_result = Some(format!("✗ Not sent: invalid price '{}'", edit.price));
+                    return;
+                }
+            };
+            let expiration = match edit.expiry.trim() {
+                "" => 0,
+                text => match history::parse_time(text) {
+                    Some(time) => time,
+                    None => {
+                        self.last_order_result = Some(format!("✗ Not sent: expiry '{}' is not yyyy.mm.dd HH:MM", text));
+                        return;
+                    }
+                },
+            };
+            self.modify_order(edit.ticket, price, sl, tp, expiration)
+        };
+        if sent {
+            self.trade_edit = None;
+        }
+    }
+    
+    /// A trade line was dropped on the chart: move that price of the trade there.
+    fn apply_level_drag(&mut self, drag: LevelDrag) {
+        let point = self.account.point;
+        let price = (drag.price / point).round() * point;
+        let LevelId { ticket, order, kind } = drag.id;
+        if order {
+            let Some(o) = self.pending_orders.iter().find(|o| o.ticket == ticket) else { return };
+            let (mut entry, mut sl, mut tp, expiration) = (o.price, o.sl, o.tp, o.expiration);
+            match kind {
+                LevelKind::Entry => entry = price,
+                LevelKind::StopLoss => sl = price,
+                LevelKind::TakeProfit => tp = price,
+            }
+            self.modify_order(ticket, entry, sl, tp, expiration);
+        } else {
+            let Some(p) = self.positions.iter().find(|p| p.ticket == ticket) else { return };
+            let (mut sl, mut tp) = (p.sl, p.tp);
+            match kind {
+                LevelKind::Entry => return,
+                LevelKind::StopLoss => sl = price,
+                LevelKind::TakeProfit => tp = price,
+            }
+            self.modify_position(ticket, sl, tp);
+        }
+    }
+    
+    /// Chart lines for positions, pending orders and their SL/TP. Pending
+    /// order prices and all SL/TP lines can be dragged.
+    fn trade_levels(&self) -> Vec<Level> {
+        let mut levels = Vec::new();
+        let add_stops = |levels: &mut Vec<Level>, ticket: u64, order: bool, sl: f64, tp: f64| {
+            for (price, kind, label, color) in [
+                (sl, LevelKind::StopLoss, "SL", egui::Color32::from_rgb(255, 80, 80)),
+                (tp, LevelKind::TakeProfit, "TP", egui::Color32::from_rgb(0, 200, 100)),
+            ] {
+                if price > 0.0 {
+                    levels.push(Level {
+                        id: LevelId { ticket, order, kind },
+                        price,
+                        name: format!("{} #{}", label, ticket),
+                        color,
+                        style: egui_plot::LineStyle::Dotted { spacing: 6.0 },
+                        draggable: true,
+                    });
+                }
+            }
+        };
+        for pos in &self.positions {
+            levels.push(Level {
+                id: LevelId { ticket: pos.ticket, order: false, kind: LevelKind::Entry },
+                price: pos.price,
+                name: format!("{} #{}", pos.pos_type, pos.ticket),
+                color: if pos.pos_type == "BUY" {
+                    egui::Color32::from_rgb(50, 100, 255)
+                } else {
+                    egui::Color32::from_rgb(255, 50, 50)
+                },
+                style: egui_plot::LineStyle::Dashed { length: 10.0 },
+                draggable: false,
+            });
+            add_stops(&mut levels, pos.ticket, false, pos.sl, pos.tp);
+        }
+        for order in &self.pending_orders {
+            levels.push(Level {
+                id: LevelId { ticket: order.ticket, order: true, kind: LevelKind::Entry },
+                price: order.price,
+                name: format!("{} #{}", order.order_type, order.ticket),
+                color: if order.order_type.contains("BUY") {
+                    egui::Color32::from_rgb(100, 150, 255)
+                } else {
+                    egui::Color32::from_rgb(255, 150, 100)
+                },
+                style: egui_plot::LineStyle::Dashed { length: 4.0 },
+                draggable: true,
+            });
+            add_stops(&mut levels, order.ticket, true, order.sl, order.tp);
+        }
+        levels
+    }
+    
+    /// Editor row under a position or pending order while it is being edited.
+    fn show_trade_edit(&mut self, ui: &mut egui::Ui, ticket: u64, order: bool) {
+        let Some(edit) = self.trade_edit.as_mut().filter(|e| e.ticket == ticket && e.order == order) else { return };
+        let (mut apply, mut cancel) = (false, false);
+        ui.indent(("trade_edit", ticket), |ui| {
+            if order {
+                ui.horizontal(|ui| {
+                    ui.label("Price:");
+                    ui.add(egui::TextEdit::singleline(&mut edit.price).desired_width(70.0));
+                    ui.label("Expiry:");
+                    ui.add(egui::TextEdit::singleline(&mut edit.expiry).desired_width(110.0).hint_text("GTC"))
+                        .on_hover_text("Server time yyyy.mm.dd HH:MM, empty = good till cancelled");
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.label("SL:");
+                ui.add(egui::TextEdit::singleline(&mut edit.sl).desired_width(70.0).hint_text("none"));
+                ui.label("TP:");
+                ui.add(egui::TextEdit::singleline(&mut edit.tp).desired_width(70.0).hint_text("none"));
+                apply = ui.small_button("Modify").clicked();
+                cancel = ui.small_button("✕").clicked();
+            });
+        });
+        if apply {
+            self.apply_trade_edit();
+        } else if cancel {
+            self.trade_edit = None;
+        }
+    }
+    
+    fn send_download_request(&mut self) {
+        let request = Request::DownloadHistory {
             symbol: self.symbol.clone(),
-            volume: self.lot_size,
-            price: price_val,
-            ticket: ticket_val,
-            timeframe: None,
-            start: None,
-            end: None,
-            mode: None,
-            request_id: None,
+            timeframe: self.history_tf,
+            start: self.history_start_date.clone(),
+            end: self.history_end_date.clone(),
+            mode: self.history_mode,
         };
         
-        self.send_request_impl(request);
+        // Keep request info for CSV filename generation when the response arrives
+        let action = PendingAction::History {
+            symbol: self.symbol.replace("/", "-"),
+            timeframe: self.history_tf,
+            mode: self.history_mode,
+        };
+        self.send_request_impl(request, action);
     }
     
-    fn send_download_request(&mut self) {
-        // Increment counter for unique history download ID
+    fn send_request_impl(&mut self, request: Request, action: PendingAction) {
+        // Every request gets a unique ID; it also numbers the output files
         self.request_counter += 1;
+        let id = self.request_counter;
+        let request = OrderRequest { request, request_id: Some(id) };
         
-        // Store request info for CSV filename generation when response arrives
-        self.pending_history_request = Some((
-            self.request_counter,
-            self.symbol.replace("/", "-"),
-            self.history_tf.clone(),
-            self.history_mode.clone(),
-        ));
+        // Paper mode: trade requests are answered at once by the simulated broker
+        if let (Some(paper), PendingAction::Order { .. }) = (self.paper.as_mut(), &action) {
+            let mut response = paper.execute(&request.request);
+            response.request_id = Some(id);
+            self.in_flight.insert(id, action);
+            let _ = self.response_sender.try_send(response);
+            self.sync_paper_account();
+            return;
+        }
+        self.in_flight.insert(id, action);
         
-        let request = OrderRequest {
-            order_type: "download_history".to_string(),
-            symbol: self.symbol.clone(),
-            volume: 0.0,
-            price: 0.0,
-            ticket: 0,
-            timeframe: Some(self.history_tf.clone()),
-            start: Some(self.history_start_date.clone()),
-            end: Some(self.history_end_date.clone()),
-            mode: Some(self.history_mode.clone()),
-            request_id: Some(self.request_counter),
+        // Await the reply off the UI thread and hand it back through the channel
+        let client = self.client.clone();
+        let response_sender = self.response_sender.clone();
+        tokio::spawn(async move {
+            let response = client.request(request).await.unwrap_or_else(|e| {
+                let mut failed = OrderResponse::failure(format!("Failed to send: {}", e));
+                failed.request_id = Some(id);
+                failed
+            });
+            let _ = response_sender.send(response).await;
+        });
+        self.last_order_result = Some(format!("Request #{} sent...", id));
+    }
+    
+    fn handle_response(&mut self, response: OrderResponse) {
+        let action = response.request_id.and_then(|id| self.in_flight.remove(&id));
+        if let Some(PendingAction::Order { request, strategy: Some(index) }) = &action {
+            let requests = self.strategies.on_order_response(*index, request, &response);
+            self.send_strategy_requests(requests);
+        }
+        
+        if response.timed_out {
+            self.handle_timed_out_response(response, action);
+            return;
+        }
+        
+        if !response.success {
+            let details = response.results.as_deref().map(format_ticket_results).unwrap_or_default();
+            self.last_order_result = Some(describe_failure(&response) + &details);
+            return;
+        }
+        
+        match action {
+            Some(PendingAction::History { symbol, timeframe, mode }) => {
+                let id = response.request_id.unwrap_or(0);
+                let msg = response.message.unwrap_or_default();
+                self.save_history_csv(id, &symbol, timeframe.as_str(), mode.as_str(), &msg);
+            }
+            Some(PendingAction::Order { request, .. }) => {
+                // Add breakline for successful market orders, at the fill reported
+                // by the EA (older EA builds: the latest tick)
+                if let (true, Some(side)) = (request.is_market(), request.side()) {
+                    let last = self.ticks.last().copied();
+                    let time = response.time.or(last.map(|t| t.time));
+                    let price = response.price.or(last.map(|t| if side == Side::Buy { t.ask } else { t.bid }));
+                    if let (Some(time), Some(price)) = (time, price) {
+                        self.add_breakline(OrderBreakline {
+                            symbol: request.symbol().to_string(),
+                            time,
+                            price,
+                            side,
+                            ticket: response.ticket.unwrap_or(0),
+                        });
+                    }
+                }
+                let details = response.results.as_deref().map(format_ticket_results).unwrap_or_default();
+                let summary = match (response.message, &request) {
+                    (Some(msg), _) => format!("✓ {}", msg),
+                    (None, Request::ModifyPosition { ticket, .. }) => format!("✓ Position #{} modified", ticket),
+                    (None, Request::ModifyOrder { ticket, .. }) => format!("✓ Order #{} modified", ticket),
+                    (None, Request::ClosePartial { ticket, volume, .. }) => format!("✓ Closed {:.2} of #{}", volume, ticket),
+                    (None, Request::CloseAll { filter, .. }) => {
+                        let closed = response.results.as_ref().map_or(0, |r| r.len());
+                        match filter {
+                            CloseFilter::All => format!("✓ Closed {} position(s)", closed),
+                            CloseFilter::Winners => format!("✓ Closed {} winning position(s)", closed),
+                            CloseFilter::Losers => format!("✓ Closed {} losing position(s)", closed),
+                        }
+                    }
+                    (None, Request::CancelAll { .. }) => {
+                        format!("✓ Cancelled {} order(s)", response.results.as_ref().map_or(0, |r| r.len()))
+                    }
+                    (None, Request::Reverse { ticket, .. }) => {
+                        format!("✓ Reversed #{} → #{}", ticket, response.ticket.unwrap_or(0))
+                    }
+                    (None, _) => format!("✓ Order executed! Ticket: {}", response.ticket.unwrap_or(0)),
+                };
+                self.last_order_result = Some(summary + &details);
+            }
+            None => {
+                self.last_order_result = Some(match response.message {
+                    Some(msg) => format!("✓ {}", msg.split(sum3api::CSV_DATA_MARKER).next().unwrap_or_default()),
+                    None => format!("✓ Ticket: {} (unmatched response)", response.ticket.unwrap_or(0)),
+                });
+            }
+        }
+    }
+    
+    fn save_history_csv(&mut self, id: u64, symbol: &str, tf: &str, mode: &str, msg: &str) {
+        // Parse CSV data from response
+        let Some(history) = HistoryData::parse(msg) else {
+            self.last_order_result = Some(format!("✓ {}", msg));
+            return;
         };
         
-        self.send_request_impl(request);
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let filename = format!(
+            "{}/History_{}_{}_{}_ID{:04}_{}.csv",
+            self.output_dir.display(),
+            symbol, tf, mode, id, timestamp
+        );
+        
+        // Save CSV to output folder
+        self.last_order_result = Some(match std::fs::write(&filename, history.csv) {
+            Ok(_) => format!("✓ {} → Saved to {}", history.info, filename),
+            Err(e) => format!("✗ Failed to save CSV: {}", e),
+        });
     }
     
-    fn send_request_impl(&mut self, request: OrderRequest) {
-        if let Err(e) = self.order_sender.try_send(request) {
-            self.last_order_result = Some(format!("Failed to send: {}", e));
-        } else {
-            self.last_order_result = Some("Request sent...".to_string());
+    fn set_candle_timeframe(&mut self, timeframe: Timeframe) {
+        if timeframe != self.candles.timeframe() {
+            self.candles = CandleSeries::from_ticks(timeframe, self.tick_window, self.ticks.iter());
+            self.rebuild_indicators();
+        }
+    }
+    
+    /// Recomputes the live chart indicators from the ticks and candles it holds.
+    fn rebuild_indicators(&mut self) {
+        self.live_indicators.rebuild(
+            self.indicators.configs(),
+            self.indicators.revision(),
+            self.ticks.latest(self.tick_window),
+            self.candles.closed(),
+        );
+    }
+    
+    fn add_breakline(&mut self, breakline: OrderBreakline) {
+        self.order_breaklines.push(breakline);
+        // Keep only last 50 breaklines
+        if self.order_breaklines.len() > 50 {
+            self.order_breaklines.remove(0);
+        }
+        let path = self.output_dir.join(BREAKLINES_FILE);
+        let saved = serde_json::to_string_pretty(&self.order_breaklines)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+        if let Err(e) = saved {
+            eprintln!("Failed to save {}: {}", path.display(), e);
         }
     }
     
     fn adjust_lot_size(&mut self, delta: f64) {
         let new_lot = self.lot_size + delta;
         // Round to lot_step
-        let steps = (new_lot / self.lot_step).round();
-        self.lot_size = (steps * self.lot_step).max(self.min_lot).min(self.max_lot);
+        let steps = (new_lot / self.account.lot_step).round();
+        self.lot_size = (steps * self.account.lot_step).max(self.account.min_lot).min(self.account.max_lot);
         self.lot_size_str = format!("{:.2}", self.lot_size);
     }
     
+    /// The EA never answered. Compare the positions it reported after the
+    /// reconnect with what we knew before, to guess whether a market order filled.
+    fn handle_timed_out_response(&mut self, response: OrderResponse, action: Option<PendingAction>) {
+        let error = response.error.unwrap_or_else(|| "Timed out, outcome unknown".to_string());
+        let market_order = match action {
+            Some(PendingAction::Order { request, .. }) if request.is_market() => Some(request),
+            _ => None,
+        };
+        
+        let Some(positions) = response.positions else {
+            self.last_order_result = Some(format!(
+                "⚠ {} (position check unavailable, verify in MT5)",
+                error
+            ));
+            return;
+        };
+        
+        let known: Vec<u64> = self.positions.iter().map(|p| p.ticket).collect();
+        let new_tickets: Vec<u64> = positions
+            .iter()
+            .map(|p| p.ticket)
+            .filter(|t| !known.contains(t))
+            .collect();
+        
+        self.last_order_result = Some(match (market_order, new_tickets.is_empty()) {
+            (Some(_), false) => format!(
+                "⚠ {}. After reconnect found new position(s) {:?}, order likely filled",
+                error, new_tickets
+            ),
+            (Some(_), true) => format!(
+                "⚠ {}. After reconnect no new position found, order likely not filled",
+                error
+            ),
+            (None, _) => format!("⚠ {}. {} open position(s) after reconnect", error, positions.len()),
+        });
+        
+        self.positions = positions;
+        if let Some(orders) = response.orders {
+            self.pending_orders = orders;
+        }
+    }
+    
+    fn show_feed_status(&self, ui: &mut egui::Ui) {
+        let status = self.feed_status.borrow().clone();
+        let (color, label) = match status.state {
+            FeedState::Connected => (egui::Color32::from_rgb(100, 200, 100), "Connected"),
+            FeedState::Stale => (egui::Color32::from_rgb(255, 200, 100), "Stale"),
+            FeedState::Disconnected => (egui::Color32::from_rgb(255, 100, 100), "Disconnected"),
+        };
+        let response = ui.colored_label(color, format!("● {}", label));
+        if let Some(last) = status.last_tick {
+            ui.label(format!("last tick {:.1}s ago", last.elapsed().as_secs_f64()));
+        }
+        let mut hover = format!("Reconnects: {}", status.reconnects);
+        if let Some(err) = status.last_error {
+            hover.push_str(&format!("\nLast error: {}", err));
+        }
+        response.on_hover_text(hover);
+    }
+    
+    fn refresh_files(&mut self) {
+        match files::list_csv_files(&self.output_dir) {
+            Ok(entries) => self.file_entries = entries,
+            Err(e) => {
+                self.file_entries.clear();
+                self.last_order_result = Some(format!("✗ Cannot list {}: {}", self.output_dir.display(), e));
+            }
+        }
+    }
+    
+    fn open_file_tab(&mut self, path: PathBuf) {
+        // Already open: just switch to it
+        if let Some(index) = self.file_tabs.iter().position(|t| t.path == path) {
+            self.active_tab = Some(index);
+            return;
+        }
+        match FileTab::open(&path) {
+            Ok(tab) => {
+                self.last_order_result = Some(format!("✓ Opened {}", tab.title));
+                self.file_tabs.push(tab);
+                self.active_tab = Some(self.file_tabs.len() - 1);
+            }
+            Err(e) => self.last_order_result = Some(format!("✗ Cannot open file: {}", e)),
+        }
+    }
+    
+    fn close_file_tab(&mut self, index: usize) {
+        self.file_tabs.remove(index);
+        self.active_tab = match self.active_tab {
+            Some(active) if active == index => None,
+            Some(active) if active > index => Some(active - 1),
+            other => other,
+        };
+    }
+    
+    fn start_replay(&mut self, index: usize) {
+        let tab = &self.file_tabs[index];
+        let symbol = tab.symbol.clone().unwrap_or_else(|| tab.title.clone());
+        let title = tab.title.clone();
+        let replay = Replay::start(title.clone(), symbol, tab.ticks().to_vec(), &self.ticks_in, self.tick_window);
+        self.replay = Some(replay);
+        self.active_tab = None;
+        self.clear_live_chart();
+        if self.paper.is_some() {
+            self.reset_paper();
+        }
+        self.last_order_result = Some(format!("Replaying {} - press ▶ to start", title));
+    }
+    
+    fn stop_replay(&mut self) {
+        // Dropping the replay resumes the live feed
+        self.replay = None;
+        self.clear_live_chart();
+        if self.paper.is_some() {
+            self.reset_paper();
+        }
+        self.last_order_result = Some("Replay stopped, back to live feed".to_string());
+    }
+    
+    fn seek_replay(&mut self, position: usize) {
+        self.clear_live_chart();
+        if let Some(replay) = &self.replay {
+            replay.seek(position);
+        }
+    }
+    
+    /// Empties the live chart when switching between live and replayed ticks.
+    fn clear_live_chart(&mut self) {
+        while self.ticks_in.receiver.try_recv().is_ok() {}
+        self.ticks.clear();
+        self.candles.clear();
+        self.rebuild_indicators();
+        self.positions.clear();
+        self.pending_orders.clear();
+    }
+    
+    fn show_replay_controls(&mut self, ui: &mut egui::Ui) {
+        let Some(replay) = &self.replay else { return };
+        let status = replay.status();
+        let mut stop = false;
+        let mut seek = None;
+        ui.horizontal(|ui| {
+            ui.label(format!("⏪ Replay: {}", replay.title));
+            if status.playing {
+                if ui.button("⏸").on_hover_text("Pause").clicked() {
+                    replay.pause();
+                }
+            } else if ui.button("▶").on_hover_text("Play").clicked() {
+                replay.play();
+            }
+            
+            // Scrub through the file; seeks when the slider is released
+            let mut position = status.position;
+            let response = ui.add(
+                egui::Slider::new(&mut position, 0..=status.total)
+                    .show_value(false)
+            );
+            if response.drag_stopped() || (response.changed() && !response.dragged()) {
+                seek = Some(position);
+            }
+            ui.label(format!("{} ({}/{})", history::format_time(status.time, true), status.position, status.total));
+            
+            egui::ComboBox::from_id_source("replay_speed")
+                .selected_text(format!("{}x", status.speed))
+                .show_ui(ui, |ui| {
+                    for speed in replay::SPEEDS {
+                        if ui.selectable_label(status.speed == speed, format!("{}x", speed)).clicked() {
+                            replay.set_speed(speed);
+                        }
+                    }
+                });
+            if ui.button("⏹ Stop").on_hover_text("End the replay and return to the live feed").clicked() {
+                stop = true;
+            }
+        });
+        if let Some(position) = seek {
+            self.seek_replay(position);
+        }
+        if stop {
+            self.stop_replay();
+        }
+    }
+    
+    /// Switches paper trading on with a fresh account, or off.
+    fn set_paper_trading(&mut self, enabled: bool) {
+        if enabled {
+            self.reset_paper();
+            self.last_order_result = Some(format!(
+                "📝 Paper trading on: orders are simulated (balance ${:.2})",
+                self.config.paper_balance
+            ));
+        } else {
+            self.paper = None;
+            self.risk.reset_day();
+            self.journal.reset();
+            self.positions.clear();
+            self.pending_orders.clear();
+            self.last_order_result = Some("Paper trading off: orders go to MT5".to_string());
+        }
+    }
+    
+    /// Metrics of the account shown: the paper account's closed trades, or
+    /// the trades the journal saw close on the EA account.
+    fn show_analytics_window(&mut self, ctx: &egui::Context) {
+        let (mode, records, initial_balance) = match &self.paper {
+            Some(paper) => {
+                let contract_size = paper.settings().contract_size;
+                let records: Vec<TradeRecord> =
+                    paper.closed_trades().iter().map(|t| TradeRecord::from_closed(t, contract_size)).collect();
+                ("Paper", records, paper.settings().initial_balance)
+            }
+            None => ("Live", self.journal.records().to_vec(), self.journal.initial_balance()),
+        };
+        let equity = self.journal.equity();
+        let report = analytics::analyze(initial_balance, &records, equity);
+        let title = format!("{} {}", mode, self.symbol);
+
+        let mut open = self.show_analytics;
+        egui::Window::new("📈 Analytics")
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                ui.heading(&title);
+                if records.is_empty() {
+                    ui.weak("No closed trades yet");
+                }
+                analytics_panel::show_report(ui, "account", &report, equity);
+                let export = ReportExport { name: &title, title: &title, report: &report, trades: &records, equity };
+                analytics_panel::export_buttons(ui, &self.output_dir, &export, &mut self.analytics_status);
+            });
+        self.show_analytics = open;
+    }
+    
+    /// Simulated broker for paper trading and backtests: paper account
+    /// settings, with the symbol rules from the EA.
+    fn sim_settings(&self) -> SimSettings {
+        SimSettings {
+            symbol: self.symbol.clone(),
+            initial_balance: self.config.paper_balance,
+            contract_size: self.config.paper_contract_size,
+            leverage: self.config.paper_leverage,
+            min_lot: self.account.min_lot,
+            max_lot: self.account.max_lot,
+            lot_step: self.account.lot_step,
+            point: self.account.point,
+            ..SimSettings::default()
+        }
+    }
+    
+    /// Starts a new paper account on the current symbol and price.
+    fn reset_paper(&mut self) {
+        let mut broker = SimBroker::new(self.sim_settings());
+        if let Some(last) = self.ticks.last() {
+            broker.update_price(last.bid, last.ask, last.time);
+        }
+        self.paper = Some(broker);
+        self.sync_paper_account();
+        self.risk.reset_day();
+        self.journal.reset();
+    }
+    
+    /// Shows the paper account in place of the EA's account, positions and orders.
+    fn sync_paper_account(&mut self) {
+        let Some(paper) = &self.paper else { return };
+        self.positions = paper.positions_data();
+        self.pending_orders = paper.orders_data();
+        self.account = AccountSnapshot {
+            balance: paper.balance(),
+            equity: paper.equity(),
+            margin: paper.margin(),
+            free_margin: paper.free_margin(),
+            ..self.account
+        };
+    }
+    
+    fn chart_view(&self) -> ChartView {
+        ChartView {
+            zone: self.display_zone,
+            hide_gaps: self.hide_gaps,
+            server_offset: self.server_offset,
+            allow_pan: true,
+        }
+    }
+    
     fn toggle_recording(&mut self) {
         self.is_recording = !self.is_recording;
         if self.is_recording {
@@ -323,117 +1319,159 @@ impl Mt5ChartApp {
 
 impl eframe::App for Mt5ChartApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Indicator setup changed (or first frame): recompute before new ticks
+        if !self.live_indicators.is_current(self.indicators.revision()) {
+            self.rebuild_indicators();
+        }
+        
         // Receive all available tick data from the channel without blocking
-        while let Ok(tick) = self.tick_receiver.try_recv() {
-            self.symbol = tick.symbol.clone();
+        while let Ok(tick) = self.ticks_in.receiver.try_recv() {
+            let symbol_changed = self.symbol != tick.symbol;
+            if symbol_changed {
+                self.symbol = tick.symbol.clone();
+                self.strategies.set_symbol(&tick.symbol);
+            }
             
-            // Record if active
-            if self.is_recording {
-                if let Some(mut file) = self.live_record_file.as_ref() {
-                     let _ = writeln!(file, "{},{},{},{}", tick.time, tick.bid, tick.ask, tick.volume);
-                }
+            // A tick still in flight from before a seek; start over from the seek target
+            let replaying = self.replay.is_some();
+            if replaying && self.ticks.last().is_some_and(|last| tick.time < last.time) {
+                self.ticks.clear();
+                self.candles.clear();
+                self.rebuild_indicators();
+            }
+            
+            // Record if active (live ticks only)
+            if self.is_recording && !replaying && let Some(mut file) = self.live_record_file.as_ref() {
+                let _ = writeln!(file, "{},{},{},{}", tick.time, tick.bid, tick.ask, tick.volume);
             }
             
-            // Update account info from latest tick
+            // Update account info from latest tick; in paper mode only the lot rules
             if tick.balance > 0.0 {
-                self.balance = tick.balance;
-                self.equity = tick.equity;
-                self.margin = tick.margin;
-                self.free_margin = tick.free_margin;
-                self.min_lot = tick.min_lot;
-                self.max_lot = tick.max_lot;
-                if tick.lot_step > 0.0 {
-                    self.lot_step = tick.lot_step;
-                }
+                let money = match self.paper {
+                    Some(_) => self.account,
+                    None => AccountSnapshot {
+                        balance: tick.balance,
+                        equity: tick.equity,
+                        margin: tick.margin,
+                        free_margin: tick.free_margin,
+                        ..self.account
+                    },
+                };
+                self.account = AccountSnapshot {
+                    min_lot: tick.min_lot,
+                    max_lot: tick.max_lot,
+                    lot_step: if tick.lot_step > 0.0 { tick.lot_step } else { self.account.lot_step },
+                    point: if tick.point > 0.0 { tick.point } else { self.account.point },
+                    ..money
+                };
+                // Paper profits follow the paper contract size, not the broker's
+                let spec = match &self.paper {
+                    Some(paper) => SymbolSpec::from_tick(&paper.tick_data(0)),
+                    None => SymbolSpec::from_tick(&tick),
+                };
+                self.account.tick_value = spec.tick_value;
+                self.account.tick_size = spec.tick_size;
+                self.account.contract_size = spec.contract_size;
+                self.risk.update_day(tick.time, self.account.balance);
             }
             
-            // Update active trades
-            self.positions = tick.positions.clone();
-            self.pending_orders = tick.orders.clone();
+            // Whole quarter hours; TimeTradeServer() - TimeGMT() can be a second off.
+            // Replayed ticks carry no offset, so keep the live one.
+            if !replaying {
+                self.server_offset = (tick.server_offset as f64 / 900.0).round() as i64 * 900;
+            }
             
-            self.data.push(tick);
-            // Keep only last 2000 points
-            if self.data.len() > 2000 {
-                self.data.remove(0);
+            // Only prices go into the tick history
+            let tick_data = tick;
+            let tick = Tick::from(&tick_data);
+            if let Some(bar) = self.candles.push(&tick) {
+                self.live_indicators.push_bar(&bar);
             }
-        }
-        
-        // Check for order responses
-        while let Ok(response) = self.response_receiver.try_recv() {
-            if response.success {
-                // Check if this is a history download with CSV data
-                if let Some(ref msg) = response.message {
-                    if msg.contains("||CSV_DATA||") {
-                        // Parse CSV data from response
-                        let parts: Vec<&str> = msg.splitn(2, "||CSV_DATA||").collect();
-                        if parts.len() == 2 {
-                            let info_part = parts[0];
-                            let csv_content = parts[1];
-                            
-                            // Generate filename using pending request info
-                            if let Some((id, symbol, tf, mode)) = self.pending_history_request.take() {
-                                let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-                                let filename = format!(
-                                    "{}/History_{}_{}_{}_ID{:04}_{}.csv",
-                                    self.output_dir.display(),
-                                    symbol, tf, mode, id, timestamp
-                                );
-                                
-                                // Convert |NL| placeholders back to real newlines
-                                let csv_with_newlines = csv_content.replace("|NL|", "\n");
-                                
-                                // Save CSV to output folder
-                                match std::fs::write(&filename, csv_with_newlines) {
-                                    Ok(_) => {
-                                        self.last_order_result = Some(format!(
-                                            "✓ {} → Saved to {}",
-                                            info_part, filename
-                                        ));
-                                    }
-                                    Err(e) => {
-                                        self.last_order_result = Some(format!(
-                                            "✗ Failed to save CSV: {}",
-                                            e
-                                        ));
-                                    }
-                                }
-                            } else {
-                                self.last_order_result = Some(format!("✓ {}", info_part));
+            self.live_indicators.push_tick(&tick);
+            self.ticks.push(tick);
+            
+            // Update active trades, from the EA or the paper account
+            match &mut self.paper {
+                Some(_) if symbol_changed => self.reset_paper(),
+                Some(paper) => {
+                    // Pending orders crossed by this tick fill as positions; SL/TP may close some
+                    let closed_before = paper.closed_trades().len();
+                    let mut filled = Vec::new();
+                    for (ticket, result) in paper.update_price(tick.bid, tick.ask, tick.time) {
+                        match result {
+                            Ok(()) => filled.push(ticket),
+                            Err(e) => {
+                                self.last_order_result = Some(format!("✗ Paper order #{} dropped: {}", ticket, e));
                             }
-                        } else {
-                            self.last_order_result = Some(format!("✓ {}", msg));
                         }
-                    } else {
-                        self.last_order_result = Some(format!("✓ {}", msg));
                     }
-                } else {
-                    // Add breakline for successful market orders
-                    if let Some(ref order_type) = self.pending_order_type.take() {
-                        let breakline = OrderBreakline {
-                            index: self.data.len().saturating_sub(1),
-                            order_type: order_type.clone(),
-                            ticket: response.ticket.unwrap_or(0),
-                        };
-                        self.order_breaklines.push(breakline);
-                        // Keep only last 50 breaklines
-                        if self.order_breaklines.len() > 50 {
-                            self.order_breaklines.remove(0);
-                        }
+                    for trade in &paper.closed_trades()[closed_before..] {
+                        let reason = if trade.reason == CloseReason::StopLoss { "stop-loss" } else { "take-profit" };
+                        self.last_order_result = Some(format!(
+                            "Paper position #{} closed by {} at {:.5} ({:+.2})",
+                            trade.ticket, reason, trade.close_price, trade.profit
+                        ));
                     }
-                    
-                    self.last_order_result = Some(format!(
-                        "✓ Order executed! Ticket: {}",
-                        response.ticket.unwrap_or(0)
-                    ));
+                    let fills: Vec<OrderBreakline> = paper
+                        .positions()
+                        .iter()
+                        .filter(|p| filled.contains(&p.ticket))
+                        .map(|p| OrderBreakline {
+                            symbol: self.symbol.clone(),
+                            time: p.open_time,
+                            price: p.open_price,
+                            side: p.side,
+                            ticket: p.ticket as i64,
+                        })
+                        .collect();
+                    for fill in fills {
+                        self.last_order_result = Some(format!("✓ Paper order #{} filled at {:.5}", fill.ticket, fill.price));
+                        self.add_breakline(fill);
+                    }
+                    self.sync_paper_account();
                 }
-            } else {
-                self.pending_order_type = None; // Clear pending on failure
-                self.pending_history_request = None; // Clear pending history request
-                self.last_order_result = Some(format!(
-                    "✗ Failed: {}",
-                    response.error.unwrap_or_else(|| "Unknown error".to_string())
-                ));
+                None => {
+                    self.positions = tick_data.positions.clone();
+                    self.pending_orders = tick_data.orders.clone();
+                    // Replayed ticks carry no positions
+                    if !replaying {
+                        let contract_size = self.account.contract_size;
+                        self.journal.update(&self.positions, tick.time, tick.bid, tick.ask, contract_size);
+                    }
+                }
+            }
+            if self.paper.is_some() || !replaying {
+                self.journal.snapshot(tick.time, self.account.balance, self.account.equity);
             }
+            
+            // Strategies see the tick with the account and trades shown in the
+            // panel (the paper account's in paper mode)
+            if self.strategies_active() {
+                let tick_data = TickData {
+                    balance: self.account.balance,
+                    equity: self.account.equity,
+                    margin: self.account.margin,
+                    free_margin: self.account.free_margin,
+                    positions: self.positions.clone(),
+                    orders: self.pending_orders.clone(),
+                    ..tick_data
+                };
+                let mut requests = self.strategies.on_position_update(&self.positions, &self.pending_orders);
+                requests.extend(self.strategies.on_tick(&tick_data));
+                self.send_strategy_requests(requests);
+            }
+        }
+        
+        // Check for order responses
+        while let Ok(response) = self.response_receiver.try_recv() {
+            self.handle_response(response);
+        }
+        
+        // Strategy timers, and trades changed by order replies
+        if self.strategies_active() {
+            let mut requests = self.strategies.on_position_update(&self.positions, &self.pending_orders);
+            requests.extend(self.strategies.on_timer(chrono::Utc::now().timestamp()));
+            self.send_strategy_requests(requests);
         }
 
         // ====================================================================
@@ -446,22 +1484,23 @@ impl eframe::App for Mt5ChartApp {
                 ui.separator();
                 
                 // Account Info
-                ui.collapsing("💰 Account Info", |ui| {
+                let account_title = if self.paper.is_some() { "💰 Account Info (paper)" } else { "💰 Account Info" };
+                ui.collapsing(account_title, |ui| {
                     egui::Grid::new("account_grid")
                         .num_columns(2)
                         .spacing([10.0, 4.0])
                         .show(ui, |ui| {
                             ui.label("Balance:");
-                            ui.colored_label(egui::Color32::from_rgb(100, 200, 100), format!("${:.2}", self.balance));
+                            ui.colored_label(egui::Color32::from_rgb(100, 200, 100), format!("${:.2}", self.account.balance));
                             ui.end_row();
                             ui.label("Equity:");
-                            ui.colored_label(egui::Color32::from_rgb(100, 180, 255), format!("${:.2}", self.equity));
+                            ui.colored_label(egui::Color32::from_rgb(100, 180, 255), format!("${:.2}", self.account.equity));
                             ui.end_row();
                             ui.label("Margin Used:");
-                            ui.colored_label(egui::Color32::from_rgb(255, 200, 100), format!("${:.2}", self.margin));
+                            ui.colored_label(egui::Color32::from_rgb(255, 200, 100), format!("${:.2}", self.account.margin));
                             ui.end_row();
                             ui.label("Free Margin:");
-                            ui.colored_label(egui::Color32::from_rgb(100, 255, 200), format!("${:.2}", self.free_margin));
+                            ui.colored_label(egui::Color32::from_rgb(100, 255, 200), format!("${:.2}", self.account.free_margin));
                             ui.end_row();
                         });
                 });
@@ -483,22 +1522,20 @@ impl eframe::App for Mt5ChartApp {
                     
                     ui.label("Timeframe:");
                     egui::ComboBox::from_id_source("tf_combo")
-                        .selected_text(&self.history_tf)
+                        .selected_text(self.history_tf.as_str())
                         .show_ui(ui, |ui| {
-                            ui.selectable_value(&mut self.history_tf, "M1".to_string(), "M1");
-                            ui.selectable_value(&mut self.history_tf, "M5".to_string(), "M5");
-                            ui.selectable_value(&mut self.history_tf, "M15".to_string(), "M15");
-                            ui.selectable_value(&mut self.history_tf, "H1".to_string(), "H1");
-                            ui.selectable_value(&mut self.history_tf, "D1".to_string(), "D1");
+                            for tf in Timeframe::ALL {
+                                ui.selectable_value(&mut self.history_tf, tf, tf.as_str());
+                            }
                         });
                     ui.end_row();
                     
                     ui.label("Mode:");
                     egui::ComboBox::from_id_source("mode_combo")
-                        .selected_text(&self.history_mode)
+                        .selected_text(self.history_mode.as_str())
                         .show_ui(ui, |ui| {
-                             ui.selectable_value(&mut self.history_mode, "OHLC".to_string(), "OHLC");
-                             ui.selectable_value(&mut self.history_mode, "TICKS".to_string(), "TICKS");
+                             ui.selectable_value(&mut self.history_mode, HistoryMode::Ohlc, "OHLC");
+                             ui.selectable_value(&mut self.history_mode, HistoryMode::Ticks, "TICKS");
                         });
                     ui.end_row();
                 });
@@ -523,43 +1560,109 @@ impl eframe::App for Mt5ChartApp {
 
                 // Order Controls
                 ui.heading("📦 Trade Controls");
+                let mut paper_mode = self.paper.is_some();
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut paper_mode, "📝 Paper trading")
+                        .on_hover_text("Send orders to a simulated broker filled at the chart's bid/ask")
+                        .changed()
+                    {
+                        self.set_paper_trading(paper_mode);
+                    }
+                    if self.paper.is_some() && ui.small_button("Reset").on_hover_text("Start a fresh paper account").clicked() {
+                        self.reset_paper();
+                        self.last_order_result = Some("📝 Paper account reset".to_string());
+                    }
+                });
+                if let Some(paper) = &self.paper {
+                    let closed = paper.closed_trades();
+                    ui.weak(format!(
+                        "{} closed trades, realized {:+.2}",
+                        closed.len(),
+                        closed.iter().map(|t| t.profit).sum::<f64>()
+                    ));
+                }
+                
+                self.show_risk_status(ui);
                 
                 // Lot Size
                 ui.horizontal(|ui| {
-                    if ui.button("−").clicked() { self.adjust_lot_size(-self.lot_step); }
+                    if ui.button("−").clicked() { self.adjust_lot_size(-self.account.lot_step); }
                     let response = ui.add(egui::TextEdit::singleline(&mut self.lot_size_str).desired_width(60.0));
-                    if response.lost_focus() {
-                        if let Ok(parsed) = self.lot_size_str.parse::<f64>() {
-                            self.lot_size = parsed.max(self.min_lot).min(self.max_lot);
-                            self.lot_size_str = format!("{:.2}", self.lot_size);
-                        }
+                    if response.lost_focus() && let Ok(parsed) = self.lot_size_str.parse::<f64>() {
+                        self.lot_size = parsed.max(self.account.min_lot).min(self.account.max_lot);
+                        self.lot_size_str = format!("{:.2}", self.lot_size);
                     }
-                    if ui.button("+").clicked() { self.adjust_lot_size(self.lot_step); }
+                    if ui.button("+").clicked() { self.adjust_lot_size(self.account.lot_step); }
                     
-                    ui.label(format!("Lots (Max: {:.1})", self.max_lot));
+                    ui.label(format!("Lots (Max: {:.1})", self.account.max_lot));
                 });
                 
-                ui.add_space(5.0);
-                ui.label("Market Orders:");
+                // Risk sizing: BUY/SELL volume from a % of the account and the SL
                 ui.horizontal(|ui| {
-                    if ui.button("BUY").clicked() { self.send_order("market_buy", None, None); }
-                    if ui.button("SELL").clicked() { self.send_order("market_sell", None, None); }
+                    ui.checkbox(&mut self.risk_sizing, "Risk")
+                        .on_hover_text("Size orders so hitting the SL loses this % of the account");
+                    ui.add_enabled(self.risk_sizing, egui::TextEdit::singleline(&mut self.risk_percent_str).desired_width(40.0));
+                    ui.label("% of");
+                    ui.selectable_value(&mut self.risk_base, RiskBase::Balance, "Balance");
+                    ui.selectable_value(&mut self.risk_base, RiskBase::Equity, "Equity");
                 });
                 
-                ui.add_space(5.0);
-                ui.label("Pending Orders:");
-                 ui.horizontal(|ui| {
-                    ui.label("@ Price:");
-                    ui.add(egui::TextEdit::singleline(&mut self.limit_price).desired_width(70.0));
-                });
+                // Stop-loss / take-profit for the next order (empty = none)
                 ui.horizontal(|ui| {
-                    let p = self.limit_price.parse().unwrap_or(0.0);
-                    if ui.small_button("Buy Limit").clicked() { self.send_order("limit_buy", Some(p), None); }
-                    if ui.small_button("Sell Limit").clicked() { self.send_order("limit_sell", Some(p), None); }
-                    if ui.small_button("Buy Stop").clicked() { self.send_order("stop_buy", Some(p), None); }
-                    if ui.small_button("Sell Stop").clicked() { self.send_order("stop_sell", Some(p), None); }
+                    ui.label("SL:");
+                    ui.add(egui::TextEdit::singleline(&mut self.sl_input).desired_width(60.0));
+                    ui.label("TP:");
+                    ui.add(egui::TextEdit::singleline(&mut self.tp_input).desired_width(60.0));
+                    ui.selectable_value(&mut self.stops_in_points, false, "Price");
+                    ui.selectable_value(&mut self.stops_in_points, true, "Points")
+                        .on_hover_text(format!("Distance from the entry price (1 point = {})", self.account.point));
+                });
+                self.show_risk_preview(ui);
+                
+                ui.add_space(5.0);
+                ui.add_enabled_ui(self.replay.is_none() || self.paper.is_some(), |ui| {
+                    if self.replay.is_some() && self.paper.is_none() {
+                        ui.weak("Orders are disabled during replay (switch on paper trading)");
+                    }
+                    ui.label("Market Orders:");
+                    ui.horizontal(|ui| {
+                        let symbol = self.symbol.clone();
+                        if ui.button("BUY").clicked() {
+                            self.send_order_with_stops(Side::Buy, None, |volume, sl, tp| Request::MarketBuy { symbol: symbol.clone(), volume, sl, tp });
+                        }
+                        if ui.button("SELL").clicked() {
+                            self.send_order_with_stops(Side::Sell, None, |volume, sl, tp| Request::MarketSell { symbol, volume, sl, tp });
+                        }
+                    });
+                
+                    ui.add_space(5.0);
+                    ui.label("Pending Orders:");
+                    ui.horizontal(|ui| {
+                        ui.label("@ Price:");
+                        ui.add(egui::TextEdit::singleline(&mut self.limit_price).desired_width(70.0));
+                    });
+                    ui.horizontal(|ui| {
+                        let price = self.limit_price.parse().unwrap_or(0.0);
+                        let symbol = self.symbol.clone();
+                        if ui.small_button("Buy Limit").clicked() {
+                            self.send_order_with_stops(Side::Buy, Some(price), |volume, sl, tp| Request::LimitBuy { symbol: symbol.clone(), volume, price, sl, tp });
+                        }
+                        if ui.small_button("Sell Limit").clicked() {
+                            self.send_order_with_stops(Side::Sell, Some(price), |volume, sl, tp| Request::LimitSell { symbol: symbol.clone(), volume, price, sl, tp });
+                        }
+                        if ui.small_button("Buy Stop").clicked() {
+                            self.send_order_with_stops(Side::Buy, Some(price), |volume, sl, tp| Request::StopBuy { symbol: symbol.clone(), volume, price, sl, tp });
+                        }
+                        if ui.small_button("Sell Stop").clicked() {
+                            self.send_order_with_stops(Side::Sell, Some(price), |volume, sl, tp| Request::StopSell { symbol, volume, price, sl, tp });
+                        }
+                    });
                 });
 
+                ui.separator();
+                
+                ui.collapsing("🤖 Strategies", |ui| self.show_strategies(ui));
+                
                 ui.separator();
 
                 // Order result feedback
@@ -575,6 +1678,17 @@ impl eframe::App for Mt5ChartApp {
                     if self.positions.is_empty() {
                         ui.label("No active positions");
                     } else {
+                        ui.horizontal(|ui| {
+                            for (label, filter) in [
+                                ("Close all", CloseFilter::All),
+                                ("Close winners", CloseFilter::Winners),
+                                ("Close losers", CloseFilter::Losers),
+                            ] {
+                                if ui.small_button(label).clicked() {
+                                    self.send_order(Request::CloseAll { symbol: self.symbol.clone(), filter });
+                                }
+                            }
+                        });
                         let positions_clone = self.positions.clone();
                         for pos in positions_clone {
                             ui.horizontal(|ui| {
@@ -584,13 +1698,30 @@ impl eframe::App for Mt5ChartApp {
                                     egui::Color32::from_rgb(255, 100, 100)
                                 };
                                 ui.colored_label(color, format!(
-                                    "#{} {} {:.2}@{:.5} P:{:.2}",
-                                    pos.ticket, pos.pos_type, pos.volume, pos.price, pos.profit
+                                    "#{} {} {:.2}@{:.5} P:{:.2}{}",
+                                    pos.ticket, pos.pos_type, pos.volume, pos.price, pos.profit, format_stops(pos.sl, pos.tp)
                                 ));
                                 if ui.small_button("Close").clicked() {
-                                    self.send_order("close_position", Some(pos.price), Some(pos.ticket));
+                                    self.send_order(Request::ClosePosition { symbol: self.symbol.clone(), ticket: pos.ticket });
+                                }
+                                if ui.small_button("Part").on_hover_text(format!("Close {:.2} lots (Trade Controls volume)", self.lot_size)).clicked() {
+                                    self.close_partial(pos.ticket);
+                                }
+                                if ui.small_button("Reverse").on_hover_text("Close, then open the same volume the other way").clicked() {
+                                    self.send_order(Request::Reverse { symbol: self.symbol.clone(), ticket: pos.ticket });
+                                }
+                                if ui.small_button("Edit").on_hover_text("Change SL/TP").clicked() {
+                                    self.trade_edit = Some(TradeEdit {
+                                        ticket: pos.ticket,
+                                        order: false,
+                                        price: String::new(),
+                                        sl: format_price(pos.sl),
+                                        tp: format_price(pos.tp),
+                                        expiry: String::new(),
+                                    });
                                 }
                             });
+                            self.show_trade_edit(ui, pos.ticket, false);
                         }
                     }
                 });
@@ -600,6 +1731,9 @@ impl eframe::App for Mt5ChartApp {
                     if self.pending_orders.is_empty() {
                         ui.label("No pending orders");
                     } else {
+                        if ui.small_button("Cancel all").clicked() {
+                            self.send_order(Request::CancelAll { symbol: self.symbol.clone() });
+                        }
                         let orders_clone = self.pending_orders.clone();
                         for order in orders_clone {
                             ui.horizontal(|ui| {
@@ -608,19 +1742,78 @@ impl eframe::App for Mt5ChartApp {
                                 } else {
                                     egui::Color32::from_rgb(255, 150, 100)
                                 };
+                                let expiry = match order.expiration {
+                                    0 => String::new(),
+                                    time => format!(" exp {}", history::format_time(time, false)),
+                                };
                                 ui.colored_label(color, format!(
-                                    "#{} {} {:.2}@{:.5}",
-                                    order.ticket, order.order_type, order.volume, order.price
+                                    "#{} {} {:.2}@{:.5}{}{}",
+                                    order.ticket, order.order_type, order.volume, order.price, format_stops(order.sl, order.tp), expiry
                                 ));
                                 if ui.small_button("Cancel").clicked() {
-                                    self.send_order("cancel_order", Some(order.price), Some(order.ticket));
+                                    self.send_order(Request::CancelOrder { symbol: self.symbol.clone(), ticket: order.ticket });
+                                }
+                                if ui.small_button("Edit").on_hover_text("Change price, SL/TP or expiry").clicked() {
+                                    self.trade_edit = Some(TradeEdit {
+                                        ticket: order.ticket,
+                                        order: true,
+                                        price: format_price(order.price),
+                                        sl: format_price(order.sl),
+                                        tp: format_price(order.tp),
+                                        expiry: match order.expiration {
+                                            0 => String::new(),
+                                            time => history::format_time(time, false),
+                                        },
+                                    });
                                 }
                             });
+                            self.show_trade_edit(ui, order.ticket, true);
                         }
                     }
                 });
             });
 
+        // ====================================================================
+        // Right Panel - Output Folder Browser
+        // ====================================================================
+        if self.show_files {
+            let mut open = None;
+            egui::SidePanel::right("files_panel")
+                .min_width(260.0)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.heading("🗂 Output Files");
+                        if ui.small_button("⟳").on_hover_text("Refresh").clicked() {
+                            self.refresh_files();
+                        }
+                    });
+                    ui.weak(self.output_dir.display().to_string());
+                    ui.separator();
+                    
+                    if self.file_entries.is_empty() {
+                        ui.label("No CSV files yet");
+                    }
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for entry in &self.file_entries {
+                            let modified = entry.modified
+                                .map(|m| chrono::DateTime::<chrono::Local>::from(m).format("%Y.%m.%d %H:%M").to_string())
+                                .unwrap_or_default();
+                            let response = ui.selectable_label(false, &entry.name).on_hover_text(format!(
+                                "{:.1} KB, modified {}\nClick to open in a chart tab",
+                                entry.size as f64 / 1024.0,
+                                modified
+                            ));
+                            if response.clicked() {
+                                open = Some(entry.path.clone());
+                            }
+                        }
+                    });
+                });
+            if let Some(path) = open {
+                self.open_file_tab(path);
+            }
+        }
+
         // ====================================================================
         // Central Panel - Chart
         // ====================================================================
@@ -628,85 +1821,233 @@ impl eframe::App for Mt5ChartApp {
             ui.heading(format!("📈 {}", self.symbol));
             
             // Header Info
-            if let Some(last_tick) = self.data.last() {
-                ui.horizontal(|ui| {
+            ui.horizontal(|ui| {
+                if self.replay.is_some() {
+                    ui.colored_label(egui::Color32::from_rgb(255, 200, 100), "⏪ Replay (live feed paused)");
+                } else {
+                    self.show_feed_status(ui);
+                }
+                if self.paper.is_some() {
+                    ui.separator();
+                    ui.colored_label(egui::Color32::from_rgb(100, 180, 255), "📝 PAPER");
+                }
+                ui.separator();
+                if let Some(last_tick) = self.ticks.last() {
                     ui.label(format!("{:.5} / {:.5}", last_tick.bid, last_tick.ask));
-                });
+                    ui.separator();
+                }
+                ui.weak(format!("Buffer {}/{} ticks", self.ticks.len(), self.ticks.capacity()));
+                ui.separator();
+                ui.weak(format!(
+                    "Config: {} | Ticks {} | Orders {}",
+                    self.config.source, self.config.tick_address, self.config.order_address
+                ));
+            });
+            
+            // Chart Tabs: live feed plus any opened CSV files
+            let mut close_tab = None;
+            ui.horizontal_wrapped(|ui| {
+                if ui.selectable_label(self.active_tab.is_none(), "● Live").clicked() {
+                    self.active_tab = None;
+                }
+                for (i, tab) in self.file_tabs.iter().enumerate() {
+                    if ui.selectable_label(self.active_tab == Some(i), &tab.title)
+                        .on_hover_text(tab.path.display().to_string())
+                        .clicked()
+                    {
+                        self.active_tab = Some(i);
+                    }
+                    if ui.small_button("✕").clicked() {
+                        close_tab = Some(i);
+                    }
+                }
+                ui.separator();
+                if ui.selectable_label(self.indicators.open, "📊 Indicators").clicked() {
+                    self.indicators.open = !self.indicators.open;
+                }
+                if ui.selectable_label(self.show_analytics, "📈 Analytics").clicked() {
+                    self.show_analytics = !self.show_analytics;
+                }
+                if ui.selectable_label(self.backtest.open, "🧪 Backtest").clicked() {
+                    self.backtest.open = !self.backtest.open;
+                }
+                if ui.selectable_label(self.optimizer.open, "🔬 Optimize").clicked() {
+                    self.optimizer.open = !self.optimizer.open;
+                }
+                if ui.selectable_label(self.show_files, "🗂 Files").clicked() {
+                    self.show_files = !self.show_files;
+                    if self.show_files {
+                        self.refresh_files();
+                    }
+                }
+            });
+            if let Some(index) = close_tab {
+                self.close_file_tab(index);
             }
             
+            // Chart Controls
+            ui.horizontal(|ui| {
+                let (mode, timeframe, has_ticks) = match self.active_tab {
+                    Some(i) => {
+                        let tab = &mut self.file_tabs[i];
+                        let (timeframe, has_ticks) = (tab.timeframe(), tab.has_ticks());
+                        (&mut tab.mode, timeframe, has_ticks)
+                    }
+                    None => (&mut self.chart_mode, self.candles.timeframe(), true),
+                };
+                let mut timeframe_choice = timeframe;
+                if has_ticks {
+                    ui.selectable_value(mode, ChartMode::Line, "Line");
+                    ui.selectable_value(mode, ChartMode::Candles, "Candles");
+                }
+                if *mode == ChartMode::Candles || !has_ticks {
+                    ui.separator();
+                    if has_ticks {
+                        for tf in Timeframe::ALL {
+                            ui.selectable_value(&mut timeframe_choice, tf, tf.as_str());
+                        }
+                    } else {
+                        ui.label(format!("{} bars", timeframe.as_str()));
+                    }
+                }
+                match self.active_tab {
+                    Some(i) => self.file_tabs[i].set_timeframe(timeframe_choice),
+                    None => self.set_candle_timeframe(timeframe_choice),
+                }
+                ui.separator();
+                egui::ComboBox::from_id_source("zone_combo")
+                    .selected_text(self.display_zone.as_str())
+                    .show_ui(ui, |ui| {
+                        for zone in DisplayZone::ALL {
+                            ui.selectable_value(&mut self.display_zone, zone, zone.as_str());
+                        }
+                    });
+                ui.checkbox(&mut self.hide_gaps, "Hide gaps")
+                    .on_hover_text("Squeeze weekends and session breaks out of the time axis");
+            });
+            
             ui.separator();
-
-            // Price chart - Index-based X Axis
-            let time_map: Vec<i64> = self.data.iter().map(|t| t.time).collect();
             
-            let plot = Plot::new("mt5_price_plot")
-                .legend(egui_plot::Legend::default())
-                .allow_boxed_zoom(true)
-                .allow_drag(true)
-                .allow_scroll(true)
-                .allow_zoom(true)
-                .x_axis_formatter(move |x, _range, _width| {
-                    let idx = x.value.round() as isize;
-                    if idx >= 0 && (idx as usize) < time_map.len() {
-                        let timestamp = time_map[idx as usize];
-                        let seconds = timestamp % 60;
-                        let minutes = (timestamp / 60) % 60;
-                        let hours = (timestamp / 3600) % 24;
-                        return format!("{:02}:{:02}:{:02}", hours, minutes, seconds);
+            let view = self.chart_view();
+            if let Some(i) = self.active_tab {
+                self.file_tabs[i].update_indicators(self.indicators.configs(), self.indicators.revision());
+                let tab = &self.file_tabs[i];
+                let (mut replay, mut backtest, mut optimize) = (false, false, false);
+                ui.horizontal(|ui| {
+                    ui.weak(format!("{} | {}", tab.symbol.as_deref().unwrap_or("?"), tab.summary()));
+                    if tab.has_ticks() {
+                        replay = ui.button("▶ Replay")
+                            .on_hover_text("Play these ticks through the live chart")
+                            .clicked();
                     }
-                    "".to_string()
+                    backtest = ui.button("🧪 Backtest")
+                        .on_hover_text("Run a strategy over this file")
+                        .clicked();
+                    optimize = ui.button("🔬 Optimize")
+                        .on_hover_text("Search strategy parameters on this file")
+                        .clicked();
                 });
+                let indicators = tab.indicator_plots(self.indicators.configs());
+                let data = tab.chart_data(&indicators);
+                chart::show_chart(ui, &format!("file_plot_{}", tab.path.display()), &data, view, |_, _| {});
+                if replay {
+                    self.start_replay(i);
+                }
+                if backtest {
+                    self.backtest.select_file(&self.file_tabs[i].path);
+                }
+                if optimize {
+                    self.optimizer.select_file(&self.file_tabs[i].path);
+                }
+                return;
+            }
+            
+            self.show_replay_controls(ui);
 
-            plot.show(ui, |plot_ui| {
-                let bid_points: PlotPoints = self.data
-                    .iter()
-                    .enumerate()
-                    .map(|(i, t)| [i as f64, t.bid])
-                    .collect();
-                
-                let ask_points: PlotPoints = self.data
-                    .iter()
-                    .enumerate()
-                    .map(|(i, t)| [i as f64, t.ask])
-                    .collect();
-
-                plot_ui.line(Line::new(bid_points).name("Bid").color(egui::Color32::from_rgb(100, 200, 100)));
-                plot_ui.line(Line::new(ask_points).name("Ask").color(egui::Color32::from_rgb(200, 100, 100)));
-                
-                // Draw Active Positions (horizontal lines)
-                for pos in &self.positions {
-                    let color = if pos.pos_type == "BUY" {
-                        egui::Color32::from_rgb(50, 100, 255) 
-                    } else {
-                        egui::Color32::from_rgb(255, 50, 50) 
-                    };
-                    
-                    plot_ui.hline(
-                        egui_plot::HLine::new(pos.price)
-                            .color(color)
-                            .name(format!("{} #{}", pos.pos_type, pos.ticket))
-                            .style(egui_plot::LineStyle::Dashed { length: 10.0 })
-                    );
+            // Live chart - the newest tick_window ticks, or the newest candles
+            // (the last one may still be forming)
+            let ticks: Vec<Tick> = self.ticks.latest(self.tick_window).copied().collect();
+            let bars: Vec<_> = match self.chart_mode {
+                ChartMode::Line => Vec::new(),
+                ChartMode::Candles => {
+                    let closed = self.candles.closed();
+                    let forming = self.candles.forming().copied();
+                    let skip = (closed.len() + forming.is_some() as usize).saturating_sub(MAX_VISIBLE_CANDLES);
+                    closed.iter().copied().chain(forming).skip(skip).collect()
                 }
+            };
+            let configs = self.indicators.configs();
+            let indicators = self.live_indicators.plots(configs, self.chart_mode, self.candles.forming());
+            let data = ChartData {
+                mode: self.chart_mode,
+                ticks: &ticks,
+                bars: &bars,
+                bar_seconds: self.candles.timeframe().seconds(),
+                forming: self.candles.forming().is_some(),
+                indicators: &indicators,
+            };
+            let time_range = data.time_range();
+            
+            // Trade lines; grabbing one turns off panning so it can be dragged
+            let levels = self.trade_levels();
+            let mut level_drag = self.level_drag.take();
+            let mut interaction = levels::LevelInteraction::default();
+            let view = ChartView { allow_pan: !self.level_hovered, ..view };
+            
+            chart::show_chart(ui, "mt5_price_plot", &data, view, |plot_ui, axis| {
+                interaction = levels::show_levels(plot_ui, &levels, &mut level_drag);
                 
-                // Draw Order Breaklines (vertical lines at execution points)
-                for breakline in &self.order_breaklines {
-                    let color = if breakline.order_type.contains("buy") {
+                // Draw Order Breaklines at the fill time, while it is inside the visible data
+                let Some((first, last)) = time_range else { return };
+                for breakline in self.order_breaklines
+                    .iter()
+                    .filter(|b| b.symbol == self.symbol && b.time >= first && b.time <= last)
+                {
+                    let x = axis.to_x(breakline.time);
+                    let color = if breakline.side == Side::Buy {
                         egui::Color32::from_rgb(0, 200, 100) // Bright green for BUY
                     } else {
                         egui::Color32::from_rgb(255, 80, 80) // Bright red for SELL
                     };
                     
                     plot_ui.vline(
-                        egui_plot::VLine::new(breakline.index as f64)
+                        egui_plot::VLine::new(x)
                             .color(color)
                             .name(format!("Order #{}", breakline.ticket))
                             .width(2.0)
                     );
+                    plot_ui.points(
+                        egui_plot::Points::new(vec![[x, breakline.price]])
+                            .shape(egui_plot::MarkerShape::Diamond)
+                            .radius(5.0)
+                            .color(color)
+                            .name(format!("Order #{}", breakline.ticket))
+                    );
                 }
             });
+            
+            self.level_drag = level_drag;
+            self.level_hovered = interaction.hovered;
+            if let Some(drag) = interaction.released {
+                self.apply_level_drag(drag);
+            }
         });
 
+        if self.indicators.open {
+            self.indicators.show(ctx);
+        }
+        if self.show_analytics {
+            self.show_analytics_window(ctx);
+        }
+        if self.backtest.open {
+            let base = self.sim_settings();
+            self.backtest.show(ctx, &base);
+        }
+        if self.optimizer.open {
+            let base = self.sim_settings();
+            self.optimizer.show(ctx, &base);
+        }
+        
         // Request a repaint to update the chart continuously
         ctx.request_repaint();
     }
@@ -718,118 +2059,30 @@ impl eframe::App for Mt5ChartApp {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Channels for tick data
-    let (tick_tx, tick_rx) = mpsc::channel(100);
-    
-    // Channels for order requests and responses
-    let (order_tx, mut order_rx) = mpsc::channel::<OrderRequest>(10);
-    let (response_tx, response_rx) = mpsc::channel::<OrderResponse>(10);
-
-    // ========================================================================
-    // Spawn ZMQ Tick Subscriber task
-    // ========================================================================
-    tokio::spawn(async move {
-        let mut socket = zeromq::SubSocket::new();
-        match socket.connect("tcp://127.0.0.1:5555").await {
-            Ok(_) => println!("Connected to ZMQ Tick Publisher on port 5555"),
-            Err(e) => eprintln!("Failed to connect to ZMQ tick publisher: {}", e),
-        }
-        
-        let _ = socket.subscribe("").await;
-
-        loop {
-            match socket.recv().await {
-                Ok(msg) => {
-                    if let Some(payload_bytes) = msg.get(0) {
-                        if let Ok(json_str) = std::str::from_utf8(payload_bytes) {
-                            match serde_json::from_str::<TickData>(json_str) {
-                                Ok(tick) => {
-                                    if let Err(e) = tick_tx.send(tick).await {
-                                        eprintln!("Tick channel error: {}", e);
-                                        break;
-                                    }
-                                }
-                                Err(e) => eprintln!("JSON Parse Error: {}. Msg: {}", e, json_str),
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("ZMQ Tick Recv Error: {}", e);
-                    tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-                }
-            }
+    // Load settings before anything connects, so bad values fail fast
+    let config = match AppConfig::load(&Cli::parse()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Configuration error: {}", e);
+            std::process::exit(2);
         }
-    });
-
-    // ========================================================================
-    // Spawn ZMQ Order Request/Response task
-    // ========================================================================
+    };
+    println!("Loaded config from {}", config.source);
+    
+    // Tick feed and order handler run inside the sum3api client
+    let client = Client::new(config.client_settings());
+    
+    // Forward the client's tick stream into the chart's channel, unless a replay has it
+    let ticks_in = TickPipeline::new(100);
+    let (tick_tx, live_paused) = (ticks_in.sender.clone(), ticks_in.live_paused.clone());
+    let mut ticks = Box::pin(client.subscribe_ticks());
     tokio::spawn(async move {
-        let mut socket = zeromq::ReqSocket::new();
-        match socket.connect("tcp://127.0.0.1:5556").await {
-            Ok(_) => println!("Connected to ZMQ Order Handler on port 5556"),
-            Err(e) => {
-                eprintln!("Failed to connect to ZMQ order handler: {}", e);
-                return;
-            }
-        }
-
-        while let Some(order_request) = order_rx.recv().await {
-            // Serialize order request to JSON
-            let json_request = match serde_json::to_string(&order_request) {
-                Ok(json) => json,
-                Err(e) => {
-                    eprintln!("Failed to serialize order request: {}", e);
-                    continue;
-                }
-            };
-            
-            println!("Sending request: {}", json_request);
-            
-            // Send request
-            if let Err(e) = socket.send(json_request.into()).await {
-                eprintln!("Failed to send: {}", e);
-                let _ = response_tx.send(OrderResponse {
-                    success: false,
-                    ticket: None,
-                    error: Some(format!("Send failed: {}", e)),
-                    message: None,
-                }).await;
+        while let Some(tick) = ticks.next().await {
+            if live_paused.load(std::sync::atomic::Ordering::Relaxed) {
                 continue;
             }
-            
-            // Wait for response
-            match socket.recv().await {
-                Ok(msg) => {
-                    if let Some(payload_bytes) = msg.get(0) {
-                        if let Ok(json_str) = std::str::from_utf8(payload_bytes) {
-                            println!("Received response: {}", json_str);
-                            match serde_json::from_str::<OrderResponse>(json_str) {
-                                Ok(response) => {
-                                    let _ = response_tx.send(response).await;
-                                }
-                                Err(e) => {
-                                    let _ = response_tx.send(OrderResponse {
-                                        success: false,
-                                        ticket: None,
-                                        error: Some(format!("Parse error: {}", e)),
-                                        message: None,
-                                    }).await;
-                                }
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Response recv error: {}", e);
-                    let _ = response_tx.send(OrderResponse {
-                        success: false,
-                        ticket: None,
-                        error: Some(format!("Recv failed: {}", e)),
-                        message: None,
-                    }).await;
-                }
+            if tick_tx.send(tick).await.is_err() {
+                break;
             }
         }
     });
@@ -847,6 +2100,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     eframe::run_native(
         "Rust + ZMQ + MT5 Trading Chart",
         options,
-        Box::new(|_cc| Box::new(Mt5ChartApp::new(tick_rx, order_tx, response_rx))),
+        Box::new(|_cc| Box::new(Mt5ChartApp::new(config, client, ticks_in))),
     ).map_err(|e| e.into())
 }
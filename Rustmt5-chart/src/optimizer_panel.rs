@@ -0,0 +1,511 @@
+//+------------------------------------------------------------------+
+//|                                               optimizer_panel.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::backtest_panel::{self, TestAccount};
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use sum3api::backtest;
+use sum3api::history;
+use sum3api::optimize::{
+    self, Objective, OptimizeResult, OptimizeSettings, ParamRange, Progress, Search, WalkForwardReport,
+    WalkForwardSettings, MAX_RUNS,
+};
+use sum3api::sim::SimSettings;
+use sum3api::strategy::{self, StrategyFactory};
+
+/// Runs listed in the results table; the heatmap uses all of them.
+const MAX_LISTED_RUNS: usize = 200;
+
+// ============================================================================
+// Optimizer Window
+// ============================================================================
+
+/// What a finished optimization produced, with the settings it ran with.
+struct Outcome {
+    factory: StrategyFactory,
+    objective: Objective,
+    results: Vec<OptimizeResult>,
+    walk_forward: Option<WalkForwardReport>,
+}
+
+struct Running {
+    progress: Arc<Progress>,
+    rx: mpsc::Receiver<Result<Outcome, String>>,
+}
+
+/// Searches a catalog strategy's parameters over a History/Live CSV,
+/// optionally walk-forward, on a background thread using all cores.
+pub struct OptimizerPanel {
+    pub open: bool,
+    path: Option<PathBuf>,
+    catalog: Vec<StrategyFactory>,
+    strategy: usize,
+    ranges: Vec<ParamRange>,
+    random: bool,
+    samples: usize,
+    seed: u64,
+    objective: Objective,
+    min_trades: usize,
+    walk_forward: bool,
+    wf: WalkForwardSettings,
+    account: TestAccount,
+    running: Option<Running>,
+    outcome: Option<Outcome>,
+    error: Option<String>,
+    // Parameters on the heatmap axes
+    heat_x: usize,
+    heat_y: usize,
+}
+
+impl OptimizerPanel {
+    pub fn new(balance: f64) -> Self {
+        let catalog = strategy::catalog();
+        let ranges = catalog.first().map(default_ranges).unwrap_or_default();
+        Self {
+            open: false,
+            path: None,
+            catalog,
+            strategy: 0,
+            ranges,
+            random: false,
+            samples: 200,
+            seed: 1,
+            objective: Objective::NetProfit,
+            min_trades: 10,
+            walk_forward: false,
+            wf: WalkForwardSettings::default(),
+            account: TestAccount::new(balance),
+            running: None,
+            outcome: None,
+            error: None,
+            heat_x: 0,
+            heat_y: 1,
+        }
+    }
+
+    /// Opens the window on a CSV file.
+    pub fn select_file(&mut self, path: &Path) {
+        self.path = Some(path.to_path_buf());
+        self.open = true;
+    }
+
+    /// `base` carries the symbol rules (lots, point, contract size, leverage)
+    /// the tests run with.
+    pub fn show(&mut self, ctx: &egui::Context, base: &SimSettings) {
+        if let Some(result) = self.running.as_ref().and_then(|r| r.rx.try_recv().ok()) {
+            self.running = None;
+            match result {
+                Ok(outcome) => {
+                    self.outcome = Some(outcome);
+                    self.error = None;
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+
+        let mut open = self.open;
+        egui::Window::new("🔬 Optimizer")
+            .open(&mut open)
+            .default_width(620.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.show_settings(ui, base);
+                    if let Some(error) = &self.error {
+                        ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("✗ {}", error));
+                    }
+                    if let Some(outcome) = &self.outcome {
+                        ui.separator();
+                        match &outcome.walk_forward {
+                            Some(report) => show_walk_forward(ui, outcome, report),
+                            None => {
+                                show_heatmap(ui, outcome, &mut self.heat_x, &mut self.heat_y);
+                                show_results(ui, outcome);
+                            }
+                        }
+                    }
+                });
+            });
+        self.open = open;
+    }
+
+    fn show_settings(&mut self, ui: &mut egui::Ui, base: &SimSettings) {
+        backtest_panel::show_file(ui, self.path.as_deref(), "none (use 🔬 on a file tab)");
+
+        egui::Grid::new("optimizer_settings").num_columns(2).spacing([10.0, 4.0]).show(ui, |ui| {
+            ui.label("Strategy:");
+            let before = self.strategy;
+            egui::ComboBox::from_id_source("optimizer_strategy")
+                .selected_text(self.catalog.get(self.strategy).map_or("-", |f| f.name))
+                .show_ui(ui, |ui| {
+                    for (i, factory) in self.catalog.iter().enumerate() {
+                        ui.selectable_value(&mut self.strategy, i, factory.name);
+                    }
+                });
+            if self.strategy != before {
+                self.ranges = default_ranges(&self.catalog[self.strategy]);
+            }
+            ui.end_row();
+
+            if let Some(factory) = self.catalog.get(self.strategy) {
+                for (spec, range) in factory.params.iter().zip(&mut self.ranges) {
+                    ui.label(format!("{}:", spec.name));
+                    ui.horizontal(|ui| {
+                        let limits = spec.min..=spec.max;
+                        ui.add(egui::DragValue::new(&mut range.min).speed(spec.step).clamp_range(limits.clone()));
+                        ui.label("to");
+                        ui.add(egui::DragValue::new(&mut range.max).speed(spec.step).clamp_range(limits));
+                        ui.label("step");
+                        ui.add(egui::DragValue::new(&mut range.step).speed(spec.step).clamp_range(0.0..=spec.max));
+                        ui.weak(format!("{} values", range.count()));
+                    });
+                    ui.end_row();
+                }
+            }
+
+            ui.label("Search:");
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.random, false, "Grid");
+                ui.selectable_value(&mut self.random, true, "Random");
+                if self.random {
+                    ui.label("samples");
+                    ui.add(egui::DragValue::new(&mut self.samples).clamp_range(1..=MAX_RUNS));
+                    ui.label("seed");
+                    ui.add(egui::DragValue::new(&mut self.seed));
+                }
+            });
+            ui.end_row();
+            ui.label("Objective:");
+            egui::ComboBox::from_id_source("optimizer_objective")
+                .selected_text(self.objective.label())
+                .show_ui(ui, |ui| {
+                    for objective in Objective::ALL {
+                        ui.selectable_value(&mut self.objective, objective, objective.label());
+                    }
+                });
+            ui.end_row();
+            ui.label("Min trades:").on_hover_text("Runs with fewer closed trades are not ranked");
+            ui.add(egui::DragValue::new(&mut self.min_trades).clamp_range(0..=100_000));
+            ui.end_row();
+
+            ui.label("Walk-forward:");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.walk_forward, "");
+                if self.walk_forward {
+                    ui.label("windows");
+                    ui.add(egui::DragValue::new(&mut self.wf.windows).clamp_range(1..=50));
+                    ui.label("in-sample");
+                    let mut percent = self.wf.in_sample * 100.0;
+                    ui.add(egui::DragValue::new(&mut percent).speed(1.0).clamp_range(10.0..=90.0).suffix("%"));
+                    self.wf.in_sample = percent / 100.0;
+                    ui.checkbox(&mut self.wf.anchored, "anchored")
+                        .on_hover_text("Every in-sample window starts at the first tick");
+                }
+            });
+            ui.end_row();
+
+            self.account.show_rows(ui);
+        });
+
+        let settings = self.path.as_ref().and_then(|path| self.settings(path, base));
+        let runs = settings.as_ref().map_or(0, |s| {
+            s.run_count().saturating_mul(if self.walk_forward { self.wf.windows } else { 1 })
+        });
+        ui.horizontal(|ui| {
+            let ready = settings.is_some() && self.running.is_none() && runs <= MAX_RUNS;
+            if ui.add_enabled(ready, egui::Button::new("▶ Run")).clicked()
+                && let (Some(path), Some(settings)) = (self.path.clone(), settings)
+            {
+                self.start(path, settings);
+            }
+            match &self.running {
+                Some(running) => {
+                    let (done, total) = (running.progress.done(), running.progress.total().max(1));
+                    ui.add(
+                        egui::ProgressBar::new(done as f32 / total as f32)
+                            .desired_width(200.0)
+                            .text(format!("{} / {}", done, total)),
+                    );
+                    if ui.button("⏹ Stop").clicked() {
+                        running.progress.cancel();
+                    }
+                }
+                None if runs > MAX_RUNS => {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 100, 100),
+                        format!("{} backtests, at most {}", runs, MAX_RUNS),
+                    );
+                }
+                None => {
+                    ui.weak(format!("up to {} backtests on {} threads", runs, worker_threads()));
+                }
+            }
+        });
+    }
+
+    fn settings(&self, path: &Path, base: &SimSettings) -> Option<OptimizeSettings> {
+        let factory = *self.catalog.get(self.strategy)?;
+        Some(OptimizeSettings {
+            factory,
+            ranges: self.ranges.clone(),
+            search: if self.random { Search::Random { samples: self.samples, seed: self.seed } } else { Search::Grid },
+            objective: self.objective,
+            min_trades: self.min_trades,
+            backtest: self.account.settings(path, base),
+        })
+    }
+
+    fn start(&mut self, path: PathBuf, settings: OptimizeSettings) {
+        let progress = Arc::new(Progress::default());
+        let wf = self.walk_forward.then_some(self.wf);
+        let (tx, rx) = mpsc::channel();
+        let shared = Arc::clone(&progress);
+        std::thread::spawn(move || {
+            let result = history::read_csv(&path).map_err(|e| e.to_string()).and_then(|data| {
+                let ticks = backtest::ticks_of(&data, &settings.backtest);
+                let outcome = |results, walk_forward| Outcome {
+                    factory: settings.factory,
+                    objective: settings.objective,
+                    results,
+                    walk_forward,
+                };
+                match wf {
+                    Some(wf) => optimize::walk_forward(&ticks, &settings, &wf, &shared)
+                        .map(|report| outcome(Vec::new(), Some(report))),
+                    None => optimize::optimize(&ticks, &settings, &shared).map(|results| outcome(results, None)),
+                }
+                .map_err(|e| e.to_string())
+            });
+            let _ = tx.send(result);
+        });
+        self.running = Some(Running { progress, rx });
+        self.error = None;
+    }
+}
+
+fn default_ranges(factory: &StrategyFactory) -> Vec<ParamRange> {
+    factory.params.iter().map(ParamRange::from_spec).collect()
+}
+
+fn worker_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+fn format_params(factory: &StrategyFactory, params: &[f64]) -> String {
+    factory
+        .params
+        .iter()
+        .zip(params)
+        .map(|(spec, value)| format!("{}={}", spec.name, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn format_score(score: Option<f64>) -> String {
+    score.map_or_else(|| "-".to_string(), |s| format!("{:.2}", s))
+}
+
+// ============================================================================
+// Results
+// ============================================================================
+
+fn show_results(ui: &mut egui::Ui, outcome: &Outcome) {
+    let factory = &outcome.factory;
+    ui.label(format!(
+        "{} runs, ranked by {} ({} shown)",
+        outcome.results.len(),
+        outcome.objective.label(),
+        outcome.results.len().min(MAX_LISTED_RUNS)
+    ));
+    egui::ScrollArea::both().id_source("optimizer_results").max_height(240.0).show(ui, |ui| {
+        egui::Grid::new("optimizer_results_grid").striped(true).show(ui, |ui| {
+            ui.strong("#");
+            for spec in factory.params {
+                ui.strong(spec.name);
+            }
+            for header in ["Score", "Net", "Trades", "Win %", "PF", "Max DD %", "Sharpe"] {
+                ui.strong(header);
+            }
+            ui.end_row();
+            for (rank, result) in outcome.results.iter().take(MAX_LISTED_RUNS).enumerate() {
+                let r = &result.report;
+                ui.label(format!("{}", rank + 1));
+                for value in &result.params {
+                    ui.label(format!("{}", value));
+                }
+                ui.strong(format_score(result.score));
+                let color =
+                    if r.net_profit >= 0.0 { egui::Color32::from_rgb(100, 200, 100) } else { egui::Color32::from_rgb(255, 100, 100) };
+                ui.colored_label(color, format!("{:+.2}", r.net_profit));
+                ui.label(format!("{}", r.trades));
+                ui.label(format!("{:.1}", r.win_rate * 100.0));
+                ui.label(r.profit_factor.map_or("∞".to_string(), |pf| format!("{:.2}", pf)));
+                ui.label(format!("{:.1}", r.max_drawdown_pct * 100.0));
+                ui.label(format!("{:.2}", r.sharpe));
+                ui.end_row();
+            }
+        });
+    });
+}
+
+/// Best score for each pair of values of two parameters, the others free.
+fn show_heatmap(ui: &mut egui::Ui, outcome: &Outcome, heat_x: &mut usize, heat_y: &mut usize) {
+    let params = outcome.factory.params;
+    if params.len() < 2 || outcome.results.is_empty() {
+        return;
+    }
+    ui.horizontal(|ui| {
+        ui.label(format!("Heatmap of best {}:", outcome.objective.label()));
+        for (id, axis, name) in [("heat_x", &mut *heat_x, "x"), ("heat_y", &mut *heat_y, "y")] {
+            egui::ComboBox::from_id_source(id)
+                .selected_text(format!("{} = {}", name, params.get(*axis).map_or("-", |p| p.name)))
+                .show_ui(ui, |ui| {
+                    for (i, spec) in params.iter().enumerate() {
+                        ui.selectable_value(axis, i, spec.name);
+                    }
+                });
+        }
+    });
+    let (x, y) = (*heat_x, *heat_y);
+    if x >= params.len() || y >= params.len() || x == y {
+        ui.weak("Pick two different parameters");
+        return;
+    }
+
+    let axis_values = |index: usize| {
+        let mut values: Vec<f64> = outcome.results.iter().map(|r| r.params[index]).collect();
+        values.sort_by(f64::total_cmp);
+        values.dedup();
+        values
+    };
+    let (xs, ys) = (axis_values(x), axis_values(y));
+    let mut cells = vec![None::<f64>; xs.len() * ys.len()];
+    for result in &outcome.results {
+        let (Some(score), Ok(col), Ok(row)) = (
+            result.score,
+            xs.binary_search_by(|v| v.total_cmp(&result.params[x])),
+            ys.binary_search_by(|v| v.total_cmp(&result.params[y])),
+        ) else {
+            continue;
+        };
+        let cell = &mut cells[row * xs.len() + col];
+        *cell = Some(cell.map_or(score, |best: f64| best.max(score)));
+    }
+    let (low, high) = cells.iter().flatten().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), s| (lo.min(*s), hi.max(*s)));
+
+    let label_width = 50.0;
+    let size = egui::vec2(ui.available_width().max(200.0), 220.0);
+    let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+    let rect = response.rect;
+    let grid = egui::Rect::from_min_max(rect.min + egui::vec2(label_width, 0.0), rect.max - egui::vec2(0.0, 18.0));
+    let cell_size = egui::vec2(grid.width() / xs.len() as f32, grid.height() / ys.len() as f32);
+    let font = egui::FontId::proportional(10.0);
+    let text_color = ui.visuals().text_color();
+    let cell_rect = |col: usize, row: usize| {
+        // Lowest y value at the bottom
+        let min = grid.min + egui::vec2(col as f32 * cell_size.x, (ys.len() - 1 - row) as f32 * cell_size.y);
+        egui::Rect::from_min_size(min, cell_size)
+    };
+
+    let mut hovered = None;
+    for row in 0..ys.len() {
+        for col in 0..xs.len() {
+            let cell = cells[row * xs.len() + col];
+            let color = match cell {
+                Some(score) => heat_color(if high > low { (score - low) / (high - low) } else { 1.0 }),
+                None => egui::Color32::from_gray(60),
+            };
+            let r = cell_rect(col, row);
+            painter.rect_filled(r.shrink(0.5), 0.0, color);
+            if response.hover_pos().is_some_and(|p| r.contains(p)) {
+                hovered = Some((col, row, cell));
+            }
+        }
+    }
+    // Axis labels, thinned so they do not overlap
+    let every = |count: usize, space: f32| (count as f32 * 40.0 / space).ceil().max(1.0) as usize;
+    for col in (0..xs.len()).step_by(every(xs.len(), grid.width())) {
+        let r = cell_rect(col, 0);
+        painter.text(egui::pos2(r.center().x, grid.max.y + 2.0), egui::Align2::CENTER_TOP, xs[col], font.clone(), text_color);
+    }
+    for row in (0..ys.len()).step_by(every(ys.len(), grid.height() * 2.0)) {
+        let r = cell_rect(0, row);
+        painter.text(egui::pos2(grid.min.x - 4.0, r.center().y), egui::Align2::RIGHT_CENTER, ys[row], font.clone(), text_color);
+    }
+    painter.text(rect.left_top(), egui::Align2::LEFT_TOP, params[y].name, font.clone(), text_color);
+    painter.text(rect.left_bottom(), egui::Align2::LEFT_BOTTOM, params[x].name, font, text_color);
+
+    if let Some((col, row, score)) = hovered {
+        response.on_hover_text(format!(
+            "{} = {}\n{} = {}\n{}: {}",
+            params[x].name,
+            xs[col],
+            params[y].name,
+            ys[row],
+            outcome.objective.label(),
+            format_score(score)
+        ));
+    }
+}
+
+/// Red (0) through yellow to green (1).
+fn heat_color(t: f64) -> egui::Color32 {
+    let t = t.clamp(0.0, 1.0) as f32;
+    let (r, g) = if t < 0.5 { (1.0, t * 2.0) } else { (2.0 - t * 2.0, 1.0) };
+    egui::Color32::from_rgb((r * 200.0) as u8 + 30, (g * 170.0) as u8 + 30, 50)
+}
+
+fn show_walk_forward(ui: &mut egui::Ui, outcome: &Outcome, report: &WalkForwardReport) {
+    let color = |value: f64| {
+        if value >= 0.0 { egui::Color32::from_rgb(100, 200, 100) } else { egui::Color32::from_rgb(255, 100, 100) }
+    };
+    ui.horizontal(|ui| {
+        ui.label("Out-of-sample net:");
+        ui.colored_label(color(report.oos_net_profit), format!("{:+.2}", report.oos_net_profit));
+        ui.label(format!("over {} trades", report.oos_trades));
+        ui.separator();
+        ui.label(format!("Walk-forward efficiency: {:.2}", report.efficiency))
+            .on_hover_text("Out-of-sample profit rate over in-sample profit rate");
+    });
+    let period = |(from, to): (i64, i64)| {
+        format!("{} → {}", history::format_time(from, true), history::format_time(to, true))
+    };
+    egui::ScrollArea::both().id_source("optimizer_folds").max_height(260.0).show(ui, |ui| {
+        egui::Grid::new("optimizer_folds_grid").striped(true).show(ui, |ui| {
+            for header in ["#", "In-sample", "Out-of-sample", "Best parameters", "IS score", "OOS score", "OOS net", "OOS trades"] {
+                ui.strong(header);
+            }
+            ui.end_row();
+            for (i, fold) in report.folds.iter().enumerate() {
+                ui.label(format!("{}", i + 1));
+                ui.label(period(fold.in_sample));
+                ui.label(period(fold.out_of_sample));
+                match &fold.best {
+                    Some(best) => {
+                        ui.label(format_params(&outcome.factory, &best.params));
+                        ui.label(format_score(best.score));
+                    }
+                    None => {
+                        ui.weak("no run reached min trades");
+                        ui.label("-");
+                    }
+                }
+                ui.label(format_score(fold.oos_score));
+                match &fold.oos {
+                    Some(oos) => {
+                        ui.colored_label(color(oos.net_profit), format!("{:+.2}", oos.net_profit));
+                        ui.label(format!("{}", oos.trades));
+                    }
+                    None => {
+                        ui.label("-");
+                        ui.label("-");
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
@@ -0,0 +1,321 @@
+//+------------------------------------------------------------------+
+//|                                                        replay.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::tick_store::Tick;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use sum3api::TickData;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+
+/// Longest real-time pause between two replayed ticks, so weekends and
+/// session breaks don't stall playback.
+const MAX_REPLAY_GAP: Duration = Duration::from_secs(2);
+
+/// Replay speed multipliers offered in the UI.
+pub const SPEEDS: [f64; 8] = [1.0, 2.0, 5.0, 10.0, 50.0, 100.0, 500.0, 1000.0];
+
+// ============================================================================
+// Tick Pipeline
+// ============================================================================
+
+/// The chart's tick channel. The ZMQ forwarder and the replay both send
+/// into it; the forwarder drops live ticks while `live_paused` is set.
+pub struct TickPipeline {
+    pub sender: mpsc::Sender<TickData>,
+    pub receiver: mpsc::Receiver<TickData>,
+    pub live_paused: Arc<AtomicBool>,
+}
+
+impl TickPipeline {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(capacity);
+        Self { sender, receiver, live_paused: Arc::new(AtomicBool::new(false)) }
+    }
+}
+
+// ============================================================================
+// Replay Session
+// ============================================================================
+
+enum ReplayCommand {
+    Play,
+    Pause,
+    Speed(f64),
+    Seek(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct ReplayStatus {
+    /// Index of the next tick to send
+    pub position: usize,
+    pub total: usize,
+    /// Time of the last tick sent
+    pub time: i64,
+    pub playing: bool,
+    pub speed: f64,
+}
+
+/// Plays recorded ticks into the tick pipeline in a background task.
+/// Dropping it stops playback and hands the chart back to the live feed.
+pub struct Replay {
+    pub title: String,
+    commands: mpsc::UnboundedSender<ReplayCommand>,
+    status: watch::Receiver<ReplayStatus>,
+    task: JoinHandle<()>,
+    live_paused: Arc<AtomicBool>,
+}
+
+impl Replay {
+    /// Starts paused at the first tick. `context` ticks before a seek target
+    /// are sent at once so the chart is not empty after seeking.
+    pub fn start(
+        title: String,
+        symbol: String,
+        ticks: Vec<Tick>,
+        pipeline: &TickPipeline,
+        context: usize,
+    ) -> Self {
+        pipeline.live_paused.store(true, Ordering::Relaxed);
+
+        let (commands, command_rx) = mpsc::unbounded_channel();
+        let (status_tx, status) = watch::channel(ReplayStatus {
+            position: 0,
+            total: ticks.len(),
+            time: ticks.first().map_or(0, |t| t.time),
+            playing: false,
+            speed: 1.0,
+        });
+        let task = tokio::spawn(run_replay(symbol, ticks, context, pipeline.sender.clone(), command_rx, status_tx));
+
+        Self { title, commands, status, task, live_paused: pipeline.live_paused.clone() }
+    }
+
+    pub fn status(&self) -> ReplayStatus {
+        self.status.borrow().clone()
+    }
+
+    pub fn play(&self) {
+        let _ = self.commands.send(ReplayCommand::Play);
+    }
+
+    pub fn pause(&self) {
+        let _ = self.commands.send(ReplayCommand::Pause);
+    }
+
+    pub fn set_speed(&self, speed: f64) {
+        let _ = self.commands.send(ReplayCommand::Speed(speed));
+    }
+
+    /// Jumps to a tick index. The caller clears its chart data first.
+    pub fn seek(&self, position: usize) {
+        let _ = self.commands.send(ReplayCommand::Seek(position));
+    }
+}
+
+impl Drop for Replay {
+    fn drop(&mut self) {
+        self.task.abort();
+        self.live_paused.store(false, Ordering::Relaxed);
+    }
+}
+
+async fn run_replay(
+    symbol: String,
+    ticks: Vec<Tick>,
+    context: usize,
+    tick_tx: mpsc::Sender<TickData>,
+    mut commands: mpsc::UnboundedReceiver<ReplayCommand>,
+    status_tx: watch::Sender<ReplayStatus>,
+) {
+    let make_tick = |t: &Tick| TickData {
+        symbol: symbol.clone(),
+        bid: t.bid,
+        ask: t.ask,
+        time: t.time,
+        volume: t.volume,
+        ..TickData::default()
+    };
+    let mut status = status_tx.borrow().clone();
+
+    loop {
+        let next = ticks.get(status.position).filter(|_| status.playing);
+        let delay = match (next, status.position.checked_sub(1).and_then(|i| ticks.get(i))) {
+            (Some(next), Some(prev)) => {
+                Duration::from_secs_f64((next.time - prev.time).max(0) as f64 / status.speed).min(MAX_REPLAY_GAP)
+            }
+            _ => Duration::ZERO,
+        };
+
+        // Wait for the next tick time, or for a command while paused/finished
+        let command = match next {
+            // Too short to sleep on; just check for a pending command
+            Some(_) if delay < Duration::from_millis(1) => match commands.try_recv() {
+                Ok(command) => Some(Some(command)),
+                Err(mpsc::error::TryRecvError::Empty) => None,
+                Err(mpsc::error::TryRecvError::Disconnected) => Some(None),
+            },
+            Some(_) => tokio::select! {
+                command = commands.recv() => Some(command),
+                _ = tokio::time::sleep(delay) => None,
+            },
+            None => Some(commands.recv().await),
+        };
+
+        match command {
+            Some(None) => return, // Replay handle dropped
+            Some(Some(ReplayCommand::Play)) => status.playing = status.position < ticks.len(),
+            Some(Some(ReplayCommand::Pause)) => status.playing = false,
+            Some(Some(ReplayCommand::Speed(speed))) => status.speed = speed.clamp(1.0, 1000.0),
+            Some(Some(ReplayCommand::Seek(position))) => {
+                status.position = position.min(ticks.len());
+                for tick in &ticks[status.position.saturating_sub(context)..status.position] {
+                    if tick_tx.send(make_tick(tick)).await.is_err() {
+                        return;
+                    }
+                }
+                if let Some(last) = status.position.checked_sub(1).and_then(|i| ticks.get(i)) {
+                    status.time = last.time;
+                }
+                status.playing &= status.position < ticks.len();
+            }
+            None => {
+                let tick = &ticks[status.position];
+                if tick_tx.send(make_tick(tick)).await.is_err() {
+                    return;
+                }
+                status.position += 1;
+                status.time = tick.time;
+                status.playing &= status.position < ticks.len();
+            }
+        }
+        status_tx.send_replace(status.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Instant;
+
+    /// Ticks one second apart, starting at t = 100.
+    fn ticks(count: i64) -> Vec<Tick> {
+        (0..count).map(|i| Tick { time: 100 + i, bid: i as f64, ask: i as f64 + 0.5, volume: 1 }).collect()
+    }
+
+    /// Lets the replay task run until it waits on the (paused) clock.
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    /// Timers fire on the next millisecond tick, so allow a little slack.
+    fn assert_delay(start: Instant, expected: Duration) {
+        let elapsed = start.elapsed();
+        assert!(elapsed >= expected && elapsed <= expected + Duration::from_millis(5), "{:?}", elapsed);
+    }
+
+    fn drain(pipeline: &mut TickPipeline) -> Vec<i64> {
+        std::iter::from_fn(|| pipeline.receiver.try_recv().ok()).map(|t| t.time).collect()
+    }
+
+    #[tokio::test]
+    async fn seek_sends_context_and_clamps_to_end() {
+        tokio::time::pause();
+        let mut pipeline = TickPipeline::new(100);
+        let replay = Replay::start("test".into(), "XAUUSDc".into(), ticks(10), &pipeline, 3);
+        assert!(pipeline.live_paused.load(Ordering::Relaxed));
+
+        replay.seek(5);
+        settle().await;
+        assert_eq!(drain(&mut pipeline), vec![102, 103, 104]);
+        let status = replay.status();
+        assert_eq!((status.position, status.time, status.playing), (5, 104, false));
+
+        // Past the end: lands on the last tick with its context
+        replay.seek(50);
+        settle().await;
+        assert_eq!(drain(&mut pipeline), vec![107, 108, 109]);
+        assert_eq!(replay.status().position, 10);
+
+        // Near the start there is less context to send
+        replay.seek(1);
+        settle().await;
+        assert_eq!(drain(&mut pipeline), vec![100]);
+
+        drop(replay);
+        assert!(!pipeline.live_paused.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn speed_scales_delay_and_is_clamped() {
+        tokio::time::pause();
+        let mut pipeline = TickPipeline::new(100);
+        let replay = Replay::start("test".into(), "XAUUSDc".into(), ticks(10), &pipeline, 0);
+
+        replay.set_speed(0.1);
+        settle().await;
+        assert_eq!(replay.status().speed, 1.0);
+        replay.set_speed(5000.0);
+        settle().await;
+        assert_eq!(replay.status().speed, 1000.0);
+
+        replay.set_speed(2.0);
+        replay.play();
+        assert_eq!(pipeline.receiver.recv().await.map(|t| t.time), Some(100));
+        let start = Instant::now();
+        assert_eq!(pipeline.receiver.recv().await.map(|t| t.time), Some(101));
+        // One second of market time at 2x
+        assert_delay(start, Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn long_gaps_are_capped() {
+        tokio::time::pause();
+        let mut pipeline = TickPipeline::new(100);
+        let weekend = vec![
+            Tick { time: 100, bid: 1.0, ask: 1.5, volume: 1 },
+            Tick { time: 100 + 2 * 86400, bid: 2.0, ask: 2.5, volume: 1 },
+        ];
+        let replay = Replay::start("test".into(), "XAUUSDc".into(), weekend, &pipeline, 0);
+
+        replay.play();
+        pipeline.receiver.recv().await;
+        let start = Instant::now();
+        pipeline.receiver.recv().await;
+        assert_delay(start, MAX_REPLAY_GAP);
+        settle().await;
+        assert!(!replay.status().playing);
+    }
+
+    #[tokio::test]
+    async fn pause_stops_output() {
+        tokio::time::pause();
+        let mut pipeline = TickPipeline::new(100);
+        let replay = Replay::start("test".into(), "XAUUSDc".into(), ticks(10), &pipeline, 0);
+
+        // Nothing is sent before play
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        assert!(drain(&mut pipeline).is_empty());
+
+        replay.play();
+        assert_eq!(pipeline.receiver.recv().await.map(|t| t.time), Some(100));
+        assert_eq!(pipeline.receiver.recv().await.map(|t| t.time), Some(101));
+        replay.pause();
+        settle().await;
+        let status = replay.status();
+        assert!(!status.playing);
+        assert_eq!(status.position, 2);
+
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        assert!(drain(&mut pipeline).is_empty());
+        assert_eq!(replay.status().position, 2);
+
+        // Resumes where it stopped
+        replay.play();
+        assert_eq!(pipeline.receiver.recv().await.map(|t| t.time), Some(102));
+    }
+}
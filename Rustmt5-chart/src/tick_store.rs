@@ -0,0 +1,129 @@
+//+------------------------------------------------------------------+
+//|                                                    tick_store.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use std::collections::VecDeque;
+use sum3api::TickData;
+
+// ============================================================================
+// Compact Tick Storage
+// ============================================================================
+
+/// Price part of a `TickData`; 32 bytes, no heap data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tick {
+    pub time: i64,
+    pub bid: f64,
+    pub ask: f64,
+    pub volume: u64,
+}
+
+impl From<&TickData> for Tick {
+    fn from(tick: &TickData) -> Self {
+        Self { time: tick.time, bid: tick.bid, ask: tick.ask, volume: tick.volume }
+    }
+}
+
+/// Fixed-capacity ring buffer of ticks. Once full, each push drops the
+/// oldest tick in O(1).
+pub struct TickStore {
+    ticks: VecDeque<Tick>,
+    capacity: usize,
+}
+
+impl TickStore {
+    pub fn new(capacity: usize) -> Self {
+        // Grow on demand; a full 1M-tick buffer is only 32 MB
+        Self { ticks: VecDeque::with_capacity(capacity.min(4096)), capacity: capacity.max(1) }
+    }
+
+    pub fn push(&mut self, tick: Tick) {
+        if self.ticks.len() == self.capacity {
+            self.ticks.pop_front();
+        }
+        self.ticks.push_back(tick);
+    }
+
+    pub fn clear(&mut self) {
+        self.ticks.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn last(&self) -> Option<&Tick> {
+        self.ticks.back()
+    }
+
+    /// All stored ticks, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Tick> {
+        self.ticks.iter()
+    }
+
+    /// The newest `count` ticks, oldest first.
+    pub fn latest(&self, count: usize) -> impl Iterator<Item = &Tick> + Clone {
+        self.ticks.range(self.ticks.len().saturating_sub(count)..)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(time: i64) -> Tick {
+        Tick { time, bid: 2000.0, ask: 2000.5, volume: 1 }
+    }
+
+    fn times<'a>(ticks: impl Iterator<Item = &'a Tick>) -> Vec<i64> {
+        ticks.map(|t| t.time).collect()
+    }
+
+    #[test]
+    fn drops_oldest_once_full() {
+        let mut store = TickStore::new(3);
+        for time in 1..=3 {
+            store.push(tick(time));
+        }
+        assert_eq!(times(store.iter()), vec![1, 2, 3]);
+
+        store.push(tick(4));
+        store.push(tick(5));
+        assert_eq!(store.len(), 3);
+        assert_eq!(times(store.iter()), vec![3, 4, 5]);
+        assert_eq!(store.last().map(|t| t.time), Some(5));
+    }
+
+    #[test]
+    fn latest_returns_newest_oldest_first() {
+        let mut store = TickStore::new(10);
+        for time in 1..=5 {
+            store.push(tick(time));
+        }
+        assert_eq!(times(store.latest(2)), vec![4, 5]);
+        assert_eq!(times(store.latest(0)), Vec::<i64>::new());
+        // Asking for more than is stored returns everything
+        assert_eq!(times(store.latest(50)), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn zero_capacity_keeps_one_tick() {
+        let mut store = TickStore::new(0);
+        assert_eq!(store.capacity(), 1);
+        assert!(store.last().is_none());
+
+        store.push(tick(1));
+        store.push(tick(2));
+        assert_eq!(store.len(), 1);
+        assert_eq!(times(store.iter()), vec![2]);
+
+        store.clear();
+        assert_eq!(store.len(), 0);
+    }
+}
@@ -0,0 +1,288 @@
+//+------------------------------------------------------------------+
+//|                                                     time_axis.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use chrono::{DateTime, Local, Offset, TimeZone};
+use egui_plot::{GridInput, GridMark};
+use serde::Deserialize;
+
+// ============================================================================
+// Display Time Zone
+// ============================================================================
+
+/// Zone the chart labels are shown in. Tick times are broker server time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayZone {
+    Server,
+    Utc,
+    Local,
+}
+
+impl DisplayZone {
+    pub const ALL: [DisplayZone; 3] = [DisplayZone::Server, DisplayZone::Utc, DisplayZone::Local];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisplayZone::Server => "Server",
+            DisplayZone::Utc => "UTC",
+            DisplayZone::Local => "Local",
+        }
+    }
+
+    /// Seconds to add to a server timestamp to get wall-clock time in this zone.
+    fn shift(&self, server_offset: i64) -> i64 {
+        match self {
+            DisplayZone::Server => 0,
+            DisplayZone::Utc => -server_offset,
+            DisplayZone::Local => {
+                let local = Local.offset_from_utc_datetime(&chrono::Utc::now().naive_utc());
+                local.fix().local_minus_utc() as i64 - server_offset
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Time Axis
+// ============================================================================
+
+/// Grid steps in seconds, from one second to one week.
+const STEPS: [i64; 20] = [
+    1, 2, 5, 10, 15, 30, 60, 120, 300, 600, 900, 1800, 3600, 7200, 14400, 21600, 43200, 86400,
+    172800, 604800,
+];
+
+/// Run of data without a collapsed gap.
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    start: i64,
+    end: i64,
+    x: f64,
+}
+
+/// Maps server timestamps to plot X (seconds) and back. Gaps longer than
+/// `max_gap` (weekends, the daily break) are squeezed to `gap_width`
+/// seconds so the data stays side by side.
+#[derive(Clone, Debug)]
+pub struct TimeAxis {
+    segments: Vec<Segment>,
+    zone: DisplayZone,
+    shift: i64,
+}
+
+impl TimeAxis {
+    /// `times` must be sorted. `max_gap: None` keeps the axis linear.
+    pub fn new(
+        times: &[i64],
+        max_gap: Option<i64>,
+        gap_width: i64,
+        zone: DisplayZone,
+        server_offset: i64,
+    ) -> Self {
+        let mut segments: Vec<Segment> = Vec::new();
+        for &t in times {
+            match segments.last_mut() {
+                Some(seg) if max_gap.is_none_or(|gap| t - seg.end <= gap) => seg.end = seg.end.max(t),
+                Some(seg) => {
+                    let x = seg.x + (seg.end - seg.start + gap_width) as f64;
+                    segments.push(Segment { start: t, end: t, x });
+                }
+                None => segments.push(Segment { start: t, end: t, x: t as f64 }),
+            }
+        }
+        Self { segments, zone, shift: zone.shift(server_offset) }
+    }
+
+    pub fn to_x(&self, time: i64) -> f64 {
+        let idx = self.segments.partition_point(|s| s.start <= time);
+        match (idx.checked_sub(1).map(|i| self.segments[i]), self.segments.get(idx)) {
+            (Some(seg), next) => {
+                let x = seg.x + (time - seg.start) as f64;
+                next.map_or(x, |n| x.min(n.x))
+            }
+            (None, Some(first)) => first.x - (first.start - time) as f64,
+            (None, None) => time as f64,
+        }
+    }
+
+    pub fn to_time(&self, x: f64) -> i64 {
+        let idx = self.segments.partition_point(|s| s.x <= x);
+        let seg = self.segments.get(idx.saturating_sub(1));
+        match seg {
+            Some(seg) => seg.start + (x - seg.x).round() as i64,
+            None => x.round() as i64,
+        }
+    }
+
+    /// X positions where a gap was collapsed.
+    pub fn gaps(&self) -> impl Iterator<Item = f64> + '_ {
+        self.segments.windows(2).map(|w| (w[0].x + (w[0].end - w[0].start) as f64 + w[1].x) / 2.0)
+    }
+
+    /// Grid marks at round times of the display zone, per segment, so labels
+    /// stay on whole minutes/hours after a collapsed gap.
+    pub fn grid_marks(&self, input: GridInput) -> Vec<GridMark> {
+        let (lo, hi) = input.bounds;
+        let first = STEPS.iter().position(|&s| s as f64 >= input.base_step_size).unwrap_or(STEPS.len() - 1);
+
+        // Visible time range of each segment; the outer ones extend to the plot edges
+        let (t_lo, t_hi) = (self.to_time(lo), self.to_time(hi));
+        let last = self.segments.len().saturating_sub(1);
+        let ranges: Vec<(i64, i64)> = if self.segments.is_empty() {
+            vec![(t_lo, t_hi)]
+        } else {
+            self.segments
+                .iter()
+                .enumerate()
+                .map(|(i, seg)| {
+                    let start = if i == 0 { t_lo } else { seg.start.max(t_lo) };
+                    let end = if i == last { t_hi } else { seg.end.min(t_hi) };
+                    (start, end)
+                })
+                .filter(|(start, end)| start <= end)
+                .collect()
+        };
+
+        let mut marks: Vec<GridMark> = Vec::new();
+        for &step in STEPS[first..].iter().take(3) {
+            for &(start, end) in &ranges {
+                if (end - start) / step > 2000 {
+                    continue;
+                }
+                // Align in display time so days start at the zone's midnight
+                let mut t = (start + self.shift).div_euclid(step) * step - self.shift;
+                while t <= end {
+                    if t >= start {
+                        let value = self.to_x(t);
+                        match marks.iter_mut().find(|m| m.value == value) {
+                            Some(mark) => mark.step_size = step as f64,
+                            None => marks.push(GridMark { value, step_size: step as f64 }),
+                        }
+                    }
+                    t += step;
+                }
+            }
+        }
+        marks
+    }
+
+    /// Axis label; the date is shown for daily steps and at midnight.
+    pub fn format_mark(&self, mark: GridMark) -> String {
+        let t = self.to_time(mark.value) + self.shift;
+        let format = if mark.step_size >= 86400.0 {
+            "%Y.%m.%d"
+        } else if t.rem_euclid(86400) == 0 {
+            "%a %d %b"
+        } else if mark.step_size >= 60.0 {
+            "%H:%M"
+        } else {
+            "%H:%M:%S"
+        };
+        format_epoch(t, format)
+    }
+
+    /// Full date and time of a server timestamp in the display zone.
+    pub fn format_time(&self, time: i64) -> String {
+        format!("{} {}", format_epoch(time + self.shift, "%Y.%m.%d %H:%M:%S"), self.zone.as_str())
+    }
+}
+
+fn format_epoch(epoch: i64, format: &str) -> String {
+    DateTime::from_timestamp(epoch, 0)
+        .map(|dt| dt.naive_utc().format(format).to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-01-23 00:00:00 server time, a Friday
+    const FRI: i64 = 1769126400;
+    const MON: i64 = FRI + 3 * 86400;
+
+    /// Friday 08:00-20:00 and Monday 08:00-20:00, one tick a minute.
+    fn two_sessions() -> Vec<i64> {
+        let session = |day: i64| (8 * 60..=20 * 60).map(move |m| day + m * 60);
+        session(FRI).chain(session(MON)).collect()
+    }
+
+    fn squeezed(zone: DisplayZone, server_offset: i64) -> TimeAxis {
+        TimeAxis::new(&two_sessions(), Some(3600), 600, zone, server_offset)
+    }
+
+    #[test]
+    fn round_trips_inside_segments() {
+        let axis = squeezed(DisplayZone::Server, 0);
+        for t in [FRI + 8 * 3600, FRI + 12 * 3600 + 17, FRI + 20 * 3600, MON + 8 * 3600, MON + 15 * 3600 + 59] {
+            assert_eq!(axis.to_time(axis.to_x(t)), t);
+        }
+
+        let linear = TimeAxis::new(&two_sessions(), None, 600, DisplayZone::Server, 0);
+        assert_eq!(linear.to_x(MON + 8 * 3600), (MON + 8 * 3600) as f64);
+        assert_eq!(linear.gaps().count(), 0);
+    }
+
+    #[test]
+    fn gap_is_squeezed_to_gap_width() {
+        let axis = squeezed(DisplayZone::Server, 0);
+        let friday_close = axis.to_x(FRI + 20 * 3600);
+        let monday_open = axis.to_x(MON + 8 * 3600);
+        assert_eq!(monday_open - friday_close, 600.0);
+
+        let gaps: Vec<f64> = axis.gaps().collect();
+        assert_eq!(gaps, vec![friday_close + 300.0]);
+    }
+
+    #[test]
+    fn time_inside_gap_clamps_to_next_segment() {
+        let axis = squeezed(DisplayZone::Server, 0);
+        let monday_open = axis.to_x(MON + 8 * 3600);
+        assert_eq!(axis.to_x(FRI + 86400), monday_open);
+        assert_eq!(axis.to_x(MON + 3600), monday_open);
+        // Shortly after the close still runs on linearly into the gap
+        assert_eq!(axis.to_x(FRI + 20 * 3600 + 60), axis.to_x(FRI + 20 * 3600) + 60.0);
+    }
+
+    #[test]
+    fn grid_marks_align_to_zone_midnight() {
+        // Server at UTC+2
+        let server_offset = 7200;
+        for zone in DisplayZone::ALL {
+            let axis = TimeAxis::new(&[FRI, FRI + 10 * 86400], None, 600, zone, server_offset);
+            let input = GridInput { bounds: (FRI as f64, (FRI + 10 * 86400) as f64), base_step_size: 43200.0 };
+            let daily: Vec<i64> = axis
+                .grid_marks(input)
+                .into_iter()
+                // Marks shared with a coarser step carry that step's size
+                .filter(|m| m.step_size >= 86400.0)
+                .map(|m| axis.to_time(m.value))
+                .collect();
+            assert!(daily.len() >= 9, "{:?}: {:?}", zone, daily);
+            for t in daily {
+                assert_eq!((t + axis.shift).rem_euclid(86400), 0, "{:?} mark at {}", zone, t);
+            }
+        }
+    }
+
+    #[test]
+    fn grid_marks_stay_on_whole_hours_after_gap() {
+        for zone in DisplayZone::ALL {
+            let axis = squeezed(zone, 7200);
+            let (lo, hi) = (axis.to_x(FRI + 8 * 3600), axis.to_x(MON + 20 * 3600));
+            let marks = axis.grid_marks(GridInput { bounds: (lo, hi), base_step_size: 3000.0 });
+            let hourly: Vec<i64> = marks
+                .iter()
+                .filter(|m| m.step_size >= 3600.0)
+                .map(|m| axis.to_time(m.value))
+                .collect();
+            assert!(hourly.iter().any(|&t| t >= MON), "{:?}", zone);
+            for t in hourly {
+                assert_eq!((t + axis.shift).rem_euclid(3600), 0, "{:?} mark at {}", zone, t);
+            }
+        }
+    }
+}
@@ -0,0 +1,510 @@
+//+------------------------------------------------------------------+
+//|                                                     analytics.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+//! Performance metrics from closed trades and equity snapshots, for
+//! backtests and live or paper trading alike, with JSON/HTML export.
+
+use crate::sim::ClosedTrade;
+use crate::Side;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 86_400.0;
+
+/// Upper bounds of the R-multiple histogram buckets; the last bucket is
+/// everything from the final bound up.
+const R_BOUNDS: [f64; 6] = [-2.0, -1.0, 0.0, 1.0, 2.0, 3.0];
+
+// ============================================================================
+// Inputs
+// ============================================================================
+
+/// A closed trade as the metrics see it.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub ticket: u64,
+    pub side: Side,
+    pub volume: f64,
+    pub open_time: i64,
+    pub close_time: i64,
+    pub open_price: f64,
+    pub close_price: f64,
+    /// After commission
+    pub net_profit: f64,
+    /// Money that hitting the initial stop-loss would have lost; `None`
+    /// for trades opened without one
+    pub risk: Option<f64>,
+}
+
+impl TradeRecord {
+    pub fn from_closed(trade: &ClosedTrade, contract_size: f64) -> Self {
+        Self {
+            ticket: trade.ticket,
+            side: trade.side,
+            volume: trade.volume,
+            open_time: trade.open_time,
+            close_time: trade.close_time,
+            open_price: trade.open_price,
+            close_price: trade.close_price,
+            net_profit: trade.net_profit(),
+            risk: (trade.initial_sl > 0.0)
+                .then(|| (trade.open_price - trade.initial_sl).abs() * trade.volume * contract_size)
+                .filter(|risk| *risk > 0.0),
+        }
+    }
+
+    /// Profit in units of the initial risk.
+    pub fn r_multiple(&self) -> Option<f64> {
+        self.risk.map(|risk| self.net_profit / risk)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub time: i64,
+    pub balance: f64,
+    pub equity: f64,
+}
+
+// ============================================================================
+// Report
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RBucket {
+    pub label: String,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceReport {
+    pub initial_balance: f64,
+    pub final_equity: f64,
+    /// First and last time seen in the trades or snapshots
+    pub start: i64,
+    pub end: i64,
+
+    pub net_profit: f64,
+    pub gross_profit: f64,
+    pub gross_loss: f64,
+    pub trades: usize,
+    pub wins: usize,
+    pub losses: usize,
+    /// Share of winning trades, 0..1
+    pub win_rate: f64,
+    pub avg_win: f64,
+    pub avg_loss: f64,
+    pub largest_win: f64,
+    pub largest_loss: f64,
+    /// Gross profit / gross loss; `None` (infinite) with profits but no losses
+    pub profit_factor: Option<f64>,
+    /// Average net profit per trade
+    pub expectancy: f64,
+
+    /// Largest fall of equity from a previous high, in money and as a
+    /// share of that high, and the longest time spent below a high (s)
+    pub max_drawdown: f64,
+    pub max_drawdown_pct: f64,
+    pub max_drawdown_duration: i64,
+
+    /// Annualized from the returns between equity snapshots, with a zero
+    /// risk-free rate; 0 with fewer than two returns
+    pub sharpe: f64,
+    pub sortino: f64,
+
+    /// Trades with an initial stop-loss, their average R and histogram
+    pub r_trades: usize,
+    pub avg_r: f64,
+    pub r_distribution: Vec<RBucket>,
+}
+
+/// Computes every metric. `equity` should be in time order and include
+/// the final state; without snapshots the drawdown is measured on the
+/// closed-trade balance curve.
+pub fn analyze(initial_balance: f64, trades: &[TradeRecord], equity: &[EquityPoint]) -> PerformanceReport {
+    let net: Vec<f64> = trades.iter().map(|t| t.net_profit).collect();
+    let wins: Vec<f64> = net.iter().copied().filter(|p| *p > 0.0).collect();
+    let losses: Vec<f64> = net.iter().copied().filter(|p| *p < 0.0).collect();
+    // Adding 0.0 turns the -0.0 of an empty f64 sum into 0.0
+    let gross_profit: f64 = wins.iter().sum::<f64>() + 0.0;
+    let gross_loss: f64 = -losses.iter().sum::<f64>() + 0.0;
+    let net_profit = net.iter().sum::<f64>() + 0.0;
+
+    // Without snapshots, fall back to the balance after each closed trade
+    let curve: Vec<EquityPoint> = if equity.is_empty() {
+        let mut sorted: Vec<&TradeRecord> = trades.iter().collect();
+        sorted.sort_by_key(|t| t.close_time);
+        let mut balance = initial_balance;
+        sorted
+            .into_iter()
+            .map(|t| {
+                balance += t.net_profit;
+                EquityPoint { time: t.close_time, balance, equity: balance }
+            })
+            .collect()
+    } else {
+        equity.to_vec()
+    };
+    let (max_drawdown, max_drawdown_pct, max_drawdown_duration) = drawdown(initial_balance, &curve);
+    let (sharpe, sortino) = sharpe_sortino(&curve);
+
+    let r: Vec<f64> = trades.iter().filter_map(|t| t.r_multiple()).collect();
+    let times = trades
+        .iter()
+        .flat_map(|t| [t.open_time, t.close_time])
+        .chain(curve.iter().map(|p| p.time));
+    let mean = |values: &[f64]| if values.is_empty() { 0.0 } else { values.iter().sum::<f64>() / values.len() as f64 };
+
+    PerformanceReport {
+        initial_balance,
+        final_equity: curve.last().map_or(initial_balance + net_profit, |p| p.equity),
+        start: times.clone().min().unwrap_or(0),
+        end: times.max().unwrap_or(0),
+        net_profit,
+        gross_profit,
+        gross_loss,
+        trades: trades.len(),
+        wins: wins.len(),
+        losses: losses.len(),
+        win_rate: if trades.is_empty() { 0.0 } else { wins.len() as f64 / trades.len() as f64 },
+        avg_win: mean(&wins),
+        avg_loss: mean(&losses),
+        largest_win: wins.iter().copied().fold(0.0, f64::max),
+        largest_loss: losses.iter().copied().fold(0.0, f64::min),
+        profit_factor: match (gross_profit > 0.0, gross_loss > 0.0) {
+            (_, true) => Some(gross_profit / gross_loss),
+            (true, false) => None,
+            (false, false) => Some(0.0),
+        },
+        expectancy: mean(&net),
+        max_drawdown,
+        max_drawdown_pct,
+        max_drawdown_duration,
+        sharpe,
+        sortino,
+        r_trades: r.len(),
+        avg_r: mean(&r),
+        r_distribution: r_histogram(&r),
+    }
+}
+
+/// (money, share of the high, longest seconds below a high)
+fn drawdown(initial_balance: f64, curve: &[EquityPoint]) -> (f64, f64, i64) {
+    let (mut peak, mut peak_time) = (initial_balance, curve.first().map_or(0, |p| p.time));
+    let (mut money, mut pct, mut duration) = (0.0f64, 0.0f64, 0i64);
+    for point in curve {
+        if point.equity >= peak {
+            peak = point.equity;
+            peak_time = point.time;
+            continue;
+        }
+        money = money.max(peak - point.equity);
+        if peak > 0.0 {
+            pct = pct.max((peak - point.equity) / peak);
+        }
+        duration = duration.max(point.time - peak_time);
+    }
+    (money, pct, duration)
+}
+
+/// Annualized by the median time between snapshots.
+fn sharpe_sortino(curve: &[EquityPoint]) -> (f64, f64) {
+    let returns: Vec<f64> = curve
+        .windows(2)
+        .filter(|w| w[0].equity > 0.0 && w[1].time > w[0].time)
+        .map(|w| w[1].equity / w[0].equity - 1.0)
+        .collect();
+    if returns.len() < 2 {
+        return (0.0, 0.0);
+    }
+    let mut intervals: Vec<i64> = curve.windows(2).map(|w| w[1].time - w[0].time).filter(|dt| *dt > 0).collect();
+    intervals.sort_unstable();
+    let interval = intervals[intervals.len() / 2] as f64;
+    let annualize = (SECONDS_PER_YEAR / interval).sqrt();
+
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let std = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    let downside = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+    let ratio = |deviation: f64| if deviation > 0.0 { mean / deviation * annualize } else { 0.0 };
+    (ratio(std), ratio(downside))
+}
+
+fn r_histogram(r: &[f64]) -> Vec<RBucket> {
+    let mut buckets = Vec::with_capacity(R_BOUNDS.len() + 1);
+    let mut lower: Option<f64> = None;
+    for upper in R_BOUNDS.into_iter().map(Some).chain([None]) {
+        let label = match (lower, upper) {
+            (None, Some(hi)) => format!("< {}R", hi),
+            (Some(lo), Some(hi)) => format!("{}R .. {}R", lo, hi),
+            (Some(lo), None) => format!("≥ {}R", lo),
+            (None, None) => unreachable!(),
+        };
+        let count = r
+            .iter()
+            .filter(|v| lower.is_none_or(|lo| **v >= lo) && upper.is_none_or(|hi| **v < hi))
+            .count();
+        buckets.push(RBucket { label, count });
+        lower = upper;
+    }
+    buckets
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+#[derive(Serialize)]
+struct Export<'a> {
+    title: &'a str,
+    report: &'a PerformanceReport,
+    trades: &'a [TradeRecord],
+    equity: &'a [EquityPoint],
+}
+
+/// The report with its trades and equity curve, as pretty JSON.
+pub fn to_json(
+    title: &str,
+    report: &PerformanceReport,
+    trades: &[TradeRecord],
+    equity: &[EquityPoint],
+) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&Export { title, report, trades, equity })
+}
+
+/// A standalone HTML page: metrics, R distribution, equity curve (SVG)
+/// and the trade list.
+pub fn to_html(title: &str, report: &PerformanceReport, trades: &[TradeRecord], equity: &[EquityPoint]) -> String {
+    let r = report;
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{0}</title>\n<style>\
+         body{{font-family:sans-serif;margin:24px;background:#1b1b1b;color:#ddd}}\
+         table{{border-collapse:collapse;margin-bottom:20px}}\
+         td,th{{padding:3px 12px;border-bottom:1px solid #333;text-align:right}}\
+         th{{text-align:left}} .pos{{color:#6c6}} .neg{{color:#f66}}\
+         </style></head><body>\n<h1>{0}</h1>\n<p>{1} to {2}</p>\n",
+        escape(title),
+        crate::history::format_time(r.start, false),
+        crate::history::format_time(r.end, false),
+    );
+
+    let profit_factor = r.profit_factor.map_or("∞".to_string(), |pf| format!("{:.2}", pf));
+    let rows = [
+        ("Initial balance", format!("{:.2}", r.initial_balance)),
+        ("Final equity", format!("{:.2}", r.final_equity)),
+        ("Net profit", format!("{:+.2}", r.net_profit)),
+        ("Gross profit / loss", format!("{:.2} / {:.2}", r.gross_profit, r.gross_loss)),
+        ("Trades", format!("{} ({} won, {} lost)", r.trades, r.wins, r.losses)),
+        ("Win rate", format!("{:.1}%", r.win_rate * 100.0)),
+        ("Average win / loss", format!("{:.2} / {:.2}", r.avg_win, r.avg_loss)),
+        ("Largest win / loss", format!("{:.2} / {:.2}", r.largest_win, r.largest_loss)),
+        ("Profit factor", profit_factor),
+        ("Expectancy", format!("{:+.2} per trade", r.expectancy)),
+        ("Max drawdown", format!("{:.2} ({:.1}%)", r.max_drawdown, r.max_drawdown_pct * 100.0)),
+        ("Max drawdown duration", format_duration(r.max_drawdown_duration)),
+        ("Sharpe / Sortino", format!("{:.2} / {:.2}", r.sharpe, r.sortino)),
+        ("Average R", format!("{:+.2}R over {} trades with a stop", r.avg_r, r.r_trades)),
+    ];
+    html.push_str("<h2>Summary</h2>\n<table>\n");
+    for (name, value) in rows {
+        let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", name, escape(&value));
+    }
+    html.push_str("</table>\n<h2>R-multiples</h2>\n<table>\n");
+    for bucket in &r.r_distribution {
+        let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", escape(&bucket.label), bucket.count);
+    }
+    html.push_str("</table>\n<h2>Equity</h2>\n");
+    html.push_str(&equity_svg(equity, 900.0, 240.0));
+
+    html.push_str("\n<h2>Trades</h2>\n<table>\n<tr><th>Ticket</th><th>Side</th><th>Lots</th><th>Opened</th>\
+                   <th>Closed</th><th>Open</th><th>Close</th><th>Net</th><th>R</th></tr>\n");
+    for t in trades {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{:.2}</td><td>{}</td><td>{}</td><td>{:.5}</td><td>{:.5}</td>\
+             <td class=\"{}\">{:+.2}</td><td>{}</td></tr>",
+            t.ticket,
+            t.side.as_str(),
+            t.volume,
+            crate::history::format_time(t.open_time, true),
+            crate::history::format_time(t.close_time, true),
+            t.open_price,
+            t.close_price,
+            if t.net_profit >= 0.0 { "pos" } else { "neg" },
+            t.net_profit,
+            t.r_multiple().map_or(String::new(), |r| format!("{:+.2}", r)),
+        );
+    }
+    html.push_str("</table>\n</body></html>\n");
+    html
+}
+
+fn equity_svg(equity: &[EquityPoint], width: f64, height: f64) -> String {
+    let (Some(first), Some(last)) = (equity.first(), equity.last()) else {
+        return "<p>No equity snapshots.</p>".to_string();
+    };
+    let low = equity.iter().map(|p| p.equity).fold(f64::INFINITY, f64::min);
+    let high = equity.iter().map(|p| p.equity).fold(f64::NEG_INFINITY, f64::max);
+    let span_t = (last.time - first.time).max(1) as f64;
+    let span_v = (high - low).max(1e-9);
+    let points: Vec<String> = equity
+        .iter()
+        .map(|p| {
+            let x = (p.time - first.time) as f64 / span_t * width;
+            let y = height - (p.equity - low) / span_v * height;
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+    format!(
+        "<svg width=\"{w}\" height=\"{h}\" style=\"background:#111\">\
+         <polyline fill=\"none\" stroke=\"#64b4ff\" stroke-width=\"1.5\" points=\"{p}\"/>\
+         <text x=\"4\" y=\"14\" fill=\"#888\" font-size=\"12\">{hi:.2}</text>\
+         <text x=\"4\" y=\"{b}\" fill=\"#888\" font-size=\"12\">{lo:.2}</text></svg>",
+        w = width,
+        h = height,
+        p = points.join(" "),
+        hi = high,
+        lo = low,
+        b = height - 4.0,
+    )
+}
+
+/// "3d 04:05:06" style duration.
+pub fn format_duration(seconds: i64) -> String {
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+    let clock = format!("{:02}:{:02}:{:02}", rest / 3600, rest % 3600 / 60, rest % 60);
+    if days > 0 { format!("{}d {}", days, clock) } else { clock }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(ticket: u64, close_time: i64, net_profit: f64, risk: Option<f64>) -> TradeRecord {
+        TradeRecord {
+            ticket,
+            side: Side::Buy,
+            volume: 0.1,
+            open_time: close_time - 10,
+            close_time,
+            open_price: 2000.0,
+            close_price: 2000.0,
+            net_profit,
+            risk,
+        }
+    }
+
+    fn point(time: i64, equity: f64) -> EquityPoint {
+        EquityPoint { time, balance: equity, equity }
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn trade_metrics() {
+        let trades = [trade(1, 100, 100.0, Some(50.0)), trade(2, 200, -50.0, Some(50.0)), trade(3, 300, 30.0, None)];
+        let report = analyze(1000.0, &trades, &[]);
+        assert_eq!((report.trades, report.wins, report.losses), (3, 2, 1));
+        assert_close(report.net_profit, 80.0);
+        assert_close(report.gross_profit, 130.0);
+        assert_close(report.gross_loss, 50.0);
+        assert_close(report.profit_factor.unwrap(), 2.6);
+        assert_close(report.win_rate, 2.0 / 3.0);
+        assert_close(report.avg_win, 65.0);
+        assert_close(report.avg_loss, -50.0);
+        assert_close(report.largest_win, 100.0);
+        assert_close(report.largest_loss, -50.0);
+        assert_close(report.expectancy, 80.0 / 3.0);
+        assert_close(report.final_equity, 1080.0);
+        assert_eq!((report.start, report.end), (90, 300));
+        assert_eq!(report.r_trades, 2);
+        assert_close(report.avg_r, 0.5);
+    }
+
+    #[test]
+    fn drawdown_falls_back_to_the_balance_curve() {
+        // Balance 1100 at t=100, 1050 at t=200, 1080 at t=300: still below the high
+        let trades = [trade(1, 100, 100.0, None), trade(2, 200, -50.0, None), trade(3, 300, 30.0, None)];
+        let report = analyze(1000.0, &trades, &[]);
+        assert_close(report.max_drawdown, 50.0);
+        assert_close(report.max_drawdown_pct, 50.0 / 1100.0);
+        assert_eq!(report.max_drawdown_duration, 200);
+
+        // Snapshots take precedence over the trades
+        let equity = [point(0, 1000.0), point(10, 1200.0), point(20, 900.0), point(30, 1000.0), point(40, 1300.0)];
+        let report = analyze(1000.0, &trades, &equity);
+        assert_close(report.max_drawdown, 300.0);
+        assert_close(report.max_drawdown_pct, 0.25);
+        assert_eq!(report.max_drawdown_duration, 20);
+        assert_close(report.final_equity, 1300.0);
+    }
+
+    #[test]
+    fn profit_factor_edge_cases() {
+        let report = analyze(1000.0, &[trade(1, 100, 10.0, None), trade(2, 200, 5.0, None)], &[]);
+        assert_eq!(report.profit_factor, None);
+        assert_eq!(report.max_drawdown, 0.0);
+
+        let report = analyze(1000.0, &[], &[]);
+        assert_eq!(report.profit_factor, Some(0.0));
+        assert_eq!(report.win_rate, 0.0);
+        assert_eq!(report.expectancy, 0.0);
+        assert_eq!(report.final_equity, 1000.0);
+        assert_eq!((report.start, report.end), (0, 0));
+    }
+
+    #[test]
+    fn report_round_trips_through_json() {
+        let equity = [point(0, 1000.0), point(100, 1010.0), point(200, 1015.0)];
+        let no_losses = analyze(1000.0, &[trade(1, 100, 10.0, Some(5.0)), trade(2, 200, 5.0, None)], &equity);
+        let mixed = analyze(1000.0, &[trade(1, 100, 10.0, None), trade(2, 200, -4.0, None)], &[]);
+        for report in [no_losses, mixed, analyze(1000.0, &[], &[])] {
+            let json = serde_json::to_string(&report).unwrap();
+            assert_eq!(serde_json::from_str::<PerformanceReport>(&json).unwrap(), report, "{}", json);
+        }
+
+        let json = serde_json::to_value(analyze(1000.0, &[trade(1, 100, 10.0, None)], &[])).unwrap();
+        assert!(json["profit_factor"].is_null());
+    }
+
+    #[test]
+    fn sharpe_and_sortino() {
+        // Daily returns of +10%, -10%, +10%
+        let day = 86_400;
+        let curve = [point(0, 100.0), point(day, 110.0), point(2 * day, 99.0), point(3 * day, 108.9)];
+        let (sharpe, sortino) = sharpe_sortino(&curve);
+        let annualize = 365.25f64.sqrt();
+        assert_close(sharpe, 75f64.sqrt() / 30.0 * annualize);
+        assert_close(sortino, 300f64.sqrt() / 30.0 * annualize);
+    }
+
+    #[test]
+    fn sharpe_needs_two_returns() {
+        assert_eq!(sharpe_sortino(&[]), (0.0, 0.0));
+        assert_eq!(sharpe_sortino(&[point(0, 100.0), point(60, 110.0)]), (0.0, 0.0));
+        // Snapshots at the same time give no return
+        assert_eq!(sharpe_sortino(&[point(0, 100.0), point(60, 110.0), point(60, 120.0)]), (0.0, 0.0));
+        // Flat equity has no deviation
+        assert_eq!(sharpe_sortino(&[point(0, 100.0), point(60, 100.0), point(120, 100.0)]), (0.0, 0.0));
+    }
+
+    #[test]
+    fn r_buckets_include_their_lower_bound() {
+        let buckets = r_histogram(&[-2.5, -2.0, -1.0001, 0.0, 0.999, 1.0, 3.0, 7.0]);
+        let labels: Vec<&str> = buckets.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, ["< -2R", "-2R .. -1R", "-1R .. 0R", "0R .. 1R", "1R .. 2R", "2R .. 3R", "≥ 3R"]);
+        let counts: Vec<usize> = buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, [1, 2, 0, 2, 1, 0, 2]);
+    }
+}
@@ -0,0 +1,322 @@
+//+------------------------------------------------------------------+
+//|                                                      backtest.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+//! Event-driven backtests: a [`Strategy`] trades recorded history through
+//! the simulated broker, tick by tick, the way it would trade live.
+
+use crate::analytics::{self, EquityPoint, PerformanceReport, TradeRecord};
+use crate::bars::{self, Bar};
+use crate::history::{CsvData, TickRow};
+use crate::sim::{ClosedTrade, SimBroker, SimSettings};
+use crate::strategy::{Strategy, StrategyRuntime};
+use crate::Timeframe;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+/// Most requests one tick may trigger, so a strategy that answers every
+/// reply with another request cannot hang the run.
+const MAX_REQUESTS_PER_TICK: usize = 1000;
+
+/// Seconds between equity curve samples.
+const EQUITY_SAMPLE_SECS: i64 = 60;
+
+// ============================================================================
+// Settings & Results
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct BacktestSettings {
+    /// Account, lot rules, slippage and commission of the simulated broker
+    pub sim: SimSettings,
+    /// Bar period of an OHLC file; `None` infers it from the bar spacing
+    pub timeframe: Option<Timeframe>,
+    /// Spread (price units) for bars whose Spread column is 0
+    pub default_spread: f64,
+}
+
+impl Default for BacktestSettings {
+    fn default() -> Self {
+        Self { sim: SimSettings::default(), timeframe: None, default_spread: 0.0 }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BacktestReport {
+    pub strategy: String,
+    /// Closed trades in closing order; positions still open at the end of
+    /// the data are closed at the last price
+    pub trades: Vec<ClosedTrade>,
+    pub equity: Vec<EquityPoint>,
+    /// Metrics over `trades` (as [`TradeRecord`]s) and `equity`
+    pub records: Vec<TradeRecord>,
+    pub report: PerformanceReport,
+    pub commission: f64,
+    pub ticks: usize,
+    /// Requests the simulated broker rejected
+    pub rejected: usize,
+}
+
+// ============================================================================
+// Runner
+// ============================================================================
+
+/// Backtests over a tick or OHLC file read with [`crate::history::read_csv`].
+pub fn run(strategy: Box<dyn Strategy>, data: &CsvData, settings: &BacktestSettings) -> BacktestReport {
+    run_ticks(strategy, &ticks_of(data, settings), settings)
+}
+
+/// The ticks a backtest over `data` trades: tick files as they are, bars
+/// through [`ticks_from_bars`].
+pub fn ticks_of<'a>(data: &'a CsvData, settings: &BacktestSettings) -> Cow<'a, [TickRow]> {
+    match data {
+        CsvData::Ticks(ticks) => Cow::Borrowed(ticks),
+        CsvData::Bars(rows) => {
+            let timeframe = settings.timeframe.or_else(|| bars::infer_timeframe(rows)).unwrap_or(Timeframe::M1);
+            Cow::Owned(ticks_from_bars(rows, timeframe, settings.default_spread))
+        }
+    }
+}
+
+/// Feeds every tick to the broker, then to the strategy; its requests are
+/// executed at once, at that tick's prices.
+pub fn run_ticks(strategy: Box<dyn Strategy>, ticks: &[TickRow], settings: &BacktestSettings) -> BacktestReport {
+    let name = strategy.name().to_string();
+    let mut broker = SimBroker::new(settings.sim.clone());
+    let mut runtime = StrategyRuntime::new(&settings.sim.symbol);
+    runtime.add(strategy);
+    runtime.set_enabled(0, true);
+
+    let mut equity = Vec::new();
+    let mut rejected = 0;
+    let mut sampled = None;
+    for tick in ticks {
+        broker.update_price(tick.bid, tick.ask, tick.time);
+        let tick_data = broker.tick_data(tick.volume);
+
+        let mut queue: VecDeque<_> = runtime.on_position_update(&tick_data.positions, &tick_data.orders).into();
+        queue.extend(runtime.on_tick(&tick_data));
+        queue.extend(runtime.on_timer(tick.time));
+        let mut executed = 0;
+        while let Some((index, request)) = queue.pop_front() {
+            if executed == MAX_REQUESTS_PER_TICK {
+                break;
+            }
+            executed += 1;
+            let response = broker.execute(&request);
+            if !response.success {
+                rejected += 1;
+            }
+            queue.extend(runtime.on_order_response(index, &request, &response));
+        }
+
+        let bucket = tick.time.div_euclid(EQUITY_SAMPLE_SECS);
+        if sampled != Some(bucket) {
+            sampled = Some(bucket);
+            equity.push(EquityPoint { time: tick.time, balance: broker.balance(), equity: broker.equity() });
+        }
+    }
+
+    // Settle what is still open at the last price
+    let open: Vec<u64> = broker.positions().iter().map(|p| p.ticket).collect();
+    for ticket in open {
+        broker.close_position(ticket);
+    }
+    if let Some(last) = ticks.last() {
+        equity.push(EquityPoint { time: last.time, balance: broker.balance(), equity: broker.equity() });
+    }
+
+    let trades = broker.closed_trades().to_vec();
+    let records: Vec<TradeRecord> =
+        trades.iter().map(|t| TradeRecord::from_closed(t, settings.sim.contract_size)).collect();
+    BacktestReport {
+        strategy: name,
+        report: analytics::analyze(settings.sim.initial_balance, &records, &equity),
+        // An empty f64 sum is -0.0; adding 0.0 keeps it from showing as "-0.00"
+        commission: trades.iter().map(|t| t.commission).sum::<f64>() + 0.0,
+        records,
+        trades,
+        equity,
+        ticks: ticks.len(),
+        rejected,
+    }
+}
+
+/// Four ticks per bar, a quarter of `timeframe` apart, like MT5's
+/// "1 minute OHLC" model: open, then low and high (low first on a bullish
+/// bar, high first on a bearish one), then close. Bars hold bid prices;
+/// the ask adds the bar's spread (or `default_spread`).
+pub fn ticks_from_bars(bars: &[Bar], timeframe: Timeframe, default_spread: f64) -> Vec<TickRow> {
+    let mut ticks = Vec::with_capacity(bars.len() * 4);
+    let duration = timeframe.seconds();
+    for bar in bars {
+        let spread = if bar.spread > 0.0 { bar.spread } else { default_spread };
+        let (first, second) = if bar.close >= bar.open { (bar.low, bar.high) } else { (bar.high, bar.low) };
+        let volume = bar.tick_volume / 4;
+        for (step, price) in [bar.open, first, second, bar.close].into_iter().enumerate() {
+            ticks.push(TickRow {
+                time: bar.time + duration * step as i64 / 4,
+                bid: price,
+                ask: price + spread,
+                volume,
+            });
+        }
+    }
+    ticks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::CloseReason;
+    use crate::strategy::OrderIntent;
+    use crate::{Side, TickData};
+
+    // 2026-01-23 00:00:00 UTC, a Friday
+    const FRI: i64 = 1769126400;
+    const MON: i64 = FRI + 3 * 86400;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    /// Sends one market order on the first tick, then holds.
+    struct OpenOnce {
+        side: Side,
+        volume: f64,
+        sent: bool,
+    }
+
+    impl Strategy for OpenOnce {
+        fn name(&self) -> &str {
+            "open once"
+        }
+
+        fn on_tick(&mut self, _tick: &TickData) -> Vec<OrderIntent> {
+            if std::mem::replace(&mut self.sent, true) {
+                return Vec::new();
+            }
+            vec![OrderIntent::Market { side: self.side, volume: self.volume, sl: 0.0, tp: 0.0 }]
+        }
+    }
+
+    fn open_once(side: Side, volume: f64) -> Box<dyn Strategy> {
+        Box::new(OpenOnce { side, volume, sent: false })
+    }
+
+    fn bar(time: i64, open: f64, high: f64, low: f64, close: f64, spread: f64) -> Bar {
+        Bar { time, open, high, low, close, tick_volume: 8, spread }
+    }
+
+    /// A bullish M5 bar on Friday and a bearish one after the weekend; only
+    /// the first has a Spread column value.
+    fn weekend_bars() -> Vec<Bar> {
+        vec![bar(FRI, 2000.0, 2002.0, 1999.0, 2001.0, 0.3), bar(MON, 2001.0, 2003.0, 1998.0, 1999.5, 0.0)]
+    }
+
+    #[test]
+    fn bar_ticks_follow_mt5_order_over_the_timeframe() {
+        let ticks = ticks_from_bars(&weekend_bars(), Timeframe::M5, 0.5);
+        let rows: Vec<(i64, f64)> = ticks.iter().map(|t| (t.time, t.bid)).collect();
+        assert_eq!(
+            rows,
+            vec![
+                // Bullish: open, low, high, close
+                (FRI, 2000.0),
+                (FRI + 75, 1999.0),
+                (FRI + 150, 2002.0),
+                (FRI + 225, 2001.0),
+                // Bearish: open, high, low, close; the weekend does not stretch the bar
+                (MON, 2001.0),
+                (MON + 75, 2003.0),
+                (MON + 150, 1998.0),
+                (MON + 225, 1999.5),
+            ]
+        );
+        for tick in &ticks[..4] {
+            assert_close(tick.ask - tick.bid, 0.3);
+            assert_eq!(tick.volume, 2);
+        }
+        for tick in &ticks[4..] {
+            assert_close(tick.ask - tick.bid, 0.5);
+        }
+    }
+
+    #[test]
+    fn bar_timeframe_comes_from_settings_or_spacing() {
+        let bars = vec![bar(FRI, 1.0, 1.0, 1.0, 1.0, 0.0), bar(FRI + 900, 1.0, 1.0, 1.0, 1.0, 0.0)];
+        let data = CsvData::Bars(bars);
+
+        let inferred = ticks_of(&data, &BacktestSettings::default());
+        assert_eq!(inferred[1].time - inferred[0].time, 225);
+
+        let settings = BacktestSettings { timeframe: Some(Timeframe::M1), ..Default::default() };
+        let given = ticks_of(&data, &settings);
+        assert_eq!(given[1].time - given[0].time, 15);
+    }
+
+    #[test]
+    fn fills_at_ask_and_bid_with_bar_spread() {
+        let settings = BacktestSettings { timeframe: Some(Timeframe::M5), default_spread: 0.5, ..Default::default() };
+        let data = CsvData::Bars(weekend_bars());
+
+        let long = run(open_once(Side::Buy, 0.1), &data, &settings);
+        assert_eq!(long.trades.len(), 1);
+        // Bought at the first ask, sold at the last bid
+        assert_close(long.trades[0].open_price, 2000.3);
+        assert_close(long.trades[0].close_price, 1999.5);
+        assert_close(long.trades[0].profit, (1999.5 - 2000.3) * 0.1 * 100.0);
+
+        let short = run(open_once(Side::Sell, 0.1), &data, &settings);
+        // Sold at the first bid, bought back at the last ask (default spread)
+        assert_close(short.trades[0].open_price, 2000.0);
+        assert_close(short.trades[0].close_price, 2000.0);
+        assert_close(short.trades[0].profit, 0.0);
+    }
+
+    #[test]
+    fn slippage_and_commission_apply_to_every_fill() {
+        let mut settings = BacktestSettings { timeframe: Some(Timeframe::M5), ..Default::default() };
+        settings.sim.slippage = 0.05;
+        settings.sim.commission_per_lot = 7.0;
+        let report = run(open_once(Side::Buy, 0.2), &CsvData::Bars(weekend_bars()), &settings);
+
+        let trade = &report.trades[0];
+        assert_close(trade.open_price, 2000.3 + 0.05);
+        assert_close(trade.close_price, 1999.5 - 0.05);
+        assert_close(trade.commission, 1.4);
+        assert_close(report.commission, 1.4);
+        assert_close(report.records[0].net_profit, trade.profit - 1.4);
+    }
+
+    #[test]
+    fn volumes_off_the_lot_rules_are_rejected() {
+        let settings = BacktestSettings { timeframe: Some(Timeframe::M5), ..Default::default() };
+        let data = CsvData::Bars(weekend_bars());
+        for volume in [0.015, 0.001, 500.0] {
+            let report = run(open_once(Side::Buy, volume), &data, &settings);
+            assert_eq!(report.rejected, 1, "{}", volume);
+            assert!(report.trades.is_empty());
+            // No trades: commission stays a clean zero
+            assert_eq!(report.commission.to_string(), "0");
+        }
+    }
+
+    #[test]
+    fn open_positions_are_closed_at_the_end() {
+        let settings = BacktestSettings { timeframe: Some(Timeframe::M5), ..Default::default() };
+        let report = run(open_once(Side::Buy, 0.1), &CsvData::Bars(weekend_bars()), &settings);
+
+        assert_eq!(report.ticks, 8);
+        let trade = &report.trades[0];
+        assert_eq!(trade.reason, CloseReason::Manual);
+        assert_eq!((trade.open_time, trade.close_time), (FRI, MON + 225));
+
+        let last = report.equity.last().unwrap();
+        assert_eq!(last.time, MON + 225);
+        assert_close(last.balance, settings.sim.initial_balance + trade.net_profit());
+        assert_close(last.equity, last.balance);
+    }
+}
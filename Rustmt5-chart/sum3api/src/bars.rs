@@ -0,0 +1,167 @@
+//+------------------------------------------------------------------+
+//|                                                          bars.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::Timeframe;
+
+// ============================================================================
+// OHLC Bars
+// ============================================================================
+
+/// One OHLC bar, built from bid prices like MT5's own rates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bar {
+    /// Open time, aligned to the timeframe
+    pub time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub tick_volume: u64,
+    /// Smallest ask - bid seen in the bar, in price units
+    pub spread: f64,
+}
+
+/// Incremental tick-to-bar aggregator for one timeframe.
+#[derive(Clone, Debug)]
+pub struct BarAggregator {
+    timeframe: Timeframe,
+    current: Option<Bar>,
+}
+
+impl BarAggregator {
+    pub fn new(timeframe: Timeframe) -> Self {
+        Self { timeframe, current: None }
+    }
+
+    pub fn timeframe(&self) -> Timeframe {
+        self.timeframe
+    }
+
+    /// Adds a tick. Returns the previous bar once a tick opens a new one.
+    pub fn push(&mut self, time: i64, bid: f64, ask: f64) -> Option<Bar> {
+        let open_time = time - time.rem_euclid(self.timeframe.seconds());
+        let spread = (ask - bid).max(0.0);
+
+        match &mut self.current {
+            Some(bar) if bar.time == open_time => {
+                bar.high = bar.high.max(bid);
+                bar.low = bar.low.min(bid);
+                bar.close = bid;
+                bar.tick_volume += 1;
+                bar.spread = bar.spread.min(spread);
+                None
+            }
+            // Out-of-order tick from an earlier bar: fold into the current one
+            Some(bar) if open_time < bar.time => {
+                bar.close = bid;
+                bar.high = bar.high.max(bid);
+                bar.low = bar.low.min(bid);
+                bar.tick_volume += 1;
+                None
+            }
+            _ => self.current.replace(Bar {
+                time: open_time,
+                open: bid,
+                high: bid,
+                low: bid,
+                close: bid,
+                tick_volume: 1,
+                spread,
+            }),
+        }
+    }
+
+    /// The bar still forming, if any.
+    pub fn current(&self) -> Option<&Bar> {
+        self.current.as_ref()
+    }
+}
+
+/// Smallest spacing between bars, matched to a known timeframe.
+pub fn infer_timeframe(bars: &[Bar]) -> Option<Timeframe> {
+    let step = bars.windows(2).map(|w| w[1].time - w[0].time).filter(|d| *d > 0).min()?;
+    Timeframe::ALL.into_iter().find(|tf| tf.seconds() == step)
+}
+
+/// Aggregates a whole tick series into completed bars plus the forming one.
+pub fn aggregate(timeframe: Timeframe, ticks: impl IntoIterator<Item = (i64, f64, f64)>) -> Vec<Bar> {
+    let mut aggregator = BarAggregator::new(timeframe);
+    let mut bars: Vec<Bar> = ticks
+        .into_iter()
+        .filter_map(|(time, bid, ask)| aggregator.push(time, bid, ask))
+        .collect();
+    bars.extend(aggregator.current().copied());
+    bars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-01-26 00:00:00 UTC, a Monday
+    const T0: i64 = 1769385600;
+
+    #[test]
+    fn closes_bar_on_first_tick_past_boundary() {
+        let mut agg = BarAggregator::new(Timeframe::M1);
+        assert_eq!(agg.push(T0 + 5, 2000.0, 2000.3), None);
+        assert_eq!(agg.push(T0 + 20, 2002.0, 2002.2), None);
+        assert_eq!(agg.push(T0 + 40, 1999.0, 1999.4), None);
+        assert_eq!(agg.push(T0 + 59, 2001.0, 2001.5), None);
+
+        let closed = agg.push(T0 + 60, 2001.5, 2001.7).expect("minute closed");
+        assert_eq!(
+            (closed.time, closed.open, closed.high, closed.low, closed.close, closed.tick_volume),
+            (T0, 2000.0, 2002.0, 1999.0, 2001.0, 4)
+        );
+        // Narrowest spread of the bar
+        assert!((closed.spread - 0.2).abs() < 1e-9);
+
+        let forming = agg.current().expect("new bar forming");
+        assert_eq!(forming.time, T0 + 60);
+        assert_eq!(forming.open, 2001.5);
+        assert_eq!(forming.tick_volume, 1);
+    }
+
+    #[test]
+    fn bar_time_is_aligned_to_timeframe() {
+        let mut agg = BarAggregator::new(Timeframe::H1);
+        agg.push(T0 + 3 * 3600 + 1234, 2000.0, 2000.5);
+        assert_eq!(agg.current().unwrap().time, T0 + 3 * 3600);
+
+        // A gap of several hours closes the bar and skips empty ones
+        let closed = agg.push(T0 + 7 * 3600 + 1, 2001.0, 2001.5).unwrap();
+        assert_eq!(closed.time, T0 + 3 * 3600);
+        assert_eq!(agg.current().unwrap().time, T0 + 7 * 3600);
+    }
+
+    #[test]
+    fn out_of_order_tick_folds_into_current_bar() {
+        let mut agg = BarAggregator::new(Timeframe::M1);
+        agg.push(T0 + 10, 2000.0, 2000.5);
+        agg.push(T0 + 70, 2001.0, 2001.5);
+
+        // Late tick from the first minute: no new bar, and it becomes the
+        // forming bar's close even though it is older than the last tick.
+        assert_eq!(agg.push(T0 + 30, 1998.0, 1998.5), None);
+        let forming = agg.current().unwrap();
+        assert_eq!(forming.time, T0 + 60);
+        assert_eq!(forming.open, 2001.0);
+        assert_eq!(forming.close, 1998.0);
+        assert_eq!(forming.low, 1998.0);
+        assert_eq!(forming.high, 2001.0);
+        assert_eq!(forming.tick_volume, 2);
+    }
+
+    #[test]
+    fn aggregate_includes_forming_bar() {
+        let ticks = [(T0, 1.0, 1.1), (T0 + 30, 2.0, 2.1), (T0 + 60, 3.0, 3.1), (T0 + 125, 4.0, 4.1)];
+        let bars = aggregate(Timeframe::M1, ticks);
+        let summary: Vec<_> = bars.iter().map(|b| (b.time, b.open, b.close, b.tick_volume)).collect();
+        assert_eq!(summary, vec![(T0, 1.0, 2.0, 2), (T0 + 60, 3.0, 3.0, 1), (T0 + 120, 4.0, 4.0, 1)]);
+        assert!(aggregate(Timeframe::M1, []).is_empty());
+    }
+}
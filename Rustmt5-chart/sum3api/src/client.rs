@@ -0,0 +1,324 @@
+//+------------------------------------------------------------------+
+//|                                                        client.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::feed::{run_tick_subscriber, FeedSettings, FeedStatus};
+use crate::orders::{run_order_handler, OrderEnvelope};
+use crate::{
+    CloseFilter, HistoryData, HistoryMode, OrderRequest, OrderResponse, Request, TickData, Timeframe, TradeError,
+};
+use futures::Stream;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+
+// ============================================================================
+// Settings & Errors
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct ClientSettings {
+    /// SUB address of the EA tick publisher
+    pub tick_address: String,
+    /// REQ address of the EA order handler
+    pub order_address: String,
+    pub feed: FeedSettings,
+    /// How long to wait for the EA to answer one request
+    pub order_timeout: Duration,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            tick_address: "tcp://127.0.0.1:5555".to_string(),
+            order_address: "tcp://127.0.0.1:5556".to_string(),
+            feed: FeedSettings {
+                stale_after: Duration::from_secs(5),
+                reconnect_after: Duration::from_secs(60),
+            },
+            order_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The background order task is gone
+    Closed,
+    /// The EA answered with `success: false`
+    Rejected(OrderResponse),
+    /// The EA never answered; the order may or may not have executed
+    TimedOut(OrderResponse),
+    /// The EA answered, but not in the shape the call expected
+    UnexpectedReply(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Closed => write!(f, "client is shut down"),
+            ClientError::Rejected(r) | ClientError::TimedOut(r) => {
+                write!(f, "{}", r.error.as_deref().unwrap_or("Unknown error"))
+            }
+            ClientError::UnexpectedReply(msg) => write!(f, "unexpected reply: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl ClientError {
+    /// The MT5 trade error behind a rejection, if the EA reported one.
+    pub fn trade_error(&self) -> Option<TradeError> {
+        match self {
+            ClientError::Rejected(r) => r.trade_error(),
+            _ => None,
+        }
+    }
+
+    /// Whether sending the same request again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        self.trade_error().is_some_and(|e| e.is_retryable())
+    }
+}
+
+// ============================================================================
+// Client
+// ============================================================================
+
+/// Handle to a running SUM3API connection. Cheap to clone; the background
+/// tasks stop when the last clone is dropped.
+#[derive(Clone)]
+pub struct Client {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    tick_tx: broadcast::Sender<TickData>,
+    order_tx: mpsc::Sender<OrderEnvelope>,
+    status_rx: watch::Receiver<FeedStatus>,
+    next_id: AtomicU64,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl Client {
+    /// Starts the tick subscriber and order handler tasks.
+    /// Must be called from within a Tokio runtime.
+    pub fn new(settings: ClientSettings) -> Self {
+        let (tick_tx, _) = broadcast::channel(1024);
+        let (order_tx, order_rx) = mpsc::channel(10);
+        let (status_tx, status_rx) = watch::channel(FeedStatus::default());
+
+        let tasks = vec![
+            tokio::spawn(run_tick_subscriber(
+                settings.tick_address.clone(),
+                settings.feed,
+                tick_tx.clone(),
+                status_tx,
+            )),
+            tokio::spawn(run_order_handler(settings.order_address.clone(), settings.order_timeout, order_rx)),
+        ];
+
+        Self {
+            inner: Arc::new(Inner {
+                tick_tx,
+                order_tx,
+                status_rx,
+                next_id: AtomicU64::new(1),
+                tasks,
+            }),
+        }
+    }
+
+    /// Stream of ticks from the moment of subscription. A subscriber that
+    /// falls too far behind skips the missed ticks instead of blocking others.
+    pub fn subscribe_ticks(&self) -> impl Stream<Item = TickData> + Send + 'static {
+        let rx = self.inner.tick_tx.subscribe();
+        futures::stream::unfold(rx, |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(tick) => return Some((tick, rx)),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        eprintln!("Tick subscriber lagged, skipped {} ticks", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+
+    /// Watch handle for the Connected / Stale / Disconnected feed state.
+    pub fn feed_status(&self) -> watch::Receiver<FeedStatus> {
+        self.inner.status_rx.clone()
+    }
+
+    /// Allocates a request ID unique within this client.
+    pub fn next_request_id(&self) -> u64 {
+        self.inner.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Sends a raw request and returns whatever the EA answered, including
+    /// failures. Assigns a request ID if the request has none.
+    pub async fn request(&self, request: impl Into<OrderRequest>) -> Result<OrderResponse, ClientError> {
+        let mut request = request.into();
+        if request.request_id.is_none() {
+            request.request_id = Some(self.next_request_id());
+        }
+        let (reply, reply_rx) = oneshot::channel();
+        self.inner
+            .order_tx
+            .send(OrderEnvelope { request, reply })
+            .await
+            .map_err(|_| ClientError::Closed)?;
+        reply_rx.await.map_err(|_| ClientError::Closed)
+    }
+
+    /// Like [`Client::request`], but turns EA failures into errors.
+    pub async fn execute(&self, request: impl Into<OrderRequest>) -> Result<OrderResponse, ClientError> {
+        let response = self.request(request).await?;
+        if response.timed_out {
+            Err(ClientError::TimedOut(response))
+        } else if !response.success {
+            Err(ClientError::Rejected(response))
+        } else {
+            Ok(response)
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Trading
+    // ------------------------------------------------------------------------
+
+    /// `sl`/`tp` are absolute prices, 0 for none (likewise below).
+    pub async fn market_buy(&self, symbol: &str, volume: f64, sl: f64, tp: f64) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::MarketBuy { symbol: symbol.to_string(), volume, sl, tp }).await
+    }
+
+    pub async fn market_sell(&self, symbol: &str, volume: f64, sl: f64, tp: f64) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::MarketSell { symbol: symbol.to_string(), volume, sl, tp }).await
+    }
+
+    pub async fn limit_buy(
+        &self,
+        symbol: &str,
+        volume: f64,
+        price: f64,
+        sl: f64,
+        tp: f64,
+    ) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::LimitBuy { symbol: symbol.to_string(), volume, price, sl, tp }).await
+    }
+
+    pub async fn limit_sell(
+        &self,
+        symbol: &str,
+        volume: f64,
+        price: f64,
+        sl: f64,
+        tp: f64,
+    ) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::LimitSell { symbol: symbol.to_string(), volume, price, sl, tp }).await
+    }
+
+    pub async fn stop_buy(
+        &self,
+        symbol: &str,
+        volume: f64,
+        price: f64,
+        sl: f64,
+        tp: f64,
+    ) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::StopBuy { symbol: symbol.to_string(), volume, price, sl, tp }).await
+    }
+
+    pub async fn stop_sell(
+        &self,
+        symbol: &str,
+        volume: f64,
+        price: f64,
+        sl: f64,
+        tp: f64,
+    ) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::StopSell { symbol: symbol.to_string(), volume, price, sl, tp }).await
+    }
+
+    pub async fn close_position(&self, symbol: &str, ticket: u64) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::ClosePosition { symbol: symbol.to_string(), ticket }).await
+    }
+
+    pub async fn cancel_order(&self, symbol: &str, ticket: u64) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::CancelOrder { symbol: symbol.to_string(), ticket }).await
+    }
+
+    pub async fn close_partial(&self, symbol: &str, ticket: u64, volume: f64) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::ClosePartial { symbol: symbol.to_string(), ticket, volume }).await
+    }
+
+    pub async fn close_all(&self, symbol: &str, filter: CloseFilter) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::CloseAll { symbol: symbol.to_string(), filter }).await
+    }
+
+    pub async fn cancel_all(&self, symbol: &str) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::CancelAll { symbol: symbol.to_string() }).await
+    }
+
+    pub async fn reverse(&self, symbol: &str, ticket: u64) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::Reverse { symbol: symbol.to_string(), ticket }).await
+    }
+
+    pub async fn modify_position(&self, symbol: &str, ticket: u64, sl: f64, tp: f64) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::ModifyPosition { symbol: symbol.to_string(), ticket, sl, tp }).await
+    }
+
+    /// `expiration` is server time in epoch seconds, 0 for good till cancelled.
+    pub async fn modify_order(
+        &self,
+        symbol: &str,
+        ticket: u64,
+        price: f64,
+        sl: f64,
+        tp: f64,
+        expiration: i64,
+    ) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::ModifyOrder { symbol: symbol.to_string(), ticket, price, sl, tp, expiration }).await
+    }
+
+    /// Open positions and pending orders for `symbol`, straight from the EA.
+    pub async fn positions(&self, symbol: &str) -> Result<OrderResponse, ClientError> {
+        self.execute(Request::GetPositions { symbol: symbol.to_string() }).await
+    }
+
+    /// Downloads history as CSV. `start`/`end` use "yyyy.mm.dd".
+    pub async fn download_history(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        start: &str,
+        end: &str,
+        mode: HistoryMode,
+    ) -> Result<HistoryData, ClientError> {
+        let request = Request::DownloadHistory {
+            symbol: symbol.to_string(),
+            timeframe,
+            start: start.to_string(),
+            end: end.to_string(),
+            mode,
+        };
+        let response = self.execute(request).await?;
+        let message = response.message.unwrap_or_default();
+        HistoryData::parse(&message).ok_or(ClientError::UnexpectedReply(message))
+    }
+}
@@ -0,0 +1,285 @@
+//+------------------------------------------------------------------+
+//|                                                        errors.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use std::fmt;
+
+// ============================================================================
+// MT5 Trade Server Return Codes
+// ============================================================================
+
+/// Failed `MqlTradeResult.retcode` values (TRADE_RETCODE_*), as sent by the
+/// EA in the `retcode` field of a failed response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TradeError {
+    Requote,                 // 10004
+    Rejected,                // 10006
+    Canceled,                // 10007
+    PartiallyFilled,         // 10010
+    RequestError,            // 10011
+    Timeout,                 // 10012
+    InvalidRequest,          // 10013
+    InvalidVolume,           // 10014
+    InvalidPrice,            // 10015
+    InvalidStops,            // 10016
+    TradeDisabled,           // 10017
+    MarketClosed,            // 10018
+    NoMoney,                 // 10019
+    PriceChanged,            // 10020
+    NoQuotes,                // 10021
+    InvalidExpiration,       // 10022
+    OrderChanged,            // 10023
+    TooManyRequests,         // 10024
+    NoChanges,               // 10025
+    ServerAutoTradingOff,    // 10026
+    ClientAutoTradingOff,    // 10027
+    Locked,                  // 10028
+    Frozen,                  // 10029
+    InvalidFill,             // 10030
+    NoConnection,            // 10031
+    OnlyReal,                // 10032
+    PendingOrdersLimit,      // 10033
+    VolumeLimit,             // 10034
+    InvalidOrderType,        // 10035
+    PositionClosed,          // 10036
+    InvalidCloseVolume,      // 10038
+    CloseOrderExists,        // 10039
+    PositionsLimit,          // 10040
+    ActivationRejected,      // 10041
+    LongOnly,                // 10042
+    ShortOnly,               // 10043
+    CloseOnly,               // 10044
+    FifoClose,               // 10045
+    HedgeProhibited,         // 10046
+    /// Any other non-zero code (terminal runtime errors such as 4756)
+    Other(u32),
+}
+
+impl TradeError {
+    /// Maps a retcode to an error; `None` for 0 and the success codes
+    /// 10008 (placed) and 10009 (done).
+    pub fn from_code(code: u32) -> Option<Self> {
+        use TradeError::*;
+        Some(match code {
+            0 | 10008 | 10009 => return None,
+            10004 => Requote,
+            10006 => Rejected,
+            10007 => Canceled,
+            10010 => PartiallyFilled,
+            10011 => RequestError,
+            10012 => Timeout,
+            10013 => InvalidRequest,
+            10014 => InvalidVolume,
+            10015 => InvalidPrice,
+            10016 => InvalidStops,
+            10017 => TradeDisabled,
+            10018 => MarketClosed,
+            10019 => NoMoney,
+            10020 => PriceChanged,
+            10021 => NoQuotes,
+            10022 => InvalidExpiration,
+            10023 => OrderChanged,
+            10024 => TooManyRequests,
+            10025 => NoChanges,
+            10026 => ServerAutoTradingOff,
+            10027 => ClientAutoTradingOff,
+            10028 => Locked,
+            10029 => Frozen,
+            10030 => InvalidFill,
+            10031 => NoConnection,
+            10032 => OnlyReal,
+            10033 => PendingOrdersLimit,
+            10034 => VolumeLimit,
+            10035 => InvalidOrderType,
+            10036 => PositionClosed,
+            10038 => InvalidCloseVolume,
+            10039 => CloseOrderExists,
+            10040 => PositionsLimit,
+            10041 => ActivationRejected,
+            10042 => LongOnly,
+            10043 => ShortOnly,
+            10044 => CloseOnly,
+            10045 => FifoClose,
+            10046 => HedgeProhibited,
+            other => Other(other),
+        })
+    }
+
+    /// Recovers the code from legacy EA messages like "Error 10019: Not enough money".
+    pub fn from_message(message: &str) -> Option<Self> {
+        let rest = message.strip_prefix("Error ")?;
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        Self::from_code(digits.parse().ok()?)
+    }
+
+    pub fn code(&self) -> u32 {
+        use TradeError::*;
+        match self {
+            Requote => 10004,
+            Rejected => 10006,
+            Canceled => 10007,
+            PartiallyFilled => 10010,
+            RequestError => 10011,
+            Timeout => 10012,
+            InvalidRequest => 10013,
+            InvalidVolume => 10014,
+            InvalidPrice => 10015,
+            InvalidStops => 10016,
+            TradeDisabled => 10017,
+            MarketClosed => 10018,
+            NoMoney => 10019,
+            PriceChanged => 10020,
+            NoQuotes => 10021,
+            InvalidExpiration => 10022,
+            OrderChanged => 10023,
+            TooManyRequests => 10024,
+            NoChanges => 10025,
+            ServerAutoTradingOff => 10026,
+            ClientAutoTradingOff => 10027,
+            Locked => 10028,
+            Frozen => 10029,
+            InvalidFill => 10030,
+            NoConnection => 10031,
+            OnlyReal => 10032,
+            PendingOrdersLimit => 10033,
+            VolumeLimit => 10034,
+            InvalidOrderType => 10035,
+            PositionClosed => 10036,
+            InvalidCloseVolume => 10038,
+            CloseOrderExists => 10039,
+            PositionsLimit => 10040,
+            ActivationRejected => 10041,
+            LongOnly => 10042,
+            ShortOnly => 10043,
+            CloseOnly => 10044,
+            FifoClose => 10045,
+            HedgeProhibited => 10046,
+            Other(code) => *code,
+        }
+    }
+
+    /// Transient conditions where sending the same request again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        use TradeError::*;
+        matches!(
+            self,
+            Requote | Timeout | PriceChanged | NoQuotes | OrderChanged | TooManyRequests | Locked | NoConnection
+        )
+    }
+
+    pub fn description(&self) -> &'static str {
+        use TradeError::*;
+        match self {
+            Requote => "Requote",
+            Rejected => "Request rejected",
+            Canceled => "Request canceled by trader",
+            PartiallyFilled => "Only part of the request was completed",
+            RequestError => "Request processing error",
+            Timeout => "Request canceled by timeout",
+            InvalidRequest => "Invalid request",
+            InvalidVolume => "Invalid volume",
+            InvalidPrice => "Invalid price",
+            InvalidStops => "Invalid stops",
+            TradeDisabled => "Trade is disabled",
+            MarketClosed => "Market is closed",
+            NoMoney => "Not enough money",
+            PriceChanged => "Prices changed",
+            NoQuotes => "No quotes to process the request",
+            InvalidExpiration => "Invalid order expiration date",
+            OrderChanged => "Order state changed",
+            TooManyRequests => "Too many requests",
+            NoChanges => "No changes in request",
+            ServerAutoTradingOff => "Autotrading disabled by server",
+            ClientAutoTradingOff => "Autotrading disabled by client terminal",
+            Locked => "Request locked for processing",
+            Frozen => "Order or position frozen",
+            InvalidFill => "Invalid order filling type",
+            NoConnection => "No connection with the trade server",
+            OnlyReal => "Operation allowed only for live accounts",
+            PendingOrdersLimit => "Pending orders limit reached",
+            VolumeLimit => "Volume limit for the symbol reached",
+            InvalidOrderType => "Incorrect or prohibited order type",
+            PositionClosed => "Position already closed",
+            InvalidCloseVolume => "Close volume exceeds position volume",
+            CloseOrderExists => "A close order already exists for this position",
+            PositionsLimit => "Open positions limit reached",
+            ActivationRejected => "Pending order activation rejected",
+            LongOnly => "Only long positions are allowed",
+            ShortOnly => "Only short positions are allowed",
+            CloseOnly => "Only position closing is allowed",
+            FifoClose => "Positions must be closed in FIFO order",
+            HedgeProhibited => "Opposite positions are prohibited",
+            Other(_) => "Terminal error",
+        }
+    }
+
+    /// What the user can do about it, for display next to the error.
+    pub fn suggestion(&self) -> &'static str {
+        use TradeError::*;
+        match self {
+            Requote | PriceChanged | NoQuotes => "Price moved; send the order again at the new price.",
+            Timeout | NoConnection => "Check the terminal's connection, then retry.",
+            TooManyRequests | Locked | OrderChanged => "Wait a moment and retry.",
+            MarketClosed => "Wait for the trading session to open.",
+            NoMoney => "Reduce the lot size or free up margin.",
+            InvalidVolume | VolumeLimit | InvalidCloseVolume => "Adjust the lot size to min/max/step limits.",
+            InvalidPrice => "Check the pending price against the current bid/ask.",
+            InvalidStops | Frozen => "Move SL/TP or the price further from the market.",
+            TradeDisabled | OnlyReal | InvalidOrderType | LongOnly | ShortOnly | CloseOnly => {
+                "This symbol or account does not allow the operation."
+            }
+            ServerAutoTradingOff => "Ask the broker to enable automated trading.",
+            ClientAutoTradingOff => "Enable Algo Trading in the MT5 toolbar.",
+            InvalidFill => "Change the filling mode (IOC/FOK/Return) in the EA.",
+            PendingOrdersLimit | PositionsLimit => "Close or cancel existing trades first.",
+            PositionClosed | CloseOrderExists => "Refresh positions; nothing more to do.",
+            FifoClose => "Close the oldest position first.",
+            HedgeProhibited => "Close the opposite position instead of opening a new one.",
+            InvalidExpiration => "Use a valid expiration or GTC.",
+            NoChanges => "The new values equal the current ones.",
+            Rejected | Canceled | PartiallyFilled | RequestError | InvalidRequest | ActivationRejected
+            | Other(_) => "See the MT5 Experts/Journal log for details.",
+        }
+    }
+}
+
+impl fmt::Display for TradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.description(), self.code())
+    }
+}
+
+impl std::error::Error for TradeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_codes_round_trip() {
+        for code in (10004..=10046).filter(|c| ![10005, 10008, 10009, 10037].contains(c)) {
+            let error = TradeError::from_code(code).unwrap();
+            assert!(!matches!(error, TradeError::Other(_)), "{} has no variant", code);
+            assert_eq!(error.code(), code);
+        }
+    }
+
+    #[test]
+    fn success_and_unknown_codes() {
+        assert_eq!(TradeError::from_code(0), None);
+        assert_eq!(TradeError::from_code(10008), None);
+        assert_eq!(TradeError::from_code(10009), None);
+        assert_eq!(TradeError::from_code(10005), Some(TradeError::Other(10005)));
+        assert_eq!(TradeError::from_code(4756).map(|e| e.code()), Some(4756));
+    }
+
+    #[test]
+    fn parses_legacy_messages() {
+        assert_eq!(TradeError::from_message("Error 10019: Not enough money"), Some(TradeError::NoMoney));
+        assert_eq!(TradeError::from_message("Error 10009: Done"), None);
+        assert_eq!(TradeError::from_message("Not enough money"), None);
+        assert_eq!(TradeError::from_message("Error : no code"), None);
+    }
+}
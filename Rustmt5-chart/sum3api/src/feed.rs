@@ -0,0 +1,154 @@
+//+------------------------------------------------------------------+
+//|                                                          feed.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::TickData;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
+use zeromq::{Socket, SocketRecv};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// Feed Status
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeedState {
+    /// Socket is up and ticks are arriving
+    Connected,
+    /// Socket is up but no tick within `stale_after`
+    Stale,
+    /// Socket is down, waiting to reconnect
+    Disconnected,
+}
+
+#[derive(Clone, Debug)]
+pub struct FeedStatus {
+    pub state: FeedState,
+    pub last_tick: Option<Instant>,
+    pub reconnects: u32,
+    pub last_error: Option<String>,
+}
+
+impl Default for FeedStatus {
+    fn default() -> Self {
+        Self {
+            state: FeedState::Disconnected,
+            last_tick: None,
+            reconnects: 0,
+            last_error: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FeedSettings {
+    /// Silence after which the feed is reported as Stale
+    pub stale_after: Duration,
+    /// Silence after which the socket is torn down and reconnected
+    pub reconnect_after: Duration,
+}
+
+// ============================================================================
+// Supervised Tick Subscriber
+// ============================================================================
+
+/// Runs until aborted. Reconnects with exponential backoff whenever the
+/// socket fails or the feed stays silent too long. Ticks are broadcast to
+/// every subscriber; having none is not an error.
+pub(crate) async fn run_tick_subscriber(
+    address: String,
+    settings: FeedSettings,
+    tick_tx: broadcast::Sender<TickData>,
+    status_tx: watch::Sender<FeedStatus>,
+) {
+    let mut backoff = INITIAL_BACKOFF;
+
+    loop {
+        let mut socket = zeromq::SubSocket::new();
+        if let Err(e) = socket.connect(&address).await {
+            eprintln!("Failed to connect to ZMQ tick publisher at {}: {}", address, e);
+            set_disconnected(&status_tx, format!("Connect failed: {}", e));
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            continue;
+        }
+        if let Err(e) = socket.subscribe("").await {
+            eprintln!("Failed to subscribe to ZMQ tick publisher: {}", e);
+            set_disconnected(&status_tx, format!("Subscribe failed: {}", e));
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            continue;
+        }
+
+        println!("Connected to ZMQ Tick Publisher at {}", address);
+        backoff = INITIAL_BACKOFF;
+        let connected_at = Instant::now();
+        let mut last_tick: Option<Instant> = None;
+        status_tx.send_modify(|s| {
+            s.state = FeedState::Connected;
+            s.last_error = None;
+        });
+
+        let reason = loop {
+            match tokio::time::timeout(settings.stale_after, socket.recv()).await {
+                Ok(Ok(msg)) => {
+                    let Some(tick) = parse_tick(msg.get(0).map(|b| b.as_ref())) else {
+                        continue;
+                    };
+                    let _ = tick_tx.send(tick);
+                    let now = Instant::now();
+                    last_tick = Some(now);
+                    status_tx.send_modify(|s| {
+                        s.state = FeedState::Connected;
+                        s.last_tick = Some(now);
+                    });
+                }
+                Ok(Err(e)) => {
+                    eprintln!("ZMQ Tick Recv Error: {}", e);
+                    break format!("Recv failed: {}", e);
+                }
+                Err(_) => {
+                    // Nothing received within stale_after
+                    let silent_for = last_tick.unwrap_or(connected_at).elapsed();
+                    if silent_for >= settings.reconnect_after {
+                        eprintln!("No ticks for {:.0}s, reconnecting", silent_for.as_secs_f64());
+                        break format!("No ticks for {:.0}s", silent_for.as_secs_f64());
+                    }
+                    status_tx.send_if_modified(|s| {
+                        let changed = s.state != FeedState::Stale;
+                        s.state = FeedState::Stale;
+                        changed
+                    });
+                }
+            }
+        };
+
+        set_disconnected(&status_tx, reason);
+        status_tx.send_modify(|s| s.reconnects += 1);
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+fn set_disconnected(status_tx: &watch::Sender<FeedStatus>, error: String) {
+    status_tx.send_modify(|s| {
+        s.state = FeedState::Disconnected;
+        s.last_error = Some(error);
+    });
+}
+
+fn parse_tick(payload: Option<&[u8]>) -> Option<TickData> {
+    let json_str = std::str::from_utf8(payload?).ok()?;
+    match serde_json::from_str::<TickData>(json_str) {
+        Ok(tick) => Some(tick),
+        Err(e) => {
+            eprintln!("JSON Parse Error: {}. Msg: {}", e, json_str);
+            None
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//+------------------------------------------------------------------+
+//|                                                       history.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::bars::Bar;
+use crate::Timeframe;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use std::io;
+use std::path::Path;
+
+// ============================================================================
+// Time Formats
+// ============================================================================
+
+/// Parses the time column of the CSV files the GUI writes: MT5 strings
+/// ("2026.01.25 23:01:57", "2026.01.25 23:00", "2026.01.25") or the epoch
+/// integers of Live recordings. MT5 server time is treated as UTC, the same
+/// way the EA encodes `tick.time`.
+pub fn parse_time(text: &str) -> Option<i64> {
+    let text = text.trim();
+    if let Ok(epoch) = text.parse::<i64>() {
+        return Some(epoch);
+    }
+    for format in ["%Y.%m.%d %H:%M:%S", "%Y.%m.%d %H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(dt.and_utc().timestamp());
+        }
+    }
+    NaiveDate::parse_from_str(text, "%Y.%m.%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp())
+}
+
+/// Formats an epoch like MT5's `TimeToString`, with or without seconds.
+pub fn format_time(epoch: i64, with_seconds: bool) -> String {
+    let format = if with_seconds { "%Y.%m.%d %H:%M:%S" } else { "%Y.%m.%d %H:%M" };
+    DateTime::from_timestamp(epoch, 0)
+        .map(|dt| dt.format(format).to_string())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Tick Files (Live_*.csv, History_*_TICKS_*.csv)
+// ============================================================================
+
+/// One row of a `Time,Bid,Ask,Volume` file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TickRow {
+    pub time: i64,
+    pub bid: f64,
+    pub ask: f64,
+    pub volume: u64,
+}
+
+/// Reads a tick CSV. Rows that fail to parse are skipped.
+pub fn read_ticks(path: &Path) -> io::Result<Vec<TickRow>> {
+    match read_csv(path)? {
+        CsvData::Ticks(ticks) => Ok(ticks),
+        CsvData::Bars(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: expected a Time,Bid,Ask,Volume file, got OHLC bars", path.display()),
+        )),
+    }
+}
+
+fn parse_tick_row(line: &str) -> Option<TickRow> {
+    let mut cols = line.split(',');
+    Some(TickRow {
+        time: parse_time(cols.next()?)?,
+        bid: cols.next()?.trim().parse().ok()?,
+        ask: cols.next()?.trim().parse().ok()?,
+        volume: cols.next().and_then(|v| v.trim().parse().ok()).unwrap_or(0),
+    })
+}
+
+// ============================================================================
+// OHLC Files (History_*_OHLC_*.csv)
+// ============================================================================
+
+/// Contents of any CSV in the output folder, told apart by its header.
+#[derive(Clone, Debug)]
+pub enum CsvData {
+    Ticks(Vec<TickRow>),
+    Bars(Vec<Bar>),
+}
+
+/// Reads a tick (`Time,Bid,Ask,Volume`) or OHLC
+/// (`Time,Open,High,Low,Close,TickVol,Spread`) CSV.
+pub fn read_csv(path: &Path) -> io::Result<CsvData> {
+    let text = std::fs::read_to_string(path)?;
+    let mut lines = text.lines();
+    let header = lines.next().unwrap_or_default().trim();
+
+    if header.starts_with("Time,Bid,Ask") {
+        Ok(CsvData::Ticks(lines.filter_map(parse_tick_row).collect()))
+    } else if header.starts_with("Time,Open,High,Low,Close") {
+        let rows: Vec<&str> = lines.collect();
+        // Spread is in points; the EA writes prices with the symbol's digits
+        let digits = rows.iter().take(100).filter_map(|l| l.split(',').nth(1)).map(decimals).max().unwrap_or(0);
+        let point = 10f64.powi(-(digits as i32));
+        Ok(CsvData::Bars(rows.into_iter().filter_map(|l| parse_bar_row(l, point)).collect()))
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: unrecognised CSV header \"{}\"", path.display(), header),
+        ))
+    }
+}
+
+fn parse_bar_row(line: &str, point: f64) -> Option<Bar> {
+    let mut cols = line.split(',');
+    let time = parse_time(cols.next()?)?;
+    let mut price = || cols.next().and_then(|v| v.trim().parse::<f64>().ok());
+    let (open, high, low, close) = (price()?, price()?, price()?, price()?);
+    Some(Bar {
+        time,
+        open,
+        high,
+        low,
+        close,
+        tick_volume: cols.next().and_then(|v| v.trim().parse().ok()).unwrap_or(0),
+        spread: cols.next().and_then(|v| v.trim().parse::<f64>().ok()).unwrap_or(0.0) * point,
+    })
+}
+
+fn decimals(number: &str) -> usize {
+    number.trim().split_once('.').map_or(0, |(_, frac)| frac.len())
+}
+
+// ============================================================================
+// File Names
+// ============================================================================
+
+/// Symbol embedded in an output file name, e.g. "XAUUSDc" from
+/// `Live_XAUUSDc_ID0001_20260126_135811.csv`.
+pub fn symbol_from_file_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let mut parts = stem.split('_');
+    match parts.next()? {
+        "Live" | "History" => parts.next().map(str::to_string),
+        _ => None,
+    }
+}
+
+/// Bar timeframe of a `History_<symbol>_<tf>_OHLC_...` file name.
+pub fn timeframe_from_file_name(path: &Path) -> Option<Timeframe> {
+    let stem = path.file_stem()?.to_str()?;
+    let mut parts = stem.strip_prefix("History_")?.split('_');
+    parts.next()?;
+    Timeframe::parse(parts.next()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn csv_file(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("sum3api-{}-{}.csv", name, std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn parses_mt5_strings_and_epochs() {
+        assert_eq!(parse_time("2026.01.25 23:01:57"), Some(1769382117));
+        assert_eq!(parse_time(" 1769382117 "), Some(1769382117));
+        assert_eq!(parse_time("2026.01.25 23:00"), Some(1769382000));
+        assert_eq!(parse_time("2026.01.25"), Some(1769299200));
+
+        for bad in ["", "Time", "2026-01-25 23:01:57", "2026.13.01", "25.01.2026"] {
+            assert_eq!(parse_time(bad), None, "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn format_time_matches_parse_time() {
+        assert_eq!(format_time(1769382117, true), "2026.01.25 23:01:57");
+        assert_eq!(format_time(1769382117, false), "2026.01.25 23:01");
+        assert_eq!(parse_time(&format_time(1769382117, true)), Some(1769382117));
+    }
+
+    #[test]
+    fn reads_tick_file_skipping_bad_rows() {
+        let path = csv_file(
+            "ticks",
+            "Time,Bid,Ask,Volume\n\
+             2026.01.25 23:01:57,2000.10,2000.35,3\n\
+             garbage\n\
+             1769382118,2000.20,2000.45\n\
+             2026.01.25 23:01:59,not-a-price,2000.50,1\n",
+        );
+        let CsvData::Ticks(ticks) = read_csv(&path).unwrap() else { panic!("expected ticks") };
+        assert_eq!(
+            ticks,
+            vec![
+                TickRow { time: 1769382117, bid: 2000.10, ask: 2000.35, volume: 3 },
+                // Missing volume column reads as 0
+                TickRow { time: 1769382118, bid: 2000.20, ask: 2000.45, volume: 0 },
+            ]
+        );
+        assert_eq!(read_ticks(&path).unwrap().len(), 2);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn reads_ohlc_file_with_spread_in_points() {
+        let path = csv_file(
+            "ohlc",
+            "Time,Open,High,Low,Close,TickVol,Spread\n\
+             2026.01.25 23:00,2000.10,2001.50,1999.80,2001.00,120,25\n\
+             2026.01.25 23:01,2001.000,2001.200,2000.900,2001.100,80,30\n\
+             2026.01.25 23:02,bad,2001.2,2000.9,2001.1,80,30\n",
+        );
+        let CsvData::Bars(bars) = read_csv(&path).unwrap() else { panic!("expected bars") };
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].time, 1769382000);
+        assert_eq!((bars[0].open, bars[0].high, bars[0].low, bars[0].close), (2000.10, 2001.50, 1999.80, 2001.00));
+        assert_eq!(bars[0].tick_volume, 120);
+        // Most decimals in the Open column is 3, so one point is 0.001
+        assert!((bars[0].spread - 0.025).abs() < 1e-12);
+        assert!((bars[1].spread - 0.030).abs() < 1e-12);
+
+        let err = read_ticks(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_header() {
+        let path = csv_file("unknown", "Date,Price\n2026.01.25,2000.0\n");
+        assert_eq!(read_csv(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn reads_symbol_and_timeframe_from_file_names() {
+        let live = Path::new("out/Live_XAUUSDc_ID0001_20260126_135811.csv");
+        assert_eq!(symbol_from_file_name(live).as_deref(), Some("XAUUSDc"));
+        assert_eq!(timeframe_from_file_name(live), None);
+
+        let history = Path::new("History_EURUSD_H1_OHLC_20260101_20260131.csv");
+        assert_eq!(symbol_from_file_name(history).as_deref(), Some("EURUSD"));
+        assert_eq!(timeframe_from_file_name(history), Some(Timeframe::H1));
+
+        assert_eq!(symbol_from_file_name(Path::new("notes.csv")), None);
+    }
+}
@@ -0,0 +1,786 @@
+//+------------------------------------------------------------------+
+//|                                                    indicators.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+//! Streaming technical indicators. Each one takes a value (or a bar) at a
+//! time and returns its latest output in O(1) or O(period), so the same
+//! code runs per tick in a strategy, per bar in a backtest, and behind
+//! the chart overlays.
+//!
+//! ```
+//! use sum3api::indicators::Ema;
+//!
+//! let mut ema = Ema::new(3);
+//! let outputs: Vec<Option<f64>> = [1.0, 2.0, 3.0, 4.0].iter().map(|p| ema.update(*p)).collect();
+//! assert_eq!(outputs, [None, None, Some(2.0), Some(3.0)]);
+//! ```
+
+use crate::bars::Bar;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Running sums are recomputed from their window this often, so rounding
+/// errors cannot pile up over millions of ticks.
+const RESUM_EVERY: u64 = 4096;
+
+/// Most outputs one indicator has (MACD line, signal, histogram).
+pub const MAX_OUTPUTS: usize = 3;
+
+/// Seconds per VWAP session.
+const SECONDS_PER_DAY: i64 = 86_400;
+
+// ============================================================================
+// Moving Averages
+// ============================================================================
+
+/// Simple moving average over the last `period` values.
+#[derive(Clone, Debug)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+    updates: u64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self { period, window: VecDeque::with_capacity(period + 1), sum: 0.0, updates: 0 }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Adds a value; `None` until `period` values have been seen.
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        self.sum += value;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or(0.0);
+        }
+        self.updates += 1;
+        if self.updates.is_multiple_of(RESUM_EVERY) {
+            self.sum = self.window.iter().sum();
+        }
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+}
+
+/// Exponential moving average, seeded with the SMA of the first `period`
+/// values like MT5's iMA.
+#[derive(Clone, Debug)]
+pub struct Ema {
+    alpha: f64,
+    seed: Sma,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self { alpha: 2.0 / (period as f64 + 1.0), seed: Sma::new(period), value: None }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(previous) => Some(previous + self.alpha * (value - previous)),
+            None => self.seed.update(value),
+        };
+        self.value
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+/// Linearly weighted moving average: the newest value weighs `period`,
+/// the oldest 1.
+#[derive(Clone, Debug)]
+pub struct Wma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+    weighted: f64,
+    updates: u64,
+}
+
+impl Wma {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self { period, window: VecDeque::with_capacity(period + 1), sum: 0.0, weighted: 0.0, updates: 0 }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        if self.window.len() == self.period {
+            // Every value moves down one weight; the oldest drops to zero
+            self.weighted += self.period as f64 * value - self.sum;
+            self.sum -= self.window.pop_front().unwrap_or(0.0);
+        } else {
+            self.weighted += (self.window.len() + 1) as f64 * value;
+        }
+        self.window.push_back(value);
+        self.sum += value;
+        self.updates += 1;
+        if self.updates.is_multiple_of(RESUM_EVERY) {
+            self.sum = self.window.iter().sum();
+            self.weighted = self.window.iter().enumerate().map(|(i, v)| (i + 1) as f64 * v).sum();
+        }
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        let weights = (self.period * (self.period + 1)) as f64 / 2.0;
+        (self.window.len() == self.period).then(|| self.weighted / weights)
+    }
+}
+
+// ============================================================================
+// Bands & Volatility
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BollingerValue {
+    pub middle: f64,
+    pub upper: f64,
+    pub lower: f64,
+}
+
+/// SMA with bands `deviations` standard deviations (population, as in
+/// MT5) above and below.
+#[derive(Clone, Debug)]
+pub struct Bollinger {
+    sma: Sma,
+    deviations: f64,
+    value: Option<BollingerValue>,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, deviations: f64) -> Self {
+        Self { sma: Sma::new(period), deviations, value: None }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<BollingerValue> {
+        self.value = self.sma.update(value).map(|middle| {
+            let variance =
+                self.sma.window.iter().map(|v| (v - middle).powi(2)).sum::<f64>() / self.sma.period as f64;
+            let width = self.deviations * variance.sqrt();
+            BollingerValue { middle, upper: middle + width, lower: middle - width }
+        });
+        self.value
+    }
+
+    pub fn value(&self) -> Option<BollingerValue> {
+        self.value
+    }
+}
+
+/// Average true range with Wilder's smoothing.
+#[derive(Clone, Debug)]
+pub struct Atr {
+    period: usize,
+    seed: Sma,
+    previous_close: Option<f64>,
+    value: Option<f64>,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self { period, seed: Sma::new(period), previous_close: None, value: None }
+    }
+
+    pub fn update(&mut self, bar: &Bar) -> Option<f64> {
+        let range = match self.previous_close {
+            Some(close) => bar.high.max(close) - bar.low.min(close),
+            None => bar.high - bar.low,
+        };
+        self.previous_close = Some(bar.close);
+        self.value = match self.value {
+            Some(atr) => Some((atr * (self.period - 1) as f64 + range) / self.period as f64),
+            None => self.seed.update(range),
+        };
+        self.value
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+// ============================================================================
+// Oscillators
+// ============================================================================
+
+/// Relative strength index, 0..100, with Wilder's smoothing.
+#[derive(Clone, Debug)]
+pub struct Rsi {
+    period: usize,
+    previous: Option<f64>,
+    gains: Sma,
+    losses: Sma,
+    average: Option<(f64, f64)>,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self { period, previous: None, gains: Sma::new(period), losses: Sma::new(period), average: None }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        let previous = self.previous.replace(value)?;
+        let change = value - previous;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        let n = self.period as f64;
+        self.average = match self.average {
+            Some((gains, losses)) => Some(((gains * (n - 1.0) + gain) / n, (losses * (n - 1.0) + loss) / n)),
+            None => self.gains.update(gain).zip(self.losses.update(loss)),
+        };
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.average.map(|(gains, losses)| match (gains > 0.0, losses > 0.0) {
+            (_, true) => 100.0 - 100.0 / (1.0 + gains / losses),
+            (true, false) => 100.0,
+            (false, false) => 50.0,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MacdValue {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+/// Fast EMA minus slow EMA, with an EMA of that as the signal line.
+#[derive(Clone, Debug)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+    value: Option<MacdValue>,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self { fast: Ema::new(fast), slow: Ema::new(slow), signal: Ema::new(signal), value: None }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<MacdValue> {
+        let (fast, slow) = (self.fast.update(value), self.slow.update(value));
+        let macd = fast.zip(slow).map(|(f, s)| f - s)?;
+        self.value = self.signal.update(macd).map(|signal| MacdValue { macd, signal, histogram: macd - signal });
+        self.value
+    }
+
+    pub fn value(&self) -> Option<MacdValue> {
+        self.value
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StochasticValue {
+    pub k: f64,
+    pub d: f64,
+}
+
+/// Slow stochastic, 0..100: where the close sits in the `k_period` range,
+/// smoothed over `slowing` bars (%K), and its SMA over `d_period` (%D).
+#[derive(Clone, Debug)]
+pub struct Stochastic {
+    k_period: usize,
+    ranges: VecDeque<(f64, f64)>,
+    slowing: Sma,
+    d: Sma,
+    value: Option<StochasticValue>,
+}
+
+impl Stochastic {
+    pub fn new(k_period: usize, d_period: usize, slowing: usize) -> Self {
+        let k_period = k_period.max(1);
+        Self {
+            k_period,
+            ranges: VecDeque::with_capacity(k_period + 1),
+            slowing: Sma::new(slowing),
+            d: Sma::new(d_period),
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, bar: &Bar) -> Option<StochasticValue> {
+        self.ranges.push_back((bar.high, bar.low));
+        if self.ranges.len() > self.k_period {
+            self.ranges.pop_front();
+        }
+        if self.ranges.len() < self.k_period {
+            return None;
+        }
+        let high = self.ranges.iter().map(|r| r.0).fold(f64::NEG_INFINITY, f64::max);
+        let low = self.ranges.iter().map(|r| r.1).fold(f64::INFINITY, f64::min);
+        let raw = if high > low { 100.0 * (bar.close - low) / (high - low) } else { 50.0 };
+        let k = self.slowing.update(raw)?;
+        self.value = self.d.update(k).map(|d| StochasticValue { k, d });
+        self.value
+    }
+
+    pub fn value(&self) -> Option<StochasticValue> {
+        self.value
+    }
+}
+
+// ============================================================================
+// Volume
+// ============================================================================
+
+/// Volume-weighted average of the typical price (high + low + close) / 3,
+/// starting over each day. Ticks without volume count as 1.
+#[derive(Clone, Debug, Default)]
+pub struct Vwap {
+    day: Option<i64>,
+    weighted: f64,
+    volume: f64,
+}
+
+impl Vwap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, bar: &Bar) -> Option<f64> {
+        let day = bar.time.div_euclid(SECONDS_PER_DAY);
+        if self.day != Some(day) {
+            *self = Self { day: Some(day), ..Self::default() };
+        }
+        let volume = bar.tick_volume.max(1) as f64;
+        self.weighted += (bar.high + bar.low + bar.close) / 3.0 * volume;
+        self.volume += volume;
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        (self.volume > 0.0).then(|| self.weighted / self.volume)
+    }
+}
+
+// ============================================================================
+// Any Indicator
+// ============================================================================
+
+/// An indicator and its parameters, as saved and edited in the chart.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IndicatorSpec {
+    Sma { period: usize },
+    Ema { period: usize },
+    Wma { period: usize },
+    Bollinger { period: usize, deviations: f64 },
+    Atr { period: usize },
+    Rsi { period: usize },
+    Macd { fast: usize, slow: usize, signal: usize },
+    Stochastic { k_period: usize, d_period: usize, slowing: usize },
+    Vwap,
+}
+
+impl IndicatorSpec {
+    /// Every kind with its usual parameters.
+    pub const DEFAULTS: [IndicatorSpec; 9] = [
+        IndicatorSpec::Sma { period: 20 },
+        IndicatorSpec::Ema { period: 20 },
+        IndicatorSpec::Wma { period: 20 },
+        IndicatorSpec::Bollinger { period: 20, deviations: 2.0 },
+        IndicatorSpec::Atr { period: 14 },
+        IndicatorSpec::Rsi { period: 14 },
+        IndicatorSpec::Macd { fast: 12, slow: 26, signal: 9 },
+        IndicatorSpec::Stochastic { k_period: 14, d_period: 3, slowing: 3 },
+        IndicatorSpec::Vwap,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IndicatorSpec::Sma { .. } => "SMA",
+            IndicatorSpec::Ema { .. } => "EMA",
+            IndicatorSpec::Wma { .. } => "WMA",
+            IndicatorSpec::Bollinger { .. } => "Bollinger",
+            IndicatorSpec::Atr { .. } => "ATR",
+            IndicatorSpec::Rsi { .. } => "RSI",
+            IndicatorSpec::Macd { .. } => "MACD",
+            IndicatorSpec::Stochastic { .. } => "Stochastic",
+            IndicatorSpec::Vwap => "VWAP",
+        }
+    }
+
+    /// Name with parameters, e.g. "MACD(12,26,9)".
+    pub fn label(&self) -> String {
+        match *self {
+            IndicatorSpec::Sma { period }
+            | IndicatorSpec::Ema { period }
+            | IndicatorSpec::Wma { period }
+            | IndicatorSpec::Atr { period }
+            | IndicatorSpec::Rsi { period } => format!("{}({})", self.name(), period),
+            IndicatorSpec::Bollinger { period, deviations } => format!("{}({},{})", self.name(), period, deviations),
+            IndicatorSpec::Macd { fast, slow, signal } => format!("{}({},{},{})", self.name(), fast, slow, signal),
+            IndicatorSpec::Stochastic { k_period, d_period, slowing } => {
+                format!("{}({},{},{})", self.name(), k_period, d_period, slowing)
+            }
+            IndicatorSpec::Vwap => self.name().to_string(),
+        }
+    }
+
+    /// Names of the outputs, in [`Outputs`] order.
+    pub fn outputs(&self) -> &'static [&'static str] {
+        match self {
+            IndicatorSpec::Bollinger { .. } => &["Middle", "Upper", "Lower"],
+            IndicatorSpec::Macd { .. } => &["MACD", "Signal", "Histogram"],
+            IndicatorSpec::Stochastic { .. } => &["%K", "%D"],
+            _ => &["Value"],
+        }
+    }
+
+    /// Drawn on the price scale rather than in a panel of its own.
+    pub fn is_overlay(&self) -> bool {
+        matches!(
+            self,
+            IndicatorSpec::Sma { .. }
+                | IndicatorSpec::Ema { .. }
+                | IndicatorSpec::Wma { .. }
+                | IndicatorSpec::Bollinger { .. }
+                | IndicatorSpec::Vwap
+        )
+    }
+
+    /// Overbought/oversold guide lines.
+    pub fn levels(&self) -> &'static [f64] {
+        match self {
+            IndicatorSpec::Rsi { .. } => &[30.0, 70.0],
+            IndicatorSpec::Stochastic { .. } => &[20.0, 80.0],
+            _ => &[],
+        }
+    }
+
+    pub fn build(&self) -> Indicator {
+        match *self {
+            IndicatorSpec::Sma { period } => Indicator::Sma(Sma::new(period)),
+            IndicatorSpec::Ema { period } => Indicator::Ema(Ema::new(period)),
+            IndicatorSpec::Wma { period } => Indicator::Wma(Wma::new(period)),
+            IndicatorSpec::Bollinger { period, deviations } => Indicator::Bollinger(Bollinger::new(period, deviations)),
+            IndicatorSpec::Atr { period } => Indicator::Atr(Atr::new(period)),
+            IndicatorSpec::Rsi { period } => Indicator::Rsi(Rsi::new(period)),
+            IndicatorSpec::Macd { fast, slow, signal } => Indicator::Macd(Macd::new(fast, slow, signal)),
+            IndicatorSpec::Stochastic { k_period, d_period, slowing } => {
+                Indicator::Stochastic(Stochastic::new(k_period, d_period, slowing))
+            }
+            IndicatorSpec::Vwap => Indicator::Vwap(Vwap::new()),
+        }
+    }
+}
+
+/// One update's outputs, in [`IndicatorSpec::outputs`] order; unused
+/// slots are NaN.
+pub type Outputs = [f64; MAX_OUTPUTS];
+
+/// Any of the indicators behind one interface, fed whole bars. Price
+/// indicators read the close.
+#[derive(Clone, Debug)]
+pub enum Indicator {
+    Sma(Sma),
+    Ema(Ema),
+    Wma(Wma),
+    Bollinger(Bollinger),
+    Atr(Atr),
+    Rsi(Rsi),
+    Macd(Macd),
+    Stochastic(Stochastic),
+    Vwap(Vwap),
+}
+
+impl Indicator {
+    pub fn update(&mut self, bar: &Bar) -> Option<Outputs> {
+        let single = |value: Option<f64>| value.map(|v| [v, f64::NAN, f64::NAN]);
+        match self {
+            Indicator::Sma(i) => single(i.update(bar.close)),
+            Indicator::Ema(i) => single(i.update(bar.close)),
+            Indicator::Wma(i) => single(i.update(bar.close)),
+            Indicator::Bollinger(i) => i.update(bar.close).map(|v| [v.middle, v.upper, v.lower]),
+            Indicator::Atr(i) => single(i.update(bar)),
+            Indicator::Rsi(i) => single(i.update(bar.close)),
+            Indicator::Macd(i) => i.update(bar.close).map(|v| [v.macd, v.signal, v.histogram]),
+            Indicator::Stochastic(i) => i.update(bar).map(|v| [v.k, v.d, f64::NAN]),
+            Indicator::Vwap(i) => single(i.update(bar)),
+        }
+    }
+}
+
+/// A single price as a bar, for feeding ticks to bar indicators.
+pub fn price_bar(time: i64, price: f64, volume: u64) -> Bar {
+    Bar { time, open: price, high: price, low: price, close: price, tick_volume: volume, spread: 0.0 }
+}
+
+// ============================================================================
+// Indicator Series
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IndicatorPoint {
+    pub time: i64,
+    pub values: Outputs,
+}
+
+/// An indicator with the outputs it produced so far, for drawing.
+#[derive(Clone, Debug)]
+pub struct IndicatorSeries {
+    spec: IndicatorSpec,
+    indicator: Indicator,
+    points: Vec<IndicatorPoint>,
+}
+
+impl IndicatorSeries {
+    pub fn new(spec: IndicatorSpec) -> Self {
+        Self { spec, indicator: spec.build(), points: Vec::new() }
+    }
+
+    pub fn from_bars(spec: IndicatorSpec, bars: &[Bar]) -> Self {
+        let mut series = Self::new(spec);
+        for bar in bars {
+            series.push(bar);
+        }
+        series
+    }
+
+    pub fn spec(&self) -> &IndicatorSpec {
+        &self.spec
+    }
+
+    /// Outputs so far, oldest first; the warm-up bars have none.
+    pub fn points(&self) -> &[IndicatorPoint] {
+        &self.points
+    }
+
+    /// Adds a completed bar.
+    pub fn push(&mut self, bar: &Bar) {
+        if let Some(values) = self.indicator.update(bar) {
+            self.points.push(IndicatorPoint { time: bar.time, values });
+        }
+    }
+
+    /// What the output would be with `bar` (still forming) added, without
+    /// adding it.
+    pub fn preview(&self, bar: &Bar) -> Option<IndicatorPoint> {
+        let values = self.indicator.clone().update(bar)?;
+        Some(IndicatorPoint { time: bar.time, values })
+    }
+
+    /// Drops the oldest points beyond `keep`. The indicator state stays.
+    pub fn trim(&mut self, keep: usize) {
+        let excess = self.points.len().saturating_sub(keep);
+        self.points.drain(..excess);
+    }
+
+    /// Starts over with no data.
+    pub fn clear(&mut self) {
+        *self = Self::new(self.spec);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(time: i64, high: f64, low: f64, close: f64, volume: u64) -> Bar {
+        Bar { time, open: close, high, low, close, tick_volume: volume, spread: 0.0 }
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    fn outputs(values: &[f64], mut update: impl FnMut(f64) -> Option<f64>) -> Vec<Option<f64>> {
+        values.iter().map(|v| update(*v)).collect()
+    }
+
+    // Unused output slots are NaN, which never compares equal
+    fn same(a: &[IndicatorPoint], b: &[IndicatorPoint]) -> bool {
+        format!("{:?}", a) == format!("{:?}", b)
+    }
+
+    fn assert_series(actual: &[Option<f64>], expected: &[Option<f64>]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            match (a, e) {
+                (Some(a), Some(e)) => assert_close(*a, *e),
+                _ => assert_eq!(a, e),
+            }
+        }
+    }
+
+    #[test]
+    fn sma() {
+        let mut sma = Sma::new(3);
+        let values = outputs(&[1.0, 2.0, 3.0, 4.0, 8.0], |v| sma.update(v));
+        assert_series(&values, &[None, None, Some(2.0), Some(3.0), Some(5.0)]);
+    }
+
+    #[test]
+    fn ema_seeds_with_the_sma() {
+        let mut ema = Ema::new(3);
+        let values = outputs(&[2.0, 4.0, 6.0, 8.0, 2.0], |v| ema.update(v));
+        assert_series(&values, &[None, None, Some(4.0), Some(6.0), Some(4.0)]);
+    }
+
+    #[test]
+    fn wma_weighs_the_newest_most() {
+        let mut wma = Wma::new(3);
+        // (1·1 + 2·2 + 3·3) / 6, then (2·1 + 3·2 + 4·3) / 6 and (3·1 + 4·2 + 0·3) / 6
+        let values = outputs(&[1.0, 2.0, 3.0, 4.0, 0.0], |v| wma.update(v));
+        assert_series(&values, &[None, None, Some(14.0 / 6.0), Some(20.0 / 6.0), Some(11.0 / 6.0)]);
+    }
+
+    #[test]
+    fn bollinger_uses_the_population_deviation() {
+        let mut bands = Bollinger::new(3, 2.0);
+        assert_eq!(bands.update(1.0), None);
+        assert_eq!(bands.update(2.0), None);
+        let first = bands.update(3.0).unwrap();
+        let width = 2.0 * (2.0f64 / 3.0).sqrt();
+        assert_close(first.middle, 2.0);
+        assert_close(first.upper, 2.0 + width);
+        assert_close(first.lower, 2.0 - width);
+        // Window 2, 3, 7: mean 4, variance 14/3
+        let next = bands.update(7.0).unwrap();
+        assert_close(next.middle, 4.0);
+        assert_close(next.upper, 4.0 + 2.0 * (14.0f64 / 3.0).sqrt());
+    }
+
+    #[test]
+    fn atr_counts_gaps_and_smooths_like_wilder() {
+        let mut atr = Atr::new(3);
+        assert_eq!(atr.update(&bar(0, 10.0, 8.0, 9.0, 1)), None);
+        assert_eq!(atr.update(&bar(60, 11.0, 9.0, 10.0, 1)), None);
+        // True ranges 2, 2, 3 seed the average
+        assert_close(atr.update(&bar(120, 12.0, 9.0, 11.0, 1)).unwrap(), 7.0 / 3.0);
+        // Gap up from 11: true range 15 - 11 = 4
+        assert_close(atr.update(&bar(180, 15.0, 13.0, 14.0, 1)).unwrap(), (7.0 / 3.0 * 2.0 + 4.0) / 3.0);
+    }
+
+    #[test]
+    fn rsi() {
+        let mut rsi = Rsi::new(2);
+        // Changes +1, +2: no losses yet
+        let values = outputs(&[1.0, 2.0, 4.0, 3.0, 3.0], |v| rsi.update(v));
+        // Then -1: gains (1.5 + 0) / 2, losses (0 + 1) / 2, RS 1.5
+        assert_series(&values, &[None, None, Some(100.0), Some(60.0), Some(60.0)]);
+
+        let mut flat = Rsi::new(2);
+        assert_eq!(outputs(&[5.0, 5.0, 5.0], |v| flat.update(v)).last(), Some(&Some(50.0)));
+    }
+
+    #[test]
+    fn macd() {
+        let mut macd = Macd::new(2, 3, 2);
+        let values: Vec<Option<MacdValue>> = [1.0, 2.0, 3.0, 4.0, 8.0].iter().map(|v| macd.update(*v)).collect();
+        assert!(values[..3].iter().all(Option::is_none));
+        // EMA(2) 3.5, EMA(3) 3: MACD 0.5 twice seeds the signal
+        let seeded = values[3].unwrap();
+        assert_close(seeded.macd, 0.5);
+        assert_close(seeded.signal, 0.5);
+        assert_close(seeded.histogram, 0.0);
+        // EMA(2) 6.5, EMA(3) 5.5
+        let last = values[4].unwrap();
+        assert_close(last.macd, 1.0);
+        assert_close(last.signal, 0.5 + 2.0 / 3.0 * 0.5);
+        assert_close(last.histogram, 1.0 - last.signal);
+    }
+
+    #[test]
+    fn stochastic() {
+        let mut stochastic = Stochastic::new(3, 2, 1);
+        assert_eq!(stochastic.update(&bar(0, 10.0, 8.0, 9.0, 1)), None);
+        assert_eq!(stochastic.update(&bar(60, 12.0, 9.0, 11.0, 1)), None);
+        // %K 20 (close 8 in 7..12), %D needs two
+        assert_eq!(stochastic.update(&bar(120, 11.0, 7.0, 8.0, 1)), None);
+        let value = stochastic.update(&bar(180, 13.0, 10.0, 12.0, 1)).unwrap();
+        assert_close(value.k, 500.0 / 6.0);
+        assert_close(value.d, (20.0 + 500.0 / 6.0) / 2.0);
+    }
+
+    #[test]
+    fn vwap_restarts_each_day() {
+        let mut vwap = Vwap::new();
+        assert_close(vwap.update(&bar(0, 3.0, 1.0, 2.0, 1)).unwrap(), 2.0);
+        assert_close(vwap.update(&bar(60, 6.0, 3.0, 3.0, 3)).unwrap(), 3.5);
+        // New day; no volume counts as 1
+        assert_close(vwap.update(&bar(SECONDS_PER_DAY, 10.0, 10.0, 10.0, 0)).unwrap(), 10.0);
+    }
+
+    #[test]
+    fn streaming_matches_batch_past_resums() {
+        let (mut sma, mut wma) = (Sma::new(50), Wma::new(50));
+        let prices: Vec<f64> = (0..3 * RESUM_EVERY as usize)
+            .map(|i| 2000.0 + (i as f64 * 0.37).sin() * 15.0 + i as f64 * 0.01)
+            .collect();
+        let weights = (50 * 51) as f64 / 2.0;
+        for (i, price) in prices.iter().enumerate() {
+            let (sma_value, wma_value) = (sma.update(*price), wma.update(*price));
+            if i + 1 < 50 {
+                continue;
+            }
+            let window = &prices[i + 1 - 50..=i];
+            assert_close(sma_value.unwrap(), window.iter().sum::<f64>() / 50.0);
+            let weighted: f64 = window.iter().enumerate().map(|(k, v)| (k + 1) as f64 * v).sum();
+            assert_close(wma_value.unwrap(), weighted / weights);
+        }
+    }
+
+    #[test]
+    fn preview_leaves_the_series_alone() {
+        let bars: Vec<Bar> = (0..30).map(|i| price_bar(i * 60, 100.0 + (i % 7) as f64, 1)).collect();
+        for spec in IndicatorSpec::DEFAULTS {
+            let mut series = IndicatorSeries::from_bars(spec, &bars[..20]);
+            let before = series.points().to_vec();
+            let forming = bars[20];
+            let preview = series.preview(&forming);
+            assert!(same(series.points(), &before), "{}", spec.label());
+
+            // Pushing the bar for real gives what the preview showed
+            series.push(&forming);
+            let pushed = series.points().last().copied();
+            assert!(same(preview.as_slice(), pushed.as_slice()), "{}", spec.label());
+        }
+    }
+
+    #[test]
+    fn trim_keeps_the_indicator_state() {
+        let bars: Vec<Bar> = (0..40).map(|i| price_bar(i * 60, 100.0 + (i % 5) as f64, 1)).collect();
+        let spec = IndicatorSpec::Ema { period: 5 };
+        let full = IndicatorSeries::from_bars(spec, &bars);
+        let mut trimmed = IndicatorSeries::from_bars(spec, &bars[..30]);
+        trimmed.trim(4);
+        assert_eq!(trimmed.points().len(), 4);
+        for bar in &bars[30..] {
+            trimmed.push(bar);
+        }
+        assert!(same(trimmed.points(), &full.points()[full.points().len() - 14..]));
+    }
+
+    #[test]
+    fn indicator_outputs_follow_the_spec() {
+        let mut bands = IndicatorSpec::Bollinger { period: 2, deviations: 1.0 }.build();
+        assert_eq!(bands.update(&price_bar(0, 1.0, 1)), None);
+        assert_eq!(bands.update(&price_bar(60, 3.0, 1)), Some([2.0, 3.0, 1.0]));
+
+        let mut sma = IndicatorSpec::Sma { period: 1 }.build();
+        let [value, unused, _] = sma.update(&price_bar(0, 5.0, 1)).unwrap();
+        assert_eq!(value, 5.0);
+        assert!(unused.is_nan());
+    }
+}
@@ -0,0 +1,49 @@
+//+------------------------------------------------------------------+
+//|                                                           lib.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+//! Async Rust client for the SUM3API MT5 bridge (`ZmqPublisher.mq5`).
+//!
+//! The EA publishes ticks on a PUB socket and serves orders on a REP
+//! socket. [`Client`] wraps both: ticks arrive as a `Stream`, orders are
+//! plain async calls.
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use sum3api::{Client, ClientSettings};
+//!
+//! # async fn run() -> Result<(), sum3api::ClientError> {
+//! let client = Client::new(ClientSettings::default());
+//! let mut ticks = Box::pin(client.subscribe_ticks());
+//! if let Some(tick) = ticks.next().await {
+//!     client.market_buy(&tick.symbol, 0.01, 0.0, 0.0).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod analytics;
+pub mod backtest;
+pub mod bars;
+mod client;
+mod errors;
+mod feed;
+pub mod history;
+pub mod indicators;
+pub mod optimize;
+mod orders;
+pub mod risk;
+pub mod sim;
+pub mod sizing;
+pub mod strategy;
+mod types;
+
+pub use client::{Client, ClientError, ClientSettings};
+pub use errors::TradeError;
+pub use feed::{FeedSettings, FeedState, FeedStatus};
+pub use types::{
+    HistoryData, HistoryMode, OrderRequest, OrderResponse, PendingOrderData, PositionData, Request,
+    check_stops, CloseFilter, Side, StopLevel, TickData, TicketResult, Timeframe, ALL_SYMBOLS, CSV_DATA_MARKER,
+};
@@ -0,0 +1,519 @@
+//+------------------------------------------------------------------+
+//|                                                      optimize.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+//! Parameter optimization: backtests a catalog strategy over a grid or a
+//! random sample of parameter values, in parallel on all cores, and ranks
+//! the runs by an [`Objective`]. Walk-forward analysis repeats this on
+//! rolling in-sample windows and checks each winner on the data after it.
+
+use crate::analytics::PerformanceReport;
+use crate::backtest::{self, BacktestSettings};
+use crate::history::TickRow;
+use crate::strategy::{ParamSpec, StrategyFactory};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Most backtests one optimization may run.
+pub const MAX_RUNS: usize = 100_000;
+
+/// Scores are capped here, so runs without losses (infinite profit or
+/// recovery factor) still rank and plot.
+const MAX_RATIO: f64 = 100.0;
+
+// ============================================================================
+// Search Space & Objective
+// ============================================================================
+
+/// Values one parameter takes: `min`, `min + step`, ... up to `max`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParamRange {
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+}
+
+impl ParamRange {
+    /// The spec's search range, or its default alone when it is not
+    /// searched by default.
+    pub fn from_spec(spec: &ParamSpec) -> Self {
+        match spec.search {
+            true => Self { min: spec.min, max: spec.max, step: spec.step },
+            false => Self::fixed(spec.default),
+        }
+    }
+
+    /// A single value, e.g. to keep a parameter out of the search.
+    pub fn fixed(value: f64) -> Self {
+        Self { min: value, max: value, step: 0.0 }
+    }
+
+    pub fn count(&self) -> usize {
+        if self.step <= 0.0 || self.max <= self.min {
+            return 1;
+        }
+        ((self.max - self.min) / self.step + 1e-9).floor() as usize + 1
+    }
+
+    pub fn value(&self, index: usize) -> f64 {
+        // Rounded to the step's decimals, so 0.1 + 0.2 lands on 0.3
+        let value = self.min + self.step * index as f64;
+        (value * 1e9).round() / 1e9
+    }
+
+    pub fn values(&self) -> Vec<f64> {
+        (0..self.count()).map(|i| self.value(i)).collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Search {
+    /// Every combination of the range values
+    Grid,
+    /// `samples` distinct combinations drawn from the grid
+    Random { samples: usize, seed: u64 },
+}
+
+/// What the optimizer maximizes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+    NetProfit,
+    ProfitFactor,
+    Expectancy,
+    Sharpe,
+    Sortino,
+    /// Net profit over max drawdown
+    RecoveryFactor,
+}
+
+impl Objective {
+    pub const ALL: [Objective; 6] = [
+        Objective::NetProfit,
+        Objective::ProfitFactor,
+        Objective::Expectancy,
+        Objective::Sharpe,
+        Objective::Sortino,
+        Objective::RecoveryFactor,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Objective::NetProfit => "Net profit",
+            Objective::ProfitFactor => "Profit factor",
+            Objective::Expectancy => "Expectancy",
+            Objective::Sharpe => "Sharpe",
+            Objective::Sortino => "Sortino",
+            Objective::RecoveryFactor => "Recovery factor",
+        }
+    }
+
+    pub fn score(&self, report: &PerformanceReport) -> f64 {
+        match self {
+            Objective::NetProfit => report.net_profit,
+            Objective::ProfitFactor => report.profit_factor.map_or(MAX_RATIO, |pf| pf.min(MAX_RATIO)),
+            Objective::Expectancy => report.expectancy,
+            Objective::Sharpe => report.sharpe,
+            Objective::Sortino => report.sortino,
+            Objective::RecoveryFactor if report.max_drawdown > 0.0 => {
+                (report.net_profit / report.max_drawdown).min(MAX_RATIO)
+            }
+            Objective::RecoveryFactor if report.net_profit > 0.0 => MAX_RATIO,
+            Objective::RecoveryFactor => 0.0,
+        }
+    }
+}
+
+// ============================================================================
+// Settings & Results
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct OptimizeSettings {
+    pub factory: StrategyFactory,
+    /// One range per `factory.params` entry, in order
+    pub ranges: Vec<ParamRange>,
+    pub search: Search,
+    pub objective: Objective,
+    /// Runs with fewer closed trades get no score and rank last
+    pub min_trades: usize,
+    pub backtest: BacktestSettings,
+}
+
+impl OptimizeSettings {
+    /// Backtests a search runs at most; sets the factory rejects as
+    /// invalid are skipped.
+    pub fn run_count(&self) -> usize {
+        let grid = self.ranges.iter().fold(1usize, |n, r| n.saturating_mul(r.count()));
+        match self.search {
+            Search::Grid => grid,
+            Search::Random { samples, .. } => samples.min(grid),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OptimizeResult {
+    pub params: Vec<f64>,
+    pub report: PerformanceReport,
+    /// `None` below `min_trades`
+    pub score: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptimizeError {
+    TooManyRuns { runs: usize, max: usize },
+    NoTicks,
+    Cancelled,
+}
+
+impl fmt::Display for OptimizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptimizeError::TooManyRuns { runs, max } => {
+                write!(f, "{} runs requested, at most {} allowed (narrow the ranges)", runs, max)
+            }
+            OptimizeError::NoTicks => write!(f, "no ticks to test on"),
+            OptimizeError::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for OptimizeError {}
+
+/// Shared with the thread watching an optimization: runs finished so far,
+/// runs planned, and a stop flag.
+#[derive(Debug, Default)]
+pub struct Progress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    cancel: AtomicBool,
+}
+
+impl Progress {
+    pub fn done(&self) -> usize {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> usize {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Runs already started still finish.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+// ============================================================================
+// Optimizer
+// ============================================================================
+
+/// Parameter sets a search tries, leaving out those `valid` rejects.
+pub fn candidates(ranges: &[ParamRange], search: Search, valid: impl Fn(&[f64]) -> bool) -> Vec<Vec<f64>> {
+    match search {
+        Search::Grid => {
+            let mut sets = vec![Vec::with_capacity(ranges.len())];
+            for range in ranges {
+                let values = range.values();
+                sets = sets
+                    .into_iter()
+                    .flat_map(|set| {
+                        values.iter().map(move |v| {
+                            let mut next = set.clone();
+                            next.push(*v);
+                            next
+                        })
+                    })
+                    .collect();
+            }
+            sets.retain(|set| valid(set));
+            sets
+        }
+        Search::Random { samples, seed } => {
+            let mut rng = fastrand::Rng::with_seed(seed);
+            let mut seen = HashSet::new();
+            let mut sets = Vec::with_capacity(samples);
+            // Small grids run out of distinct valid sets; give up after as many misses
+            let mut misses = 0;
+            while sets.len() < samples && misses < samples.max(100) {
+                let indices: Vec<usize> = ranges.iter().map(|r| rng.usize(..r.count())).collect();
+                let set: Vec<f64> = ranges.iter().zip(&indices).map(|(r, i)| r.value(*i)).collect();
+                if seen.insert(indices) && valid(&set) {
+                    sets.push(set);
+                } else {
+                    misses += 1;
+                }
+            }
+            sets
+        }
+    }
+}
+
+/// Backtests every candidate in parallel. Results come best first.
+pub fn optimize(
+    ticks: &[TickRow],
+    settings: &OptimizeSettings,
+    progress: &Progress,
+) -> Result<Vec<OptimizeResult>, OptimizeError> {
+    let runs = settings.run_count();
+    if runs > MAX_RUNS {
+        return Err(OptimizeError::TooManyRuns { runs, max: MAX_RUNS });
+    }
+    if ticks.is_empty() {
+        return Err(OptimizeError::NoTicks);
+    }
+    let sets = candidates(&settings.ranges, settings.search, settings.factory.valid);
+    progress.total.fetch_add(sets.len(), Ordering::Relaxed);
+    run_sets(ticks, settings, sets, progress)
+}
+
+fn run_sets(
+    ticks: &[TickRow],
+    settings: &OptimizeSettings,
+    sets: Vec<Vec<f64>>,
+    progress: &Progress,
+) -> Result<Vec<OptimizeResult>, OptimizeError> {
+    let mut results: Vec<OptimizeResult> = sets
+        .into_par_iter()
+        .filter_map(|params| {
+            if progress.is_cancelled() {
+                return None;
+            }
+            let report = backtest::run_ticks((settings.factory.build)(&params), ticks, &settings.backtest).report;
+            progress.done.fetch_add(1, Ordering::Relaxed);
+            let score = (report.trades >= settings.min_trades).then(|| settings.objective.score(&report));
+            Some(OptimizeResult { params, report, score })
+        })
+        .collect();
+    if progress.is_cancelled() {
+        return Err(OptimizeError::Cancelled);
+    }
+    rank(&mut results);
+    Ok(results)
+}
+
+/// Best score first; unscored runs last.
+fn rank(results: &mut [OptimizeResult]) {
+    results.sort_by(|a, b| match (a.score, b.score) {
+        (Some(a), Some(b)) => b.total_cmp(&a),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+// ============================================================================
+// Walk-Forward Analysis
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WalkForwardSettings {
+    /// Number of in-sample / out-of-sample pairs
+    pub windows: usize,
+    /// Share of each window used in-sample, 0..1 exclusive
+    pub in_sample: f64,
+    /// In-sample windows all start at the first tick instead of rolling
+    pub anchored: bool,
+}
+
+impl Default for WalkForwardSettings {
+    fn default() -> Self {
+        Self { windows: 4, in_sample: 0.75, anchored: false }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WalkForwardFold {
+    pub in_sample: (i64, i64),
+    pub out_of_sample: (i64, i64),
+    /// Best in-sample run; `None` when no run had a score
+    pub best: Option<OptimizeResult>,
+    /// The best parameters, tested on the out-of-sample window
+    pub oos: Option<PerformanceReport>,
+    pub oos_score: Option<f64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct WalkForwardReport {
+    pub folds: Vec<WalkForwardFold>,
+    /// Summed over the out-of-sample windows
+    pub oos_net_profit: f64,
+    pub oos_trades: usize,
+    /// Out-of-sample net profit per unit of time over in-sample net profit
+    /// per unit of time; near 1 when in-sample results hold up, 0 when
+    /// in-sample lost money
+    pub efficiency: f64,
+}
+
+/// Time windows `(in_sample, out_of_sample)` over `start..end`. The
+/// out-of-sample windows follow each other up to `end`.
+pub fn walk_forward_windows(start: i64, end: i64, wf: &WalkForwardSettings) -> Vec<((i64, i64), (i64, i64))> {
+    let windows = wf.windows.max(1);
+    let in_sample = wf.in_sample.clamp(0.05, 0.95);
+    let span = (end - start) as f64;
+    let oos_len = span / (windows as f64 + in_sample / (1.0 - in_sample));
+    let is_len = span - windows as f64 * oos_len;
+    (0..windows)
+        .map(|k| {
+            let shift = k as f64 * oos_len;
+            let is_start = if wf.anchored { start } else { start + shift.round() as i64 };
+            let is_end = start + (is_len + shift).round() as i64;
+            let oos_end = if k + 1 == windows { end } else { start + (is_len + shift + oos_len).round() as i64 };
+            ((is_start, is_end), (is_end, oos_end))
+        })
+        .collect()
+}
+
+/// Optimizes on each in-sample window and backtests the winner on the
+/// out-of-sample window that follows it.
+pub fn walk_forward(
+    ticks: &[TickRow],
+    settings: &OptimizeSettings,
+    wf: &WalkForwardSettings,
+    progress: &Progress,
+) -> Result<WalkForwardReport, OptimizeError> {
+    let (Some(first), Some(last)) = (ticks.first(), ticks.last()) else {
+        return Err(OptimizeError::NoTicks);
+    };
+    let runs = settings.run_count().saturating_mul(wf.windows.max(1));
+    if runs > MAX_RUNS {
+        return Err(OptimizeError::TooManyRuns { runs, max: MAX_RUNS });
+    }
+    // Every window tries the same sets
+    let sets = candidates(&settings.ranges, settings.search, settings.factory.valid);
+    let windows = walk_forward_windows(first.time, last.time + 1, wf);
+    progress.total.fetch_add(sets.len() * windows.len(), Ordering::Relaxed);
+    let slice = |(from, to): (i64, i64)| {
+        let start = ticks.partition_point(|t| t.time < from);
+        let end = ticks.partition_point(|t| t.time < to);
+        &ticks[start..end]
+    };
+
+    let mut folds = Vec::new();
+    let (mut is_rate, mut oos_rate) = ((0.0, 0.0), (0.0, 0.0));
+    for (in_sample, out_of_sample) in windows {
+        let is_ticks = slice(in_sample);
+        let best = if is_ticks.is_empty() {
+            progress.done.fetch_add(sets.len(), Ordering::Relaxed);
+            None
+        } else {
+            run_sets(is_ticks, settings, sets.clone(), progress)?.into_iter().next().filter(|r| r.score.is_some())
+        };
+        let oos_ticks = slice(out_of_sample);
+        let oos = match &best {
+            Some(best) if !oos_ticks.is_empty() => {
+                Some(backtest::run_ticks((settings.factory.build)(&best.params), oos_ticks, &settings.backtest).report)
+            }
+            _ => None,
+        };
+        if let (Some(best), Some(oos)) = (&best, &oos) {
+            is_rate.0 += best.report.net_profit;
+            is_rate.1 += (in_sample.1 - in_sample.0) as f64;
+            oos_rate.0 += oos.net_profit;
+            oos_rate.1 += (out_of_sample.1 - out_of_sample.0) as f64;
+        }
+        let oos_score = oos.as_ref().map(|r| settings.objective.score(r));
+        folds.push(WalkForwardFold { in_sample, out_of_sample, best, oos, oos_score });
+    }
+
+    let per_second = |(profit, secs): (f64, f64)| if secs > 0.0 { profit / secs } else { 0.0 };
+    let is_per_second = per_second(is_rate);
+    Ok(WalkForwardReport {
+        // Adding 0.0 turns the -0.0 of an empty f64 sum into 0.0
+        oos_net_profit: folds.iter().filter_map(|f| f.oos.as_ref()).map(|r| r.net_profit).sum::<f64>() + 0.0,
+        oos_trades: folds.iter().filter_map(|f| f.oos.as_ref()).map(|r| r.trades).sum(),
+        efficiency: if is_per_second > 0.0 { per_second(oos_rate) / is_per_second } else { 0.0 },
+        folds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy;
+
+    fn range(min: f64, max: f64, step: f64) -> ParamRange {
+        ParamRange { min, max, step }
+    }
+
+    #[test]
+    fn range_values() {
+        assert_eq!(range(5.0, 200.0, 5.0).count(), 40);
+        assert_eq!(range(0.1, 0.3, 0.1).values(), [0.1, 0.2, 0.3]);
+        assert_eq!(range(0.1, 0.35, 0.1).values(), [0.1, 0.2, 0.3]);
+        assert_eq!(range(0.01, 1.0, 0.01).value(29), 0.3);
+        assert_eq!(range(2.0, 1.0, 1.0).count(), 1);
+        assert_eq!(ParamRange::fixed(0.01).count(), 1);
+        assert_eq!(ParamRange::fixed(0.01).values(), [0.01]);
+    }
+
+    #[test]
+    fn grid_tries_every_valid_combination() {
+        let ranges = [range(1.0, 3.0, 1.0), range(2.0, 3.0, 1.0)];
+        let all = candidates(&ranges, Search::Grid, |_| true);
+        assert_eq!(all, [[1.0, 2.0], [1.0, 3.0], [2.0, 2.0], [2.0, 3.0], [3.0, 2.0], [3.0, 3.0]]);
+        let ordered = candidates(&ranges, Search::Grid, |p| p[0] < p[1]);
+        assert_eq!(ordered, [[1.0, 2.0], [1.0, 3.0], [2.0, 3.0]]);
+    }
+
+    #[test]
+    fn random_search_samples_distinct_valid_sets() {
+        let ranges = [range(1.0, 100.0, 1.0), range(1.0, 100.0, 1.0)];
+        let search = Search::Random { samples: 50, seed: 7 };
+        let sets = candidates(&ranges, search, |p| p[0] < p[1]);
+        assert_eq!(sets.len(), 50);
+        assert!(sets.iter().all(|p| p[0] < p[1]));
+        let distinct: HashSet<String> = sets.iter().map(|p| format!("{:?}", p)).collect();
+        assert_eq!(distinct.len(), 50);
+        // Same seed, same sample
+        assert_eq!(candidates(&ranges, search, |p| p[0] < p[1]), sets);
+
+        // A grid smaller than the sample gives every set once
+        let small = candidates(&[range(1.0, 3.0, 1.0)], search, |_| true);
+        assert_eq!(small.len(), 3);
+    }
+
+    #[test]
+    fn default_sma_grid_fits_the_run_limit() {
+        let factory = strategy::catalog()[0];
+        let ranges: Vec<ParamRange> = factory.params.iter().map(ParamRange::from_spec).collect();
+        assert_eq!(ranges[2], ParamRange::fixed(0.01));
+        let grid: usize = ranges.iter().map(|r| r.count()).product();
+        assert_eq!(grid, 40 * 50);
+        assert!(grid <= MAX_RUNS);
+
+        // fast >= slow would only repeat runs with slow raised to fast + 1
+        let sets = candidates(&ranges, Search::Grid, factory.valid);
+        assert_eq!(sets.len(), 2000 - 190);
+        assert!(sets.iter().all(|p| p[0] < p[1]));
+    }
+
+    #[test]
+    fn rolling_walk_forward_windows() {
+        let wf = WalkForwardSettings { windows: 4, in_sample: 0.75, anchored: false };
+        assert_eq!(
+            walk_forward_windows(0, 1000, &wf),
+            [
+                ((0, 429), (429, 571)),
+                ((143, 571), (571, 714)),
+                ((286, 714), (714, 857)),
+                ((429, 857), (857, 1000)),
+            ]
+        );
+    }
+
+    #[test]
+    fn anchored_walk_forward_windows() {
+        let wf = WalkForwardSettings { windows: 3, in_sample: 0.5, anchored: true };
+        let windows = walk_forward_windows(100, 500, &wf);
+        assert_eq!(windows, [((100, 200), (200, 300)), ((100, 300), (300, 400)), ((100, 400), (400, 500))]);
+    }
+}
@@ -0,0 +1,262 @@
+//+------------------------------------------------------------------+
+//|                                                        orders.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::{OrderRequest, OrderResponse, Request};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use zeromq::{ReqSocket, Socket, SocketRecv, SocketSend};
+
+/// An order request plus the slot its response goes to.
+pub(crate) struct OrderEnvelope {
+    pub request: OrderRequest,
+    pub reply: oneshot::Sender<OrderResponse>,
+}
+
+enum ExchangeError {
+    /// Request never left the socket; nothing happened on the EA side
+    NotDelivered(String),
+    /// Request was sent but no reply arrived in time; outcome unknown
+    Timeout,
+    /// Socket failed mid-exchange and must be recreated
+    Broken(String),
+    /// Reply arrived but could not be decoded; socket is still usable
+    BadReply(String),
+}
+
+// ============================================================================
+// Lazy-Pirate Order Handler
+// ============================================================================
+
+/// Serves order requests one at a time over a REQ socket. A request that
+/// gets no reply within `timeout` is answered locally as "outcome unknown",
+/// the socket is recreated and the EA is asked for its open positions so
+/// the caller can reconcile.
+pub(crate) async fn run_order_handler(
+    address: String,
+    timeout: Duration,
+    mut order_rx: mpsc::Receiver<OrderEnvelope>,
+) {
+    let mut socket: Option<ReqSocket> = None;
+
+    while let Some(OrderEnvelope { request: order_request, reply }) = order_rx.recv().await {
+        // Serialize order request to JSON
+        let json_request = match serde_json::to_string(&order_request) {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("Failed to serialize order request: {}", e);
+                let _ = reply.send(OrderResponse::failure(format!("Serialize error: {}", e)));
+                continue;
+            }
+        };
+
+        if socket.is_none() {
+            match connect(&address).await {
+                Ok(s) => socket = Some(s),
+                Err(e) => {
+                    let mut response = OrderResponse::failure(e);
+                    response.request_id = order_request.request_id;
+                    let _ = reply.send(response);
+                    continue;
+                }
+            }
+        }
+        let Some(active) = socket.as_mut() else { continue };
+
+        let mut response = match exchange(active, json_request, timeout).await {
+            Ok(response) => response,
+            Err(ExchangeError::BadReply(e)) => OrderResponse::failure(e),
+            Err(ExchangeError::NotDelivered(e)) | Err(ExchangeError::Broken(e)) => {
+                eprintln!("Order socket error: {}", e);
+                socket = None;
+                OrderResponse::failure(e)
+            }
+            Err(ExchangeError::Timeout) => {
+                eprintln!("No reply within {}s, recreating order socket", timeout.as_secs());
+                socket = None;
+                let mut response = OrderResponse::failure(format!(
+                    "Timed out after {}s, outcome unknown",
+                    timeout.as_secs()
+                ));
+                response.timed_out = true;
+
+                // Ask the EA what is actually open so the caller can tell
+                // whether the order went through.
+                if let Ok(mut fresh) = connect(&address).await {
+                    let query = OrderRequest::from(Request::GetPositions {
+                        symbol: order_request.request.symbol().to_string(),
+                    });
+                    if let Ok(json) = serde_json::to_string(&query) {
+                        match exchange(&mut fresh, json, timeout).await {
+                            Ok(snapshot) => {
+                                response.positions = snapshot.positions;
+                                response.orders = snapshot.orders;
+                                socket = Some(fresh);
+                            }
+                            Err(ExchangeError::BadReply(_)) => socket = Some(fresh),
+                            Err(_) => eprintln!("Position check after reconnect failed"),
+                        }
+                    }
+                }
+                response
+            }
+        };
+
+        // Replies come back strictly in order on a REQ socket, so a reply
+        // without an echoed ID (older EA builds) belongs to this request.
+        // A reply echoing another ID means the socket is out of step: drop
+        // it like a timed-out one and fail this request.
+        match (response.request_id, order_request.request_id) {
+            (Some(got), Some(sent)) if got != sent => {
+                socket = None;
+                response = OrderResponse::failure(format!(
+                    "Reply for request #{} while waiting for #{}, outcome unknown",
+                    got, sent
+                ));
+                response.request_id = Some(sent);
+            }
+            (None, sent) => response.request_id = sent,
+            _ => {}
+        }
+
+        // The caller may have given up waiting; that is fine
+        let _ = reply.send(response);
+    }
+}
+
+async fn connect(address: &str) -> Result<ReqSocket, String> {
+    let mut socket = ReqSocket::new();
+    match socket.connect(address).await {
+        Ok(_) => {
+            println!("Connected to ZMQ Order Handler at {}", address);
+            Ok(socket)
+        }
+        Err(e) => {
+            eprintln!("Failed to connect to ZMQ order handler: {}", e);
+            Err(format!("Not connected to {}: {}", address, e))
+        }
+    }
+}
+
+async fn exchange(
+    socket: &mut ReqSocket,
+    json_request: String,
+    timeout: Duration,
+) -> Result<OrderResponse, ExchangeError> {
+    match tokio::time::timeout(timeout, socket.send(json_request.into())).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => return Err(ExchangeError::NotDelivered(format!("Send failed: {}", e))),
+        Err(_) => return Err(ExchangeError::NotDelivered("Send timed out, request not delivered".to_string())),
+    }
+
+    let msg = match tokio::time::timeout(timeout, socket.recv()).await {
+        Ok(Ok(msg)) => msg,
+        Ok(Err(e)) => return Err(ExchangeError::Broken(format!("Recv failed: {}", e))),
+        Err(_) => return Err(ExchangeError::Timeout),
+    };
+
+    let json_str = msg
+        .get(0)
+        .and_then(|bytes| std::str::from_utf8(bytes).ok())
+        .ok_or_else(|| ExchangeError::BadReply("Empty or non-UTF-8 response".to_string()))?;
+    serde_json::from_str::<OrderResponse>(json_str)
+        .map_err(|e| ExchangeError::BadReply(format!("Parse error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zeromq::{RepSocket, ZmqMessage};
+
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    async fn bind_rep() -> (RepSocket, String) {
+        let mut rep = RepSocket::new();
+        let endpoint = rep.bind("tcp://127.0.0.1:0").await.expect("bind");
+        (rep, endpoint.to_string())
+    }
+
+    fn spawn_handler(address: String) -> mpsc::Sender<OrderEnvelope> {
+        let (order_tx, order_rx) = mpsc::channel(4);
+        tokio::spawn(run_order_handler(address, TIMEOUT, order_rx));
+        order_tx
+    }
+
+    async fn submit(order_tx: &mpsc::Sender<OrderEnvelope>, request: OrderRequest) -> OrderResponse {
+        let (reply, response) = oneshot::channel();
+        order_tx.send(OrderEnvelope { request, reply }).await.expect("handler alive");
+        response.await.expect("handler replied")
+    }
+
+    async fn recv_request(rep: &mut RepSocket) -> OrderRequest {
+        let msg = tokio::time::timeout(Duration::from_secs(5), rep.recv())
+            .await
+            .expect("request within 5s")
+            .expect("recv");
+        serde_json::from_slice(msg.get(0).expect("frame")).expect("valid request")
+    }
+
+    fn order(request_id: u64) -> OrderRequest {
+        let mut request = OrderRequest::from(Request::ClosePosition { symbol: "XAUUSD".to_string(), ticket: 7 });
+        request.request_id = Some(request_id);
+        request
+    }
+
+    #[tokio::test]
+    async fn unanswered_request_times_out_and_recreates_socket() {
+        let (mut rep, address) = bind_rep().await;
+        let order_tx = spawn_handler(address);
+
+        let pending = tokio::spawn({
+            let order_tx = order_tx.clone();
+            async move { submit(&order_tx, order(1)).await }
+        });
+        // Take the order and never answer it
+        assert_eq!(recv_request(&mut rep).await.request_id, Some(1));
+
+        // The reconciliation query can only arrive on a fresh REQ socket:
+        // the old one is still waiting for its reply.
+        let query = recv_request(&mut rep).await;
+        assert!(matches!(query.request, Request::GetPositions { .. }));
+        rep.send(ZmqMessage::from(r#"{"success":true,"positions":[]}"#)).await.unwrap();
+
+        let response = pending.await.unwrap();
+        assert!(response.timed_out);
+        assert!(!response.success);
+        assert_eq!(response.request_id, Some(1));
+        assert!(response.positions.is_some());
+
+        // The recreated socket carries the next request
+        let next = tokio::spawn(async move { submit(&order_tx, order(2)).await });
+        assert_eq!(recv_request(&mut rep).await.request_id, Some(2));
+        rep.send(ZmqMessage::from(r#"{"request_id":2,"success":true}"#)).await.unwrap();
+        let response = next.await.unwrap();
+        assert!(response.success);
+        assert!(!response.timed_out);
+    }
+
+    #[tokio::test]
+    async fn mismatched_reply_fails_and_recreates_socket() {
+        let (mut rep, address) = bind_rep().await;
+        let order_tx = spawn_handler(address);
+
+        let pending = tokio::spawn({
+            let order_tx = order_tx.clone();
+            async move { submit(&order_tx, order(1)).await }
+        });
+        recv_request(&mut rep).await;
+        rep.send(ZmqMessage::from(r#"{"request_id":9,"success":true}"#)).await.unwrap();
+
+        let response = pending.await.unwrap();
+        assert!(!response.success);
+        assert_eq!(response.request_id, Some(1));
+        assert!(response.error.unwrap().contains("#9"));
+
+        let next = tokio::spawn(async move { submit(&order_tx, order(2)).await });
+        assert_eq!(recv_request(&mut rep).await.request_id, Some(2));
+        rep.send(ZmqMessage::from(r#"{"request_id":2,"success":true}"#)).await.unwrap();
+        assert!(next.await.unwrap().success);
+    }
+}
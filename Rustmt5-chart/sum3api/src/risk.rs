@@ -0,0 +1,324 @@
+//+------------------------------------------------------------------+
+//|                                                          risk.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+//! Pre-trade risk checks. Requests that add risk are tested against the
+//! configured limits before they are sent; a kill switch locks trading.
+
+use crate::{PendingOrderData, PositionData, Request};
+use std::fmt;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+// ============================================================================
+// Limits
+// ============================================================================
+
+/// Hard limits on new orders. 0 turns a limit off.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RiskLimits {
+    /// Largest volume of a single order
+    pub max_order_lots: f64,
+    /// Most open positions plus pending orders
+    pub max_open_positions: usize,
+    /// Most lots on one symbol, positions and pending orders together
+    pub max_symbol_lots: f64,
+    /// Largest loss of equity against the balance at the start of the
+    /// (server) day
+    pub max_daily_loss: f64,
+    /// Free margin that must remain before a new order is sent
+    pub min_free_margin: f64,
+}
+
+/// The account as the risk checks see it. `positions` and `orders` are
+/// those of the traded symbol (the EA reports only its chart symbol).
+#[derive(Clone, Copy, Debug)]
+pub struct AccountState<'a> {
+    pub equity: f64,
+    pub free_margin: f64,
+    pub positions: &'a [PositionData],
+    pub orders: &'a [PendingOrderData],
+}
+
+/// Why a request was blocked.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RiskBreach {
+    Locked,
+    OrderSize { lots: f64, max: f64 },
+    OpenPositions { count: usize, max: usize },
+    SymbolExposure { symbol: String, lots: f64, max: f64 },
+    DailyLoss { loss: f64, max: f64 },
+    FreeMargin { free: f64, min: f64 },
+}
+
+impl fmt::Display for RiskBreach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskBreach::Locked => write!(f, "trading is locked by the kill switch"),
+            RiskBreach::OrderSize { lots, max } => {
+                write!(f, "order volume {:.2} exceeds the {:.2} lot limit", lots, max)
+            }
+            RiskBreach::OpenPositions { count, max } => {
+                write!(f, "{} positions/orders already open (limit {})", count, max)
+            }
+            RiskBreach::SymbolExposure { symbol, lots, max } => {
+                write!(f, "{:.2} lots on {} would exceed the {:.2} lot limit", lots, symbol, max)
+            }
+            RiskBreach::DailyLoss { loss, max } => {
+                write!(f, "daily loss {:.2} has reached the {:.2} limit", loss, max)
+            }
+            RiskBreach::FreeMargin { free, min } => {
+                write!(f, "free margin {:.2} is below the {:.2} minimum", free, min)
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Risk Engine
+// ============================================================================
+
+/// Checks requests against [`RiskLimits`], tracks the start-of-day balance
+/// and holds the kill switch lock.
+#[derive(Clone, Debug)]
+pub struct RiskEngine {
+    limits: RiskLimits,
+    day: Option<i64>,
+    day_start_balance: f64,
+    locked: bool,
+}
+
+impl RiskEngine {
+    pub fn new(limits: RiskLimits) -> Self {
+        Self { limits, day: None, day_start_balance: 0.0, locked: false }
+    }
+
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Feeds the account balance at server `time`; the first balance of
+    /// each server day is the reference for the daily loss.
+    pub fn update_day(&mut self, time: i64, balance: f64) {
+        let day = time.div_euclid(SECONDS_PER_DAY);
+        if self.day != Some(day) && balance > 0.0 {
+            self.day = Some(day);
+            self.day_start_balance = balance;
+        }
+    }
+
+    /// Forgets the start-of-day balance, e.g. after switching accounts.
+    pub fn reset_day(&mut self) {
+        self.day = None;
+    }
+
+    /// Equity lost since the start of the day (negative when up).
+    pub fn daily_loss(&self, equity: f64) -> f64 {
+        match self.day {
+            Some(_) => self.day_start_balance - equity,
+            None => 0.0,
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Kill switch: blocks every request that adds risk until `unlock`.
+    pub fn lock(&mut self) {
+        self.locked = true;
+    }
+
+    pub fn unlock(&mut self) {
+        self.locked = false;
+    }
+
+    /// Passes requests that only reduce or adjust risk (closes, cancels,
+    /// SL/TP changes, queries); checks new orders and reversals. While
+    /// locked, pending orders cannot be moved either.
+    pub fn check(&self, request: &Request, account: &AccountState) -> Result<(), RiskBreach> {
+        let opens = request.volume();
+        let reverses = matches!(request, Request::Reverse { .. });
+        if self.locked && (opens.is_some() || reverses || matches!(request, Request::ModifyOrder { .. })) {
+            return Err(RiskBreach::Locked);
+        }
+        if opens.is_none() && !reverses {
+            return Ok(());
+        }
+
+        let limits = &self.limits;
+        let loss = self.daily_loss(account.equity);
+        if limits.max_daily_loss > 0.0 && loss >= limits.max_daily_loss {
+            return Err(RiskBreach::DailyLoss { loss, max: limits.max_daily_loss });
+        }
+        if limits.min_free_margin > 0.0 && account.free_margin < limits.min_free_margin {
+            return Err(RiskBreach::FreeMargin { free: account.free_margin, min: limits.min_free_margin });
+        }
+
+        // A reversal swaps one position for another of the same size
+        let Some(lots) = opens else {
+            return Ok(());
+        };
+        if limits.max_order_lots > 0.0 && lots > limits.max_order_lots + 1e-9 {
+            return Err(RiskBreach::OrderSize { lots, max: limits.max_order_lots });
+        }
+        let count = account.positions.len() + account.orders.len();
+        if limits.max_open_positions > 0 && count >= limits.max_open_positions {
+            return Err(RiskBreach::OpenPositions { count, max: limits.max_open_positions });
+        }
+        if limits.max_symbol_lots > 0.0 {
+            let open: f64 = account.positions.iter().map(|p| p.volume).sum::<f64>()
+                + account.orders.iter().map(|o| o.volume).sum::<f64>();
+            if open + lots > limits.max_symbol_lots + 1e-9 {
+                return Err(RiskBreach::SymbolExposure {
+                    symbol: request.symbol().to_string(),
+                    lots: open + lots,
+                    max: limits.max_symbol_lots,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CloseFilter;
+
+    const SYMBOL: &str = "XAUUSDc";
+
+    fn position(volume: f64) -> PositionData {
+        PositionData { ticket: 1, pos_type: "BUY".to_string(), volume, price: 2000.0, profit: 0.0, sl: 0.0, tp: 0.0 }
+    }
+
+    fn order(volume: f64) -> PendingOrderData {
+        PendingOrderData {
+            ticket: 2,
+            order_type: "BUY LIMIT".to_string(),
+            volume,
+            price: 1990.0,
+            sl: 0.0,
+            tp: 0.0,
+            expiration: 0,
+        }
+    }
+
+    fn account<'a>(positions: &'a [PositionData], orders: &'a [PendingOrderData]) -> AccountState<'a> {
+        AccountState { equity: 10_000.0, free_margin: 8_000.0, positions, orders }
+    }
+
+    fn buy(volume: f64) -> Request {
+        Request::MarketBuy { symbol: SYMBOL.to_string(), volume, sl: 0.0, tp: 0.0 }
+    }
+
+    fn reverse() -> Request {
+        Request::Reverse { symbol: SYMBOL.to_string(), ticket: 1 }
+    }
+
+    #[test]
+    fn no_limits_pass_everything() {
+        let engine = RiskEngine::new(RiskLimits::default());
+        assert_eq!(engine.check(&buy(50.0), &account(&[], &[])), Ok(()));
+    }
+
+    #[test]
+    fn order_size() {
+        let engine = RiskEngine::new(RiskLimits { max_order_lots: 1.0, ..Default::default() });
+        assert_eq!(engine.check(&buy(1.0), &account(&[], &[])), Ok(()));
+        assert_eq!(
+            engine.check(&buy(1.5), &account(&[], &[])),
+            Err(RiskBreach::OrderSize { lots: 1.5, max: 1.0 })
+        );
+    }
+
+    #[test]
+    fn open_positions_count_orders_too() {
+        let engine = RiskEngine::new(RiskLimits { max_open_positions: 2, ..Default::default() });
+        let positions = [position(0.1)];
+        assert_eq!(engine.check(&buy(0.1), &account(&positions, &[])), Ok(()));
+        assert_eq!(
+            engine.check(&buy(0.1), &account(&positions, &[order(0.1)])),
+            Err(RiskBreach::OpenPositions { count: 2, max: 2 })
+        );
+    }
+
+    #[test]
+    fn symbol_exposure() {
+        let engine = RiskEngine::new(RiskLimits { max_symbol_lots: 1.0, ..Default::default() });
+        let (positions, orders) = ([position(0.5)], [order(0.3)]);
+        assert_eq!(engine.check(&buy(0.2), &account(&positions, &orders)), Ok(()));
+        let Err(RiskBreach::SymbolExposure { symbol, lots, max }) = engine.check(&buy(0.3), &account(&positions, &orders))
+        else {
+            panic!("expected a symbol exposure breach");
+        };
+        assert_eq!(symbol, SYMBOL);
+        assert!((lots - 1.1).abs() < 1e-9);
+        assert_eq!(max, 1.0);
+    }
+
+    #[test]
+    fn daily_loss_from_the_start_of_day_balance() {
+        let mut engine = RiskEngine::new(RiskLimits { max_daily_loss: 500.0, ..Default::default() });
+        engine.update_day(86_400 + 10, 10_400.0);
+        // Later balances the same day do not move the reference
+        engine.update_day(86_400 + 20, 10_000.0);
+        assert_eq!(engine.daily_loss(10_000.0), 400.0);
+        assert_eq!(engine.check(&buy(0.1), &account(&[], &[])), Ok(()));
+
+        let down = AccountState { equity: 9_900.0, ..account(&[], &[]) };
+        assert_eq!(engine.check(&buy(0.1), &down), Err(RiskBreach::DailyLoss { loss: 500.0, max: 500.0 }));
+        assert_eq!(engine.check(&reverse(), &down), Err(RiskBreach::DailyLoss { loss: 500.0, max: 500.0 }));
+
+        // A new day starts from its own balance
+        engine.update_day(2 * 86_400, 9_900.0);
+        assert_eq!(engine.check(&buy(0.1), &down), Ok(()));
+    }
+
+    #[test]
+    fn free_margin() {
+        let engine = RiskEngine::new(RiskLimits { min_free_margin: 9_000.0, ..Default::default() });
+        assert_eq!(
+            engine.check(&buy(0.1), &account(&[], &[])),
+            Err(RiskBreach::FreeMargin { free: 8_000.0, min: 9_000.0 })
+        );
+    }
+
+    #[test]
+    fn locked_still_allows_closing() {
+        let mut engine = RiskEngine::new(RiskLimits { max_daily_loss: 100.0, ..Default::default() });
+        engine.update_day(0, 10_500.0);
+        engine.lock();
+        let state = account(&[], &[]);
+        assert_eq!(engine.check(&buy(0.1), &state), Err(RiskBreach::Locked));
+        assert_eq!(engine.check(&reverse(), &state), Err(RiskBreach::Locked));
+        let reprice = Request::ModifyOrder {
+            symbol: SYMBOL.to_string(),
+            ticket: 2,
+            price: 1995.0,
+            sl: 0.0,
+            tp: 0.0,
+            expiration: 0,
+        };
+        assert_eq!(engine.check(&reprice, &state), Err(RiskBreach::Locked));
+
+        let reducing = [
+            Request::ClosePosition { symbol: SYMBOL.to_string(), ticket: 1 },
+            Request::ClosePartial { symbol: SYMBOL.to_string(), ticket: 1, volume: 0.1 },
+            Request::CloseAll { symbol: crate::ALL_SYMBOLS.to_string(), filter: CloseFilter::All },
+            Request::CancelOrder { symbol: SYMBOL.to_string(), ticket: 2 },
+            Request::CancelAll { symbol: crate::ALL_SYMBOLS.to_string() },
+            Request::ModifyPosition { symbol: SYMBOL.to_string(), ticket: 1, sl: 1990.0, tp: 0.0 },
+            Request::GetPositions { symbol: SYMBOL.to_string() },
+        ];
+        for request in &reducing {
+            assert_eq!(engine.check(request, &state), Ok(()), "{:?}", request);
+        }
+
+        engine.unlock();
+        assert_eq!(engine.check(&buy(0.1), &state), Err(RiskBreach::DailyLoss { loss: 500.0, max: 100.0 }));
+        assert_eq!(engine.check(&reprice, &state), Ok(()));
+    }
+}
@@ -0,0 +1,803 @@
+//+------------------------------------------------------------------+
+//|                                                           sim.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::{
+    check_stops, OrderResponse, PendingOrderData, PositionData, Request, Side, TickData, TicketResult, TradeError,
+    ALL_SYMBOLS,
+};
+
+// ============================================================================
+// Settings & State
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct SimSettings {
+    pub symbol: String,
+    pub initial_balance: f64,
+    /// Units per lot (100 oz for XAUUSD)
+    pub contract_size: f64,
+    pub leverage: f64,
+    pub min_lot: f64,
+    pub max_lot: f64,
+    pub lot_step: f64,
+    /// Price of one point (0.001 for a 3-digit quote)
+    pub point: f64,
+    /// Price moved against every fill, opening and closing
+    pub slippage: f64,
+    /// Round-turn commission per lot, charged when a position (or part
+    /// of it) closes
+    pub commission_per_lot: f64,
+}
+
+impl Default for SimSettings {
+    fn default() -> Self {
+        Self {
+            symbol: "XAUUSDc".to_string(),
+            initial_balance: 10_000.0,
+            contract_size: 100.0,
+            leverage: 100.0,
+            min_lot: 0.01,
+            max_lot: 100.0,
+            lot_step: 0.01,
+            point: 0.001,
+            slippage: 0.0,
+            commission_per_lot: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PendingKind {
+    Limit,
+    Stop,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimPosition {
+    pub ticket: u64,
+    pub side: Side,
+    pub volume: f64,
+    pub open_price: f64,
+    pub open_time: i64,
+    pub profit: f64,
+    // Stop-loss / take-profit prices, 0 when not set
+    pub sl: f64,
+    pub tp: f64,
+    /// Stop-loss the position opened with, for R-multiples
+    pub initial_sl: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimOrder {
+    pub ticket: u64,
+    pub side: Side,
+    pub kind: PendingKind,
+    pub volume: f64,
+    pub price: f64,
+    pub sl: f64,
+    pub tp: f64,
+    /// Server time the order is removed, 0 = good till cancelled
+    pub expiration: i64,
+}
+
+/// Why a position was closed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloseReason {
+    Manual,
+    StopLoss,
+    TakeProfit,
+}
+
+/// A position after it was closed.
+#[derive(Clone, Debug)]
+pub struct ClosedTrade {
+    pub ticket: u64,
+    pub side: Side,
+    pub volume: f64,
+    pub open_price: f64,
+    pub close_price: f64,
+    /// Stop-loss at the open, 0 when it had none
+    pub initial_sl: f64,
+    pub open_time: i64,
+    pub close_time: i64,
+    /// Price P&L, before commission
+    pub profit: f64,
+    pub commission: f64,
+    pub reason: CloseReason,
+}
+
+impl ClosedTrade {
+    pub fn net_profit(&self) -> f64 {
+        self.profit - self.commission
+    }
+}
+
+// ============================================================================
+// Simulated Broker
+// ============================================================================
+
+/// In-process stand-in for the MT5 trade server: fills market orders at the
+/// current bid/ask, triggers pending orders as prices cross, and tracks
+/// positions, balance, equity and margin for one symbol.
+#[derive(Clone, Debug)]
+pub struct SimBroker {
+    settings: SimSettings,
+    balance: f64,
+    bid: f64,
+    ask: f64,
+    time: i64,
+    next_ticket: u64,
+    positions: Vec<SimPosition>,
+    orders: Vec<SimOrder>,
+    closed: Vec<ClosedTrade>,
+}
+
+impl SimBroker {
+    pub fn new(settings: SimSettings) -> Self {
+        Self {
+            balance: settings.initial_balance,
+            settings,
+            bid: 0.0,
+            ask: 0.0,
+            time: 0,
+            next_ticket: 1000,
+            positions: Vec::new(),
+            orders: Vec::new(),
+            closed: Vec::new(),
+        }
+    }
+
+    pub fn settings(&self) -> &SimSettings {
+        &self.settings
+    }
+
+    /// Moves the market. Triggers crossed pending orders, closes positions
+    /// whose SL/TP was reached (see `closed_trades`) and revalues the rest.
+    /// Returns each triggered order's ticket, with `NoMoney` when the free
+    /// margin could not carry it and the order was dropped.
+    pub fn update_price(&mut self, bid: f64, ask: f64, time: i64) -> Vec<(u64, Result<(), TradeError>)> {
+        self.bid = bid;
+        self.ask = ask;
+        self.time = time;
+        self.revalue();
+
+        let (bid, ask) = (self.bid, self.ask);
+        self.orders.retain(|o| o.expiration == 0 || o.expiration > time);
+        let (triggered, waiting): (Vec<SimOrder>, Vec<SimOrder>) =
+            self.orders.drain(..).partition(|o| match (o.side, o.kind) {
+                (Side::Buy, PendingKind::Limit) => ask <= o.price,
+                (Side::Sell, PendingKind::Limit) => bid >= o.price,
+                (Side::Buy, PendingKind::Stop) => ask >= o.price,
+                (Side::Sell, PendingKind::Stop) => bid <= o.price,
+            });
+        self.orders = waiting;
+
+        let mut tickets = Vec::with_capacity(triggered.len());
+        for order in triggered {
+            let price = if order.side == Side::Buy { ask } else { bid };
+            if self.required_margin(order.volume, price) > self.free_margin() {
+                tickets.push((order.ticket, Err(TradeError::NoMoney)));
+                continue;
+            }
+            self.open_position(order.ticket, order.side, order.volume, order.sl, order.tp);
+            tickets.push((order.ticket, Ok(())));
+        }
+
+        // Stops are checked at the price the position would close at
+        let hits: Vec<(u64, CloseReason)> = self.positions
+            .iter()
+            .filter_map(|p| {
+                let close = if p.side == Side::Buy { bid } else { ask };
+                let (sl_hit, tp_hit) = match p.side {
+                    Side::Buy => (p.sl > 0.0 && close <= p.sl, p.tp > 0.0 && close >= p.tp),
+                    Side::Sell => (p.sl > 0.0 && close >= p.sl, p.tp > 0.0 && close <= p.tp),
+                };
+                match (sl_hit, tp_hit) {
+                    (true, _) => Some((p.ticket, CloseReason::StopLoss)),
+                    (false, true) => Some((p.ticket, CloseReason::TakeProfit)),
+                    _ => None,
+                }
+            })
+            .collect();
+        for (ticket, reason) in hits {
+            self.close_with_reason(ticket, reason);
+        }
+
+        self.revalue();
+        tickets
+    }
+
+    fn revalue(&mut self) {
+        let (bid, ask, contract_size) = (self.bid, self.ask, self.settings.contract_size);
+        for pos in &mut self.positions {
+            pos.profit = position_profit(pos.side, pos.volume, pos.open_price, bid, ask, contract_size);
+        }
+    }
+
+    /// Executes one EA request against the simulated account.
+    /// `download_history` is not a trading action and is rejected here.
+    pub fn execute(&mut self, request: &Request) -> OrderResponse {
+        if self.bid <= 0.0 && !matches!(request, Request::GetPositions { .. }) {
+            return reject(TradeError::NoQuotes);
+        }
+
+        match request {
+            Request::MarketBuy { volume, sl, tp, .. } => self.market(Side::Buy, *volume, *sl, *tp),
+            Request::MarketSell { volume, sl, tp, .. } => self.market(Side::Sell, *volume, *sl, *tp),
+            Request::LimitBuy { volume, price, sl, tp, .. } => {
+                self.pending(Side::Buy, PendingKind::Limit, *volume, *price, *sl, *tp)
+            }
+            Request::LimitSell { volume, price, sl, tp, .. } => {
+                self.pending(Side::Sell, PendingKind::Limit, *volume, *price, *sl, *tp)
+            }
+            Request::StopBuy { volume, price, sl, tp, .. } => {
+                self.pending(Side::Buy, PendingKind::Stop, *volume, *price, *sl, *tp)
+            }
+            Request::StopSell { volume, price, sl, tp, .. } => {
+                self.pending(Side::Sell, PendingKind::Stop, *volume, *price, *sl, *tp)
+            }
+            Request::ClosePosition { ticket, .. } => match self.close_position(*ticket) {
+                Some(_) => success(*ticket),
+                None => reject(TradeError::PositionClosed),
+            },
+            Request::CancelOrder { ticket, .. } => {
+                let before = self.orders.len();
+                self.orders.retain(|o| o.ticket != *ticket);
+                if self.orders.len() < before {
+                    success(*ticket)
+                } else {
+                    reject(TradeError::InvalidRequest)
+                }
+            }
+            Request::ClosePartial { ticket, volume, .. } => self.close_partial(*ticket, *volume),
+            Request::CloseAll { symbol, filter } => {
+                let covered = self.covers(symbol);
+                let tickets: Vec<u64> = self.positions
+                    .iter()
+                    .filter(|p| covered && filter.matches(p.profit))
+                    .map(|p| p.ticket)
+                    .collect();
+                let results = tickets
+                    .into_iter()
+                    .map(|ticket| match self.close_position(ticket) {
+                        Some(_) => TicketResult::ok(ticket),
+                        None => TicketResult::failed(ticket, TradeError::PositionClosed),
+                    })
+                    .collect();
+                bulk_response(results)
+            }
+            Request::CancelAll { symbol } => {
+                let results = match self.covers(symbol) {
+                    true => self.orders.drain(..).map(|o| TicketResult::ok(o.ticket)).collect(),
+                    false => Vec::new(),
+                };
+                bulk_response(results)
+            }
+            Request::Reverse { ticket, .. } => self.reverse(*ticket),
+            Request::ModifyPosition { ticket, sl, tp, .. } => self.modify_position(*ticket, *sl, *tp),
+            Request::ModifyOrder { ticket, price, sl, tp, expiration, .. } => {
+                self.modify_order(*ticket, *price, *sl, *tp, *expiration)
+            }
+            Request::GetPositions { .. } => {
+                let mut response = success(0);
+                response.ticket = None;
+                response.positions = Some(self.positions_data());
+                response.orders = Some(self.orders_data());
+                response
+            }
+            Request::DownloadHistory { .. } => {
+                OrderResponse::failure("Unknown order type: download_history".to_string())
+            }
+        }
+    }
+
+    /// Whether a bulk action on `symbol` reaches this broker's one symbol.
+    fn covers(&self, symbol: &str) -> bool {
+        symbol == ALL_SYMBOLS || symbol == self.settings.symbol
+    }
+
+    fn market(&mut self, side: Side, volume: f64, sl: f64, tp: f64) -> OrderResponse {
+        if let Err(e) = self.check_volume(volume) {
+            return reject(e);
+        }
+        let price = match side {
+            Side::Buy => self.ask,
+            Side::Sell => self.bid,
+        };
+        if check_stops(side, price, sl, tp).is_err() {
+            return reject(TradeError::InvalidStops);
+        }
+        if self.required_margin(volume, price) > self.free_margin() {
+            return reject(TradeError::NoMoney);
+        }
+        let ticket = self.take_ticket();
+        self.open_position(ticket, side, volume, sl, tp);
+        let mut response = success(ticket);
+        // Fill price, after slippage
+        response.price = self.positions.last().map(|p| p.open_price);
+        response.time = Some(self.time);
+        response
+    }
+
+    fn pending(&mut self, side: Side, kind: PendingKind, volume: f64, price: f64, sl: f64, tp: f64) -> OrderResponse {
+        if let Err(e) = self.check_volume(volume) {
+            return reject(e);
+        }
+        if !self.pending_price_valid(side, kind, price) {
+            return reject(TradeError::InvalidPrice);
+        }
+        if check_stops(side, price, sl, tp).is_err() {
+            return reject(TradeError::InvalidStops);
+        }
+        if self.required_margin(volume, price) > self.free_margin() {
+            return reject(TradeError::NoMoney);
+        }
+        let ticket = self.take_ticket();
+        self.orders.push(SimOrder { ticket, side, kind, volume, price, sl, tp, expiration: 0 });
+        success(ticket)
+    }
+
+    // Limits must sit on the better side of the market, stops beyond it
+    fn pending_price_valid(&self, side: Side, kind: PendingKind, price: f64) -> bool {
+        match (side, kind) {
+            (Side::Buy, PendingKind::Limit) => price > 0.0 && price < self.ask,
+            (Side::Sell, PendingKind::Limit) => price > self.bid,
+            (Side::Buy, PendingKind::Stop) => price > self.ask,
+            (Side::Sell, PendingKind::Stop) => price > 0.0 && price < self.bid,
+        }
+    }
+
+    /// Stops of an open position are checked against its closing price.
+    fn modify_position(&mut self, ticket: u64, sl: f64, tp: f64) -> OrderResponse {
+        let (bid, ask) = (self.bid, self.ask);
+        let Some(pos) = self.positions.iter_mut().find(|p| p.ticket == ticket) else {
+            return reject(TradeError::PositionClosed);
+        };
+        let close = if pos.side == Side::Buy { bid } else { ask };
+        if check_stops(pos.side, close, sl, tp).is_err() {
+            return reject(TradeError::InvalidStops);
+        }
+        pos.sl = sl;
+        pos.tp = tp;
+        success(ticket)
+    }
+
+    fn modify_order(&mut self, ticket: u64, price: f64, sl: f64, tp: f64, expiration: i64) -> OrderResponse {
+        let Some(idx) = self.orders.iter().position(|o| o.ticket == ticket) else {
+            return reject(TradeError::InvalidRequest);
+        };
+        let (side, kind) = (self.orders[idx].side, self.orders[idx].kind);
+        if !self.pending_price_valid(side, kind, price) {
+            return reject(TradeError::InvalidPrice);
+        }
+        if check_stops(side, price, sl, tp).is_err() {
+            return reject(TradeError::InvalidStops);
+        }
+        if expiration != 0 && expiration <= self.time {
+            return reject(TradeError::InvalidExpiration);
+        }
+        let order = &mut self.orders[idx];
+        order.price = price;
+        order.sl = sl;
+        order.tp = tp;
+        order.expiration = expiration;
+        success(ticket)
+    }
+
+    /// Closes a whole position at the current price.
+    pub fn close_position(&mut self, ticket: u64) -> Option<ClosedTrade> {
+        self.close_with_reason(ticket, CloseReason::Manual)
+    }
+
+    fn close_with_reason(&mut self, ticket: u64, reason: CloseReason) -> Option<ClosedTrade> {
+        let idx = self.positions.iter().position(|p| p.ticket == ticket)?;
+        let pos = self.positions.remove(idx);
+        Some(self.book_close(&pos, pos.volume, reason))
+    }
+
+    /// Closes `volume` lots of a position; the whole volume closes it.
+    fn close_partial(&mut self, ticket: u64, volume: f64) -> OrderResponse {
+        let Some(idx) = self.positions.iter().position(|p| p.ticket == ticket) else {
+            return reject(TradeError::PositionClosed);
+        };
+        if let Err(e) = self.check_volume(volume) {
+            return reject(e);
+        }
+        let pos = self.positions[idx].clone();
+        if volume >= pos.volume - 1e-9 {
+            self.close_position(ticket);
+            return success(ticket);
+        }
+        let step = self.settings.lot_step;
+        let remaining = ((pos.volume - volume) / step).round() * step;
+        if remaining < self.settings.min_lot - 1e-9 {
+            return reject(TradeError::InvalidCloseVolume);
+        }
+        self.book_close(&pos, volume, CloseReason::Manual);
+        let (bid, ask, contract_size) = (self.bid, self.ask, self.settings.contract_size);
+        let pos = &mut self.positions[idx];
+        pos.volume = remaining;
+        pos.profit = position_profit(pos.side, remaining, pos.open_price, bid, ask, contract_size);
+        success(ticket)
+    }
+
+    /// Closes the position and opens its volume on the other side, without SL/TP.
+    fn reverse(&mut self, ticket: u64) -> OrderResponse {
+        let Some(pos) = self.positions.iter().find(|p| p.ticket == ticket).cloned() else {
+            return reject(TradeError::PositionClosed);
+        };
+        self.close_position(ticket);
+        let mut response = self.market(pos.side.opposite(), pos.volume, 0.0, 0.0);
+        let opened = match response.ticket {
+            Some(new_ticket) => TicketResult::ok(new_ticket as u64),
+            None => TicketResult::failed(0, response.trade_error().unwrap_or(TradeError::InvalidRequest)),
+        };
+        response.results = Some(vec![TicketResult::ok(ticket), opened]);
+        response
+    }
+
+    /// Books the P&L of closing `volume` lots of `pos` at the current price.
+    fn book_close(&mut self, pos: &SimPosition, volume: f64, reason: CloseReason) -> ClosedTrade {
+        let close_price = match pos.side {
+            Side::Buy => self.bid - self.settings.slippage,
+            Side::Sell => self.ask + self.settings.slippage,
+        };
+        let profit = position_profit(pos.side, volume, pos.open_price, close_price, close_price, self.settings.contract_size);
+        let commission = self.settings.commission_per_lot * volume;
+        self.balance += profit - commission;
+        let trade = ClosedTrade {
+            ticket: pos.ticket,
+            side: pos.side,
+            volume,
+            open_price: pos.open_price,
+            close_price,
+            initial_sl: pos.initial_sl,
+            open_time: pos.open_time,
+            close_time: self.time,
+            profit,
+            commission,
+            reason,
+        };
+        self.closed.push(trade.clone());
+        trade
+    }
+
+    fn open_position(&mut self, ticket: u64, side: Side, volume: f64, sl: f64, tp: f64) {
+        let open_price = match side {
+            Side::Buy => self.ask + self.settings.slippage,
+            Side::Sell => self.bid - self.settings.slippage,
+        };
+        self.positions.push(SimPosition {
+            ticket,
+            side,
+            volume,
+            open_price,
+            open_time: self.time,
+            profit: position_profit(side, volume, open_price, self.bid, self.ask, self.settings.contract_size),
+            sl,
+            tp,
+            initial_sl: sl,
+        });
+    }
+
+    fn check_volume(&self, volume: f64) -> Result<(), TradeError> {
+        let s = &self.settings;
+        let steps = volume / s.lot_step;
+        if !volume.is_finite() || volume < s.min_lot - 1e-9 || volume > s.max_lot + 1e-9 || (steps - steps.round()).abs() > 1e-6 {
+            return Err(TradeError::InvalidVolume);
+        }
+        Ok(())
+    }
+
+    fn take_ticket(&mut self) -> u64 {
+        self.next_ticket += 1;
+        self.next_ticket
+    }
+
+    fn required_margin(&self, volume: f64, price: f64) -> f64 {
+        volume * self.settings.contract_size * price / self.settings.leverage
+    }
+
+    // ------------------------------------------------------------------------
+    // Account
+    // ------------------------------------------------------------------------
+
+    pub fn balance(&self) -> f64 {
+        self.balance
+    }
+
+    pub fn equity(&self) -> f64 {
+        self.balance + self.positions.iter().map(|p| p.profit).sum::<f64>()
+    }
+
+    pub fn margin(&self) -> f64 {
+        self.positions.iter().map(|p| self.required_margin(p.volume, p.open_price)).sum()
+    }
+
+    pub fn free_margin(&self) -> f64 {
+        self.equity() - self.margin()
+    }
+
+    pub fn positions(&self) -> &[SimPosition] {
+        &self.positions
+    }
+
+    pub fn orders(&self) -> &[SimOrder] {
+        &self.orders
+    }
+
+    pub fn closed_trades(&self) -> &[ClosedTrade] {
+        &self.closed
+    }
+
+    pub fn positions_data(&self) -> Vec<PositionData> {
+        self.positions
+            .iter()
+            .map(|p| PositionData {
+                ticket: p.ticket,
+                pos_type: p.side.as_str().to_string(),
+                volume: p.volume,
+                price: p.open_price,
+                profit: p.profit,
+                sl: p.sl,
+                tp: p.tp,
+            })
+            .collect()
+    }
+
+    pub fn orders_data(&self) -> Vec<PendingOrderData> {
+        self.orders
+            .iter()
+            .map(|o| PendingOrderData {
+                ticket: o.ticket,
+                order_type: format!(
+                    "{} {}",
+                    o.side.as_str(),
+                    if o.kind == PendingKind::Limit { "LIMIT" } else { "STOP" }
+                ),
+                volume: o.volume,
+                price: o.price,
+                sl: o.sl,
+                tp: o.tp,
+                expiration: o.expiration,
+            })
+            .collect()
+    }
+
+    /// The tick the EA would publish for the current state.
+    pub fn tick_data(&self, volume: u64) -> TickData {
+        TickData {
+            symbol: self.settings.symbol.clone(),
+            bid: self.bid,
+            ask: self.ask,
+            time: self.time,
+            volume,
+            server_offset: 0,
+            balance: self.balance,
+            equity: self.equity(),
+            margin: self.margin(),
+            free_margin: self.free_margin(),
+            min_lot: self.settings.min_lot,
+            max_lot: self.settings.max_lot,
+            lot_step: self.settings.lot_step,
+            point: self.settings.point,
+            // Profit is in the quote currency, taken as the account currency
+            tick_value: self.settings.point * self.settings.contract_size,
+            tick_size: self.settings.point,
+            contract_size: self.settings.contract_size,
+            positions: self.positions_data(),
+            orders: self.orders_data(),
+        }
+    }
+}
+
+/// Floating P&L, valued at the price the position would close at.
+fn position_profit(side: Side, volume: f64, open_price: f64, bid: f64, ask: f64, contract_size: f64) -> f64 {
+    match side {
+        Side::Buy => (bid - open_price) * volume * contract_size,
+        Side::Sell => (open_price - ask) * volume * contract_size,
+    }
+}
+
+fn success(ticket: u64) -> OrderResponse {
+    OrderResponse {
+        success: true,
+        ticket: Some(ticket as i64),
+        error: None,
+        ..OrderResponse::failure(String::new())
+    }
+}
+
+/// Reply to a bulk action: fails when any ticket failed, and lists them all.
+fn bulk_response(results: Vec<TicketResult>) -> OrderResponse {
+    let failed = results.iter().filter(|r| !r.success).count();
+    let mut response = match failed {
+        0 => success(0),
+        _ => OrderResponse::failure(format!("{} of {} tickets failed", failed, results.len())),
+    };
+    response.ticket = None;
+    response.results = Some(results);
+    response
+}
+
+/// Failure in the EA's format: numeric retcode plus "Error N: text".
+pub fn reject(error: TradeError) -> OrderResponse {
+    let mut response = OrderResponse::failure(format!("Error {}: {}", error.code(), error.description()));
+    response.retcode = Some(error.code());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CloseFilter;
+
+    const SYMBOL: &str = "XAUUSDc";
+
+    // 10k balance, 100 oz lots, 1:100, so one lot at 2000 needs 2000 margin
+    fn broker() -> SimBroker {
+        let mut broker = SimBroker::new(SimSettings::default());
+        broker.update_price(2000.0, 2000.5, 1_000);
+        broker
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+    }
+
+    fn buy(volume: f64, sl: f64, tp: f64) -> Request {
+        Request::MarketBuy { symbol: SYMBOL.to_string(), volume, sl, tp }
+    }
+
+    fn sell(volume: f64, sl: f64, tp: f64) -> Request {
+        Request::MarketSell { symbol: SYMBOL.to_string(), volume, sl, tp }
+    }
+
+    #[test]
+    fn market_order_fills_at_the_quote() {
+        let mut broker = broker();
+        let response = broker.execute(&buy(1.0, 0.0, 0.0));
+        assert!(response.success);
+        assert_eq!(response.price, Some(2000.5));
+        assert_eq!(response.time, Some(1_000));
+
+        broker.update_price(2001.0, 2001.5, 1_001);
+        assert_close(broker.positions()[0].profit, 50.0);
+        assert_close(broker.equity(), 10_050.0);
+        assert_close(broker.margin(), 2000.5);
+    }
+
+    #[test]
+    fn market_order_checks_volume_and_margin() {
+        let mut broker = broker();
+        assert_eq!(broker.execute(&buy(0.015, 0.0, 0.0)).retcode, Some(10014));
+        assert_eq!(broker.execute(&buy(6.0, 0.0, 0.0)).retcode, Some(10019));
+        assert_eq!(broker.execute(&buy(1.0, 2001.0, 0.0)).retcode, Some(10016));
+        assert!(broker.positions().is_empty());
+    }
+
+    #[test]
+    fn pending_order_triggers_when_crossed() {
+        let mut broker = broker();
+        let request = Request::LimitBuy { symbol: SYMBOL.to_string(), volume: 1.0, price: 1999.0, sl: 0.0, tp: 0.0 };
+        let ticket = broker.execute(&request).ticket.unwrap() as u64;
+
+        assert!(broker.update_price(1999.0, 1999.5, 1_001).is_empty());
+        assert_eq!(broker.update_price(1998.5, 1999.0, 1_002), vec![(ticket, Ok(()))]);
+        assert!(broker.orders().is_empty());
+        assert_eq!(broker.positions()[0].ticket, ticket);
+        assert_close(broker.positions()[0].open_price, 1999.0);
+    }
+
+    #[test]
+    fn pending_order_checks_margin() {
+        let mut broker = broker();
+        let stop = |volume| Request::StopBuy { symbol: SYMBOL.to_string(), volume, price: 2010.0, sl: 0.0, tp: 0.0 };
+        assert_eq!(broker.execute(&stop(5.0)).retcode, Some(10019));
+        let ticket = broker.execute(&stop(4.0)).ticket.unwrap() as u64;
+
+        // A losing short eats the free margin the stop needs when it triggers
+        assert!(broker.execute(&sell(1.0, 0.0, 0.0)).success);
+        let triggered = broker.update_price(2010.0, 2010.5, 1_001);
+        assert_eq!(triggered, vec![(ticket, Err(TradeError::NoMoney))]);
+        assert!(broker.orders().is_empty());
+        assert_eq!(broker.positions().len(), 1);
+    }
+
+    #[test]
+    fn stop_loss_and_take_profit_close_positions() {
+        let mut broker = broker();
+        broker.execute(&buy(1.0, 1995.0, 2010.0));
+        broker.execute(&sell(1.0, 2010.0, 1990.0));
+
+        broker.update_price(1995.0, 1995.5, 1_001);
+        let [stop_loss] = broker.closed_trades() else { panic!("expected one close") };
+        assert_eq!(stop_loss.reason, CloseReason::StopLoss);
+        assert_close(stop_loss.close_price, 1995.0);
+        assert_close(stop_loss.profit, -550.0);
+
+        broker.update_price(1989.5, 1990.0, 1_002);
+        let take_profit = &broker.closed_trades()[1];
+        assert_eq!(take_profit.reason, CloseReason::TakeProfit);
+        assert_close(take_profit.profit, 1000.0);
+        assert!(broker.positions().is_empty());
+        assert_close(broker.balance(), 10_450.0);
+    }
+
+    #[test]
+    fn partial_close_keeps_the_rest_open() {
+        let mut broker = broker();
+        let ticket = broker.execute(&buy(1.0, 0.0, 0.0)).ticket.unwrap() as u64;
+        broker.update_price(2001.0, 2001.5, 1_001);
+
+        let partial = |volume| Request::ClosePartial { symbol: SYMBOL.to_string(), ticket, volume };
+        assert!(broker.execute(&partial(0.3)).success);
+        assert_close(broker.closed_trades()[0].volume, 0.3);
+        assert_close(broker.closed_trades()[0].profit, 15.0);
+        assert_close(broker.positions()[0].volume, 0.7);
+        assert_close(broker.balance(), 10_015.0);
+
+        assert_eq!(broker.execute(&partial(0.695)).retcode, Some(10014));
+        assert!(broker.execute(&partial(0.7)).success);
+        assert!(broker.positions().is_empty());
+    }
+
+    #[test]
+    fn reverse_opens_the_other_side() {
+        let mut broker = broker();
+        let ticket = broker.execute(&buy(0.5, 1990.0, 0.0)).ticket.unwrap() as u64;
+        let response = broker.execute(&Request::Reverse { symbol: SYMBOL.to_string(), ticket });
+        assert!(response.success);
+
+        let [reversed] = broker.positions() else { panic!("expected one position") };
+        assert_eq!(reversed.side, Side::Sell);
+        assert_close(reversed.volume, 0.5);
+        assert_eq!(reversed.sl, 0.0);
+        let results = response.results.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].ticket, ticket);
+        assert_eq!(results[1].ticket, reversed.ticket);
+    }
+
+    #[test]
+    fn bulk_actions_match_the_symbol() {
+        let mut broker = broker();
+        broker.execute(&buy(0.1, 0.0, 0.0));
+        let limit = Request::LimitBuy { symbol: SYMBOL.to_string(), volume: 0.1, price: 1990.0, sl: 0.0, tp: 0.0 };
+        broker.execute(&limit);
+
+        let other = "EURUSD".to_string();
+        broker.execute(&Request::CloseAll { symbol: other.clone(), filter: CloseFilter::All });
+        broker.execute(&Request::CancelAll { symbol: other });
+        assert_eq!((broker.positions().len(), broker.orders().len()), (1, 1));
+
+        broker.execute(&Request::CloseAll { symbol: ALL_SYMBOLS.to_string(), filter: CloseFilter::All });
+        broker.execute(&Request::CancelAll { symbol: ALL_SYMBOLS.to_string() });
+        assert_eq!((broker.positions().len(), broker.orders().len()), (0, 0));
+    }
+
+    #[test]
+    fn pending_order_expires() {
+        let mut broker = broker();
+        let request = Request::LimitSell { symbol: SYMBOL.to_string(), volume: 1.0, price: 2005.0, sl: 0.0, tp: 0.0 };
+        let ticket = broker.execute(&request).ticket.unwrap() as u64;
+        let modify = |expiration| Request::ModifyOrder {
+            symbol: SYMBOL.to_string(),
+            ticket,
+            price: 2005.0,
+            sl: 0.0,
+            tp: 0.0,
+            expiration,
+        };
+        assert_eq!(broker.execute(&modify(1_000)).retcode, Some(10022));
+        assert!(broker.execute(&modify(1_060)).success);
+
+        broker.update_price(2000.0, 2000.5, 1_059);
+        assert_eq!(broker.orders().len(), 1);
+        broker.update_price(2000.0, 2000.5, 1_060);
+        assert!(broker.orders().is_empty());
+    }
+}
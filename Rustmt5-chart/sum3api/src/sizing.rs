@@ -0,0 +1,185 @@
+//+------------------------------------------------------------------+
+//|                                                        sizing.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+//! Position sizing from a risk budget: how many lots lose a given amount
+//! of money if the stop-loss is hit.
+
+use crate::TickData;
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Symbol Specification
+// ============================================================================
+
+/// What one lot of the symbol is worth, as reported by the EA.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SymbolSpec {
+    /// Account-currency value of a `tick_size` move for one lot
+    /// (SYMBOL_TRADE_TICK_VALUE), 0 when unknown
+    pub tick_value: f64,
+    /// Smallest price change (SYMBOL_TRADE_TICK_SIZE), 0 when unknown
+    pub tick_size: f64,
+    /// Units per lot (SYMBOL_TRADE_CONTRACT_SIZE)
+    pub contract_size: f64,
+    pub min_lot: f64,
+    pub max_lot: f64,
+    pub lot_step: f64,
+}
+
+impl SymbolSpec {
+    pub fn from_tick(tick: &TickData) -> Self {
+        Self {
+            tick_value: tick.tick_value,
+            tick_size: tick.tick_size,
+            contract_size: tick.contract_size,
+            min_lot: tick.min_lot,
+            max_lot: tick.max_lot,
+            lot_step: tick.lot_step,
+        }
+    }
+
+    /// Money lost by one lot over `distance` of price. Falls back to the
+    /// contract size (profit currency = account currency) when the EA did
+    /// not send a tick value.
+    pub fn loss_per_lot(&self, distance: f64) -> Option<f64> {
+        let loss = if self.tick_value > 0.0 && self.tick_size > 0.0 {
+            distance / self.tick_size * self.tick_value
+        } else if self.contract_size > 0.0 {
+            distance * self.contract_size
+        } else {
+            return None;
+        };
+        (loss > 0.0).then_some(loss)
+    }
+
+    /// Rounds down to the lot step, so the rounded size never risks more
+    /// than asked.
+    pub fn floor_lots(&self, lots: f64) -> f64 {
+        if self.lot_step <= 0.0 {
+            return lots;
+        }
+        // The epsilon keeps 0.3 / 0.01 = 29.999... from losing a step
+        let steps = (lots / self.lot_step + 1e-9).floor();
+        (steps * self.lot_step * 1e8).round() / 1e8
+    }
+}
+
+// ============================================================================
+// Risk Sizing
+// ============================================================================
+
+/// Which account figure a risk percentage is taken of.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskBase {
+    #[default]
+    Balance,
+    Equity,
+}
+
+/// Why the computed size differs from the exact risk budget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SizeLimit {
+    /// The budget buys less than the minimum lot; `lots` is the minimum
+    /// and risks more than asked
+    BelowMinLot,
+    /// Capped at the maximum lot; risks less than asked
+    AboveMaxLot,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PositionSize {
+    pub lots: f64,
+    /// Money lost at the stop with `lots`
+    pub risk: f64,
+    /// Money the caller asked to risk
+    pub budget: f64,
+    pub limit: Option<SizeLimit>,
+}
+
+/// Lots that lose about `budget` when price moves `stop_distance` against
+/// the position, rounded to the symbol's lot rules.
+pub fn size_for_risk(budget: f64, stop_distance: f64, spec: &SymbolSpec) -> Result<PositionSize, String> {
+    if !budget.is_finite() || budget <= 0.0 {
+        return Err(format!("risk amount must be positive, got {:.2}", budget));
+    }
+    if !stop_distance.is_finite() || stop_distance <= 0.0 {
+        return Err("a stop-loss is needed to size by risk".to_string());
+    }
+    let per_lot = spec
+        .loss_per_lot(stop_distance)
+        .ok_or("symbol tick value / contract size unknown (no tick from the EA yet)")?;
+
+    let exact = spec.floor_lots(budget / per_lot);
+    let (lots, limit) = if exact < spec.min_lot {
+        (spec.min_lot, Some(SizeLimit::BelowMinLot))
+    } else if spec.max_lot > 0.0 && exact > spec.max_lot {
+        (spec.max_lot, Some(SizeLimit::AboveMaxLot))
+    } else {
+        (exact, None)
+    };
+    Ok(PositionSize { lots, risk: lots * per_lot, budget, limit })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // XAUUSD-like: 0.001 move of one 100 oz lot is worth 0.1
+    fn gold() -> SymbolSpec {
+        SymbolSpec { tick_value: 0.1, tick_size: 0.001, contract_size: 100.0, min_lot: 0.01, max_lot: 10.0, lot_step: 0.01 }
+    }
+
+    #[test]
+    fn floors_to_the_lot_step() {
+        let spec = gold();
+        assert_eq!(spec.floor_lots(0.3), 0.3);
+        assert_eq!(spec.floor_lots(0.29999), 0.29);
+        assert_eq!(spec.floor_lots(1.019), 1.01);
+        assert_eq!(SymbolSpec { lot_step: 0.0, ..spec }.floor_lots(0.123), 0.123);
+    }
+
+    #[test]
+    fn loss_per_lot_falls_back_to_contract_size() {
+        assert_eq!(gold().loss_per_lot(5.0), Some(500.0));
+        let no_tick_value = SymbolSpec { tick_value: 0.0, tick_size: 0.0, ..gold() };
+        assert_eq!(no_tick_value.loss_per_lot(5.0), Some(500.0));
+        let unknown = SymbolSpec { contract_size: 0.0, ..no_tick_value };
+        assert_eq!(unknown.loss_per_lot(5.0), None);
+        assert!(size_for_risk(100.0, 5.0, &unknown).is_err());
+    }
+
+    #[test]
+    fn sizes_to_the_budget() {
+        let size = size_for_risk(150.0, 5.0, &gold()).unwrap();
+        assert_eq!(size.lots, 0.3);
+        assert!((size.risk - 150.0).abs() < 1e-9);
+        assert_eq!(size.limit, None);
+
+        // 0.499 lots round down, never over the budget
+        let size = size_for_risk(249.5, 5.0, &gold()).unwrap();
+        assert_eq!(size.lots, 0.49);
+        assert!(size.risk <= size.budget);
+    }
+
+    #[test]
+    fn clamps_to_lot_limits() {
+        let below = size_for_risk(1.0, 5.0, &gold()).unwrap();
+        assert_eq!((below.lots, below.limit), (0.01, Some(SizeLimit::BelowMinLot)));
+        assert!(below.risk > below.budget);
+
+        let above = size_for_risk(10_000.0, 0.5, &gold()).unwrap();
+        assert_eq!((above.lots, above.limit), (10.0, Some(SizeLimit::AboveMaxLot)));
+        assert!((above.risk - 500.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_bad_inputs() {
+        assert!(size_for_risk(100.0, 0.0, &gold()).is_err());
+        assert!(size_for_risk(100.0, f64::NAN, &gold()).is_err());
+        assert!(size_for_risk(0.0, 5.0, &gold()).is_err());
+    }
+}
@@ -0,0 +1,582 @@
+//+------------------------------------------------------------------+
+//|                                                      strategy.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+//! Automated trading. A [`Strategy`] reacts to market and account events
+//! with [`OrderIntent`]s; a [`StrategyRuntime`] hosts several of them and
+//! turns their intents into [`Request`]s for the host to send.
+
+use crate::{CloseFilter, OrderResponse, PendingOrderData, PositionData, Request, Side, TickData, TradeError};
+use crate::indicators::Sma;
+
+/// Seconds between `on_timer` calls.
+pub const TIMER_SECS: i64 = 1;
+
+// ============================================================================
+// Intents
+// ============================================================================
+
+/// What a strategy wants done, on the runtime's symbol.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OrderIntent {
+    Market { side: Side, volume: f64, sl: f64, tp: f64 },
+    Limit { side: Side, volume: f64, price: f64, sl: f64, tp: f64 },
+    Stop { side: Side, volume: f64, price: f64, sl: f64, tp: f64 },
+    Close { ticket: u64 },
+    ClosePartial { ticket: u64, volume: f64 },
+    CloseAll { filter: CloseFilter },
+    Cancel { ticket: u64 },
+    CancelAll,
+    Modify { ticket: u64, sl: f64, tp: f64 },
+}
+
+impl OrderIntent {
+    pub fn into_request(self, symbol: &str) -> Request {
+        let symbol = symbol.to_string();
+        match self {
+            OrderIntent::Market { side: Side::Buy, volume, sl, tp } => Request::MarketBuy { symbol, volume, sl, tp },
+            OrderIntent::Market { side: Side::Sell, volume, sl, tp } => Request::MarketSell { symbol, volume, sl, tp },
+            OrderIntent::Limit { side: Side::Buy, volume, price, sl, tp } => {
+                Request::LimitBuy { symbol, volume, price, sl, tp }
+            }
+            OrderIntent::Limit { side: Side::Sell, volume, price, sl, tp } => {
+                Request::LimitSell { symbol, volume, price, sl, tp }
+            }
+            OrderIntent::Stop { side: Side::Buy, volume, price, sl, tp } => {
+                Request::StopBuy { symbol, volume, price, sl, tp }
+            }
+            OrderIntent::Stop { side: Side::Sell, volume, price, sl, tp } => {
+                Request::StopSell { symbol, volume, price, sl, tp }
+            }
+            OrderIntent::Close { ticket } => Request::ClosePosition { symbol, ticket },
+            OrderIntent::ClosePartial { ticket, volume } => Request::ClosePartial { symbol, ticket, volume },
+            OrderIntent::CloseAll { filter } => Request::CloseAll { symbol, filter },
+            OrderIntent::Cancel { ticket } => Request::CancelOrder { symbol, ticket },
+            OrderIntent::CancelAll => Request::CancelAll { symbol },
+            OrderIntent::Modify { ticket, sl, tp } => Request::ModifyPosition { symbol, ticket, sl, tp },
+        }
+    }
+}
+
+// ============================================================================
+// Strategy Trait
+// ============================================================================
+
+/// An automated trading strategy. Every callback returns the orders it
+/// wants sent; the default callbacks do nothing.
+pub trait Strategy: Send {
+    fn name(&self) -> &str;
+
+    fn on_tick(&mut self, tick: &TickData) -> Vec<OrderIntent>;
+
+    /// The reply to one of this strategy's own requests.
+    fn on_order_response(&mut self, _request: &Request, _response: &OrderResponse) -> Vec<OrderIntent> {
+        Vec::new()
+    }
+
+    /// Positions or pending orders changed.
+    fn on_position_update(&mut self, _positions: &[PositionData], _orders: &[PendingOrderData]) -> Vec<OrderIntent> {
+        Vec::new()
+    }
+
+    /// Called every [`TIMER_SECS`] with the current time.
+    fn on_timer(&mut self, _time: i64) -> Vec<OrderIntent> {
+        Vec::new()
+    }
+
+    /// One line of state for the UI.
+    fn status(&self) -> String {
+        String::new()
+    }
+}
+
+// ============================================================================
+// Runtime
+// ============================================================================
+
+/// A hosted strategy and what the runtime knows about it.
+pub struct StrategySlot {
+    pub strategy: Box<dyn Strategy>,
+    pub enabled: bool,
+    /// Requests handed to the host
+    pub sent: usize,
+    /// Last failed or blocked request
+    pub last_error: Option<String>,
+}
+
+/// Runs strategies on one symbol. Methods return `(slot index, request)`
+/// pairs; the host sends them and routes each reply back with
+/// [`StrategyRuntime::on_order_response`].
+pub struct StrategyRuntime {
+    symbol: String,
+    slots: Vec<StrategySlot>,
+    last_timer: i64,
+    /// Tickets, volumes and levels at the last position update
+    trades: Vec<(u64, f64, f64, f64, f64)>,
+}
+
+impl StrategyRuntime {
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            slots: Vec::new(),
+            last_timer: 0,
+            trades: Vec::new(),
+        }
+    }
+
+    /// Adds a strategy, disabled.
+    pub fn add(&mut self, strategy: Box<dyn Strategy>) {
+        self.slots.push(StrategySlot { strategy, enabled: false, sent: 0, last_error: None });
+    }
+
+    pub fn slots(&self) -> &[StrategySlot] {
+        &self.slots
+    }
+
+    /// Enabling also makes the next position update reach every strategy,
+    /// so the new one learns what is open.
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        if let Some(slot) = self.slots.get_mut(index) {
+            slot.enabled = enabled;
+            slot.last_error = None;
+            self.trades.clear();
+        }
+    }
+
+    pub fn set_symbol(&mut self, symbol: &str) {
+        self.symbol = symbol.to_string();
+    }
+
+    /// A request the host refused to send (e.g. a risk breach) reaches the
+    /// strategy as a failed reply; what it asks for in return is dropped.
+    pub fn report_blocked(&mut self, index: usize, request: &Request, reason: String) {
+        if let Some(slot) = self.slots.get_mut(index) {
+            slot.sent = slot.sent.saturating_sub(1);
+            slot.strategy.on_order_response(request, &OrderResponse::failure(reason.clone()));
+            slot.last_error = Some(reason);
+        }
+    }
+
+    pub fn on_tick(&mut self, tick: &TickData) -> Vec<(usize, Request)> {
+        self.dispatch(|s| s.on_tick(tick))
+    }
+
+    /// Dispatches only when a position or order was opened, closed or
+    /// changed since the last call; floating profit alone does not count.
+    pub fn on_position_update(&mut self, positions: &[PositionData], orders: &[PendingOrderData]) -> Vec<(usize, Request)> {
+        let trades: Vec<_> = positions
+            .iter()
+            .map(|p| (p.ticket, p.volume, p.price, p.sl, p.tp))
+            .chain(orders.iter().map(|o| (o.ticket, o.volume, o.price, o.sl, o.tp)))
+            .collect();
+        if trades == self.trades {
+            return Vec::new();
+        }
+        self.trades = trades;
+        self.dispatch(|s| s.on_position_update(positions, orders))
+    }
+
+    /// Fires `on_timer` once every [`TIMER_SECS`]; call it as often as convenient.
+    pub fn on_timer(&mut self, time: i64) -> Vec<(usize, Request)> {
+        if time < self.last_timer + TIMER_SECS {
+            return Vec::new();
+        }
+        self.last_timer = time;
+        self.dispatch(|s| s.on_timer(time))
+    }
+
+    /// Replies reach the strategy that sent the request, even if it has
+    /// been disabled since; what it asks for then is dropped.
+    pub fn on_order_response(&mut self, index: usize, request: &Request, response: &OrderResponse) -> Vec<(usize, Request)> {
+        let Some(slot) = self.slots.get_mut(index) else {
+            return Vec::new();
+        };
+        if !response.success {
+            slot.last_error = Some(format!(
+                "{} failed: {}",
+                request.kind(),
+                response.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        let intents = slot.strategy.on_order_response(request, response);
+        if !slot.enabled {
+            return Vec::new();
+        }
+        slot.sent += intents.len();
+        intents.into_iter().map(|i| (index, i.into_request(&self.symbol))).collect()
+    }
+
+    fn dispatch(&mut self, mut call: impl FnMut(&mut dyn Strategy) -> Vec<OrderIntent>) -> Vec<(usize, Request)> {
+        let mut requests = Vec::new();
+        for (index, slot) in self.slots.iter_mut().enumerate().filter(|(_, s)| s.enabled) {
+            let intents = call(slot.strategy.as_mut());
+            slot.sent += intents.len();
+            requests.extend(intents.into_iter().map(|i| (index, i.into_request(&self.symbol))));
+        }
+        requests
+    }
+}
+
+// ============================================================================
+// Catalog
+// ============================================================================
+
+/// A tunable number of a strategy, with the range worth searching.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub default: f64,
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+    /// Searched by a new optimization; otherwise held at `default` until
+    /// the user widens its range
+    pub search: bool,
+}
+
+/// Builds fresh strategies from parameter values, for backtests.
+#[derive(Clone, Copy, Debug)]
+pub struct StrategyFactory {
+    pub name: &'static str,
+    pub params: &'static [ParamSpec],
+    /// Takes one value per `params` entry, in order
+    pub build: fn(&[f64]) -> Box<dyn Strategy>,
+    /// Whether a parameter set is worth a backtest; the optimizer skips
+    /// the others
+    pub valid: fn(&[f64]) -> bool,
+}
+
+impl StrategyFactory {
+    pub fn defaults(&self) -> Vec<f64> {
+        self.params.iter().map(|p| p.default).collect()
+    }
+}
+
+/// The built-in strategies.
+pub fn catalog() -> Vec<StrategyFactory> {
+    vec![StrategyFactory {
+        name: "SMA cross",
+        params: &[
+            ParamSpec { name: "fast", default: 50.0, min: 5.0, max: 200.0, step: 5.0, search: true },
+            ParamSpec { name: "slow", default: 200.0, min: 20.0, max: 1000.0, step: 20.0, search: true },
+            // Bigger lots only scale profit, so they would always win
+            ParamSpec { name: "volume", default: 0.01, min: 0.01, max: 1.0, step: 0.01, search: false },
+        ],
+        build: |p| Box::new(SmaCross::new(p[0] as usize, p[1] as usize, p[2])),
+        // SmaCross::new raises slow above fast, which would repeat runs
+        valid: |p| p[0] < p[1],
+    }]
+}
+
+// ============================================================================
+// Example Strategy
+// ============================================================================
+
+/// Moving average crossover on tick mid prices: long while the fast
+/// average is above the slow one, short while below. Only positions it
+/// opened itself are closed on a cross; manual trades are left alone.
+pub struct SmaCross {
+    name: String,
+    fast: Sma,
+    slow: Sma,
+    volume: f64,
+    // Ticks seen, for the warm-up status
+    seen: usize,
+    /// Side of the last signal
+    trend: Option<Side>,
+    positions: Vec<PositionData>,
+    /// Tickets of the positions this strategy opened
+    own: Vec<u64>,
+    /// Waiting for the reply to a market order
+    pending: bool,
+}
+
+impl SmaCross {
+    pub fn new(fast: usize, slow: usize, volume: f64) -> Self {
+        let fast = fast.max(1);
+        let slow = slow.max(fast + 1);
+        Self {
+            name: format!("SMA cross {}/{}", fast, slow),
+            fast: Sma::new(fast),
+            slow: Sma::new(slow),
+            volume,
+            seen: 0,
+            trend: None,
+            positions: Vec::new(),
+            own: Vec::new(),
+            pending: false,
+        }
+    }
+}
+
+impl Strategy for SmaCross {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_tick(&mut self, tick: &TickData) -> Vec<OrderIntent> {
+        let mid = (tick.bid + tick.ask) / 2.0;
+        self.seen += 1;
+        let (Some(fast), Some(slow)) = (self.fast.update(mid), self.slow.update(mid)) else {
+            return Vec::new();
+        };
+        if self.pending {
+            return Vec::new();
+        }
+
+        let side = if fast > slow { Side::Buy } else { Side::Sell };
+        if self.trend == Some(side) {
+            return Vec::new();
+        }
+        let first_signal = self.trend.is_none();
+        self.trend = Some(side);
+        if first_signal {
+            return Vec::new(); // Wait for an actual cross
+        }
+
+        let opposite = side.opposite().as_str();
+        let mut intents: Vec<OrderIntent> = self
+            .positions
+            .iter()
+            .filter(|p| p.pos_type == opposite && self.own.contains(&p.ticket))
+            .map(|p| OrderIntent::Close { ticket: p.ticket })
+            .collect();
+        intents.push(OrderIntent::Market { side, volume: self.volume, sl: 0.0, tp: 0.0 });
+        self.pending = true;
+        intents
+    }
+
+    fn on_order_response(&mut self, request: &Request, response: &OrderResponse) -> Vec<OrderIntent> {
+        match request {
+            _ if request.is_market() => {
+                self.pending = false;
+                if let (true, Some(ticket)) = (response.success, response.ticket) {
+                    self.own.push(ticket as u64);
+                }
+            }
+            Request::ClosePosition { ticket, .. }
+                if response.success || response.trade_error() == Some(TradeError::PositionClosed) =>
+            {
+                self.own.retain(|t| t != ticket);
+            }
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    fn on_position_update(&mut self, positions: &[PositionData], _orders: &[PendingOrderData]) -> Vec<OrderIntent> {
+        self.positions = positions.to_vec();
+        Vec::new()
+    }
+
+    fn status(&self) -> String {
+        let (Some(fast), Some(slow)) = (self.fast.value(), self.slow.value()) else {
+            return format!("warming up {}/{}", self.seen, self.slow.period());
+        };
+        let trend = match self.trend {
+            Some(Side::Buy) => "up",
+            Some(Side::Sell) => "down",
+            None => "-",
+        };
+        format!("fast {:.5} slow {:.5} trend {}", fast, slow, trend)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Logs every callback it gets and answers with a fixed intent per event.
+    struct Recorder {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn boxed(name: &'static str, log: &Arc<Mutex<Vec<String>>>) -> Box<dyn Strategy> {
+            Box::new(Recorder { name, log: log.clone() })
+        }
+
+        fn record(&self, event: String) {
+            self.log.lock().unwrap().push(format!("{} {}", self.name, event));
+        }
+    }
+
+    impl Strategy for Recorder {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn on_tick(&mut self, tick: &TickData) -> Vec<OrderIntent> {
+            self.record(format!("tick {}", tick.bid));
+            vec![OrderIntent::Market { side: Side::Buy, volume: 0.1, sl: 0.0, tp: 0.0 }]
+        }
+
+        fn on_order_response(&mut self, request: &Request, response: &OrderResponse) -> Vec<OrderIntent> {
+            self.record(format!("{} {}", request.kind(), if response.success { "ok" } else { "failed" }));
+            vec![OrderIntent::Close { ticket: 1 }]
+        }
+
+        fn on_position_update(&mut self, positions: &[PositionData], _orders: &[PendingOrderData]) -> Vec<OrderIntent> {
+            self.record(format!("positions {}", positions.len()));
+            Vec::new()
+        }
+
+        fn on_timer(&mut self, time: i64) -> Vec<OrderIntent> {
+            self.record(format!("timer {}", time));
+            vec![OrderIntent::CancelAll]
+        }
+    }
+
+    fn runtime(log: &Arc<Mutex<Vec<String>>>) -> StrategyRuntime {
+        let mut runtime = StrategyRuntime::new("XAUUSDc");
+        runtime.add(Recorder::boxed("a", log));
+        runtime.add(Recorder::boxed("b", log));
+        runtime
+    }
+
+    fn take(log: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+        std::mem::take(&mut *log.lock().unwrap())
+    }
+
+    fn buy() -> Request {
+        Request::MarketBuy { symbol: "XAUUSDc".to_string(), volume: 0.1, sl: 0.0, tp: 0.0 }
+    }
+
+    #[test]
+    fn runtime_dispatches_only_to_enabled_strategies() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut runtime = runtime(&log);
+
+        // Strategies start disabled
+        assert!(runtime.on_tick(&tick(1.0)).is_empty());
+        assert!(take(&log).is_empty());
+
+        runtime.set_enabled(1, true);
+        assert_eq!(runtime.on_tick(&tick(2.0)), vec![(1, buy())]);
+        assert_eq!(take(&log), vec!["b tick 2"]);
+
+        runtime.set_enabled(0, true);
+        runtime.set_symbol("EURUSD");
+        let requests = runtime.on_tick(&tick(3.0));
+        assert_eq!(requests.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![0, 1]);
+        assert!(requests.iter().all(|(_, r)| r.symbol() == "EURUSD"));
+        assert_eq!(take(&log), vec!["a tick 3", "b tick 3"]);
+
+        runtime.set_enabled(1, false);
+        assert_eq!(runtime.on_tick(&tick(4.0)).len(), 1);
+        assert_eq!(take(&log), vec!["a tick 4"]);
+        assert_eq!((runtime.slots()[0].sent, runtime.slots()[1].sent), (2, 2));
+    }
+
+    #[test]
+    fn runtime_fires_timer_once_per_interval() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut runtime = runtime(&log);
+        runtime.set_enabled(0, true);
+
+        let cancel = Request::CancelAll { symbol: "XAUUSDc".to_string() };
+        assert_eq!(runtime.on_timer(100), vec![(0, cancel)]);
+        assert!(runtime.on_timer(100 + TIMER_SECS - 1).is_empty());
+        assert_eq!(runtime.on_timer(100 + TIMER_SECS).len(), 1);
+        assert_eq!(take(&log), vec!["a timer 100".to_string(), format!("a timer {}", 100 + TIMER_SECS)]);
+    }
+
+    #[test]
+    fn runtime_skips_unchanged_position_updates() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut runtime = runtime(&log);
+        runtime.set_enabled(0, true);
+
+        let mut position = sell_position(7);
+        runtime.on_position_update(&[position.clone()], &[]);
+        // Floating profit alone is not a change
+        position.profit = 25.0;
+        runtime.on_position_update(&[position.clone()], &[]);
+        position.sl = 1.5;
+        runtime.on_position_update(&[position], &[]);
+        runtime.on_position_update(&[], &[]);
+        assert_eq!(take(&log), vec!["a positions 1", "a positions 1", "a positions 0"]);
+    }
+
+    #[test]
+    fn runtime_routes_replies_to_the_sender() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut runtime = runtime(&log);
+        runtime.set_enabled(0, true);
+        runtime.set_enabled(1, true);
+
+        let ok = OrderResponse { success: true, ..OrderResponse::failure(String::new()) };
+        let close = Request::ClosePosition { symbol: "XAUUSDc".to_string(), ticket: 1 };
+        assert_eq!(runtime.on_order_response(1, &buy(), &ok), vec![(1, close)]);
+        assert_eq!(take(&log), vec!["b market_buy ok"]);
+        assert_eq!(runtime.slots()[1].last_error, None);
+
+        let failed = OrderResponse::failure("no money".to_string());
+        runtime.on_order_response(0, &buy(), &failed);
+        assert_eq!(take(&log), vec!["a market_buy failed"]);
+        assert!(runtime.slots()[0].last_error.as_deref().unwrap().contains("no money"));
+
+        // A disabled strategy still hears its reply but cannot send more
+        runtime.set_enabled(1, false);
+        assert!(runtime.on_order_response(1, &buy(), &ok).is_empty());
+        assert_eq!(take(&log), vec!["b market_buy ok"]);
+
+        assert!(runtime.on_order_response(9, &buy(), &ok).is_empty());
+        assert!(take(&log).is_empty());
+    }
+
+    #[test]
+    fn runtime_reports_blocked_requests_to_the_sender() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut runtime = runtime(&log);
+        runtime.set_enabled(0, true);
+        runtime.set_enabled(1, true);
+        runtime.on_tick(&tick(1.0));
+        take(&log);
+
+        runtime.report_blocked(1, &buy(), "trading is locked by the kill switch".to_string());
+        assert_eq!(take(&log), vec!["b market_buy failed"]);
+        let (a, b) = (&runtime.slots()[0], &runtime.slots()[1]);
+        assert_eq!((a.sent, a.last_error.as_deref()), (1, None));
+        assert_eq!((b.sent, b.last_error.as_deref()), (0, Some("trading is locked by the kill switch")));
+    }
+
+    fn tick(price: f64) -> TickData {
+        TickData { bid: price, ask: price, ..Default::default() }
+    }
+
+    fn sell_position(ticket: u64) -> PositionData {
+        PositionData { ticket, pos_type: "SELL".to_string(), volume: 0.1, price: 1.0, profit: 0.0, sl: 0.0, tp: 0.0 }
+    }
+
+    #[test]
+    fn sma_cross_closes_only_its_own_positions() {
+        let mut strategy = SmaCross::new(2, 3, 0.1);
+        for price in [1.0, 2.0, 3.0] {
+            assert!(strategy.on_tick(&tick(price)).is_empty());
+        }
+
+        // Cross down: open a short
+        let intents = strategy.on_tick(&tick(1.0));
+        assert_eq!(intents, vec![OrderIntent::Market { side: Side::Sell, volume: 0.1, sl: 0.0, tp: 0.0 }]);
+        let request = intents[0].clone().into_request("XAUUSDc");
+        let response = OrderResponse { success: true, ticket: Some(11), error: None, ..OrderResponse::failure(String::new()) };
+        strategy.on_order_response(&request, &response);
+
+        // A manual short (#7) sits next to the strategy's (#11)
+        strategy.on_position_update(&[sell_position(7), sell_position(11)], &[]);
+        let intents = strategy.on_tick(&tick(10.0));
+        assert_eq!(
+            intents,
+            vec![
+                OrderIntent::Close { ticket: 11 },
+                OrderIntent::Market { side: Side::Buy, volume: 0.1, sl: 0.0, tp: 0.0 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,783 @@
+//+------------------------------------------------------------------+
+//|                                                         types.rs |
+//|                                    Copyright 2026, Algorembrant  |
+//|                                                                  |
+//+------------------------------------------------------------------+
+
+use crate::TradeError;
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Tick Feed (PUB 5555)
+// ============================================================================
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PositionData {
+    pub ticket: u64,
+    #[serde(rename = "type")]
+    pub pos_type: String, // "BUY" or "SELL"
+    pub volume: f64,
+    pub price: f64,
+    pub profit: f64,
+    // Stop-loss / take-profit prices, 0 when not set
+    #[serde(default)]
+    pub sl: f64,
+    #[serde(default)]
+    pub tp: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PendingOrderData {
+    pub ticket: u64,
+    #[serde(rename = "type")]
+    pub order_type: String, // "BUY LIMIT", "SELL STOP", etc.
+    pub volume: f64,
+    pub price: f64,
+    #[serde(default)]
+    pub sl: f64,
+    #[serde(default)]
+    pub tp: f64,
+    // Server time the order expires, 0 = good till cancelled
+    #[serde(default)]
+    pub expiration: i64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TickData {
+    pub symbol: String,
+    pub bid: f64,
+    pub ask: f64,
+    pub time: i64,
+    #[serde(default)]
+    pub volume: u64,
+    // Broker server time minus UTC, in seconds (`time` is server time)
+    #[serde(default)]
+    pub server_offset: i64,
+    // Account info
+    #[serde(default)]
+    pub balance: f64,
+    #[serde(default)]
+    pub equity: f64,
+    #[serde(default)]
+    pub margin: f64,
+    #[serde(default)]
+    pub free_margin: f64,
+    // Trading constraints
+    #[serde(default)]
+    pub min_lot: f64,
+    #[serde(default)]
+    pub max_lot: f64,
+    #[serde(default)]
+    pub lot_step: f64,
+    // Price of one point (SYMBOL_POINT), 0 from older EA builds
+    #[serde(default)]
+    pub point: f64,
+    // Value of one lot, for risk sizing (0 from older EA builds):
+    // account money per tick_size move, and units per lot
+    #[serde(default)]
+    pub tick_value: f64,
+    #[serde(default)]
+    pub tick_size: f64,
+    #[serde(default)]
+    pub contract_size: f64,
+
+    // Active trades
+    #[serde(default)]
+    pub positions: Vec<PositionData>,
+    #[serde(default)]
+    pub orders: Vec<PendingOrderData>,
+}
+
+// ============================================================================
+// Order Protocol (REQ/REP 5556)
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Side::Buy => "BUY",
+            Side::Sell => "SELL",
+        }
+    }
+
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+}
+
+/// Chart periods understood by the EA (`download_history` timeframe).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Timeframe {
+    M1,
+    M5,
+    M15,
+    H1,
+    H4,
+    D1,
+}
+
+impl Timeframe {
+    pub const ALL: [Timeframe; 6] = [
+        Timeframe::M1,
+        Timeframe::M5,
+        Timeframe::M15,
+        Timeframe::H1,
+        Timeframe::H4,
+        Timeframe::D1,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Timeframe::M1 => "M1",
+            Timeframe::M5 => "M5",
+            Timeframe::M15 => "M15",
+            Timeframe::H1 => "H1",
+            Timeframe::H4 => "H4",
+            Timeframe::D1 => "D1",
+        }
+    }
+
+    /// Inverse of `as_str` ("M1", "H4", ...).
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|tf| tf.as_str() == text)
+    }
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Timeframe::M1 => 60,
+            Timeframe::M5 => 300,
+            Timeframe::M15 => 900,
+            Timeframe::H1 => 3600,
+            Timeframe::H4 => 14400,
+            Timeframe::D1 => 86400,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HistoryMode {
+    Ohlc,
+    Ticks,
+}
+
+impl HistoryMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryMode::Ohlc => "OHLC",
+            HistoryMode::Ticks => "TICKS",
+        }
+    }
+}
+
+/// Symbol of a `close_all`/`cancel_all` that covers every symbol on the
+/// account, e.g. for the kill switch.
+pub const ALL_SYMBOLS: &str = "*";
+
+/// One action the EA can perform. Serialized with the wire name in the
+/// `type` field, e.g. `{"type":"limit_buy","symbol":"XAUUSDc","volume":0.01,"price":2000.0}`,
+/// so each variant only carries the fields the EA reads for it.
+/// `sl`/`tp` are absolute prices; 0 means none and is left off the wire.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    MarketBuy {
+        symbol: String,
+        volume: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        sl: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        tp: f64,
+    },
+    MarketSell {
+        symbol: String,
+        volume: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        sl: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        tp: f64,
+    },
+    LimitBuy {
+        symbol: String,
+        volume: f64,
+        price: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        sl: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        tp: f64,
+    },
+    LimitSell {
+        symbol: String,
+        volume: f64,
+        price: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        sl: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        tp: f64,
+    },
+    StopBuy {
+        symbol: String,
+        volume: f64,
+        price: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        sl: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        tp: f64,
+    },
+    StopSell {
+        symbol: String,
+        volume: f64,
+        price: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        sl: f64,
+        #[serde(default, skip_serializing_if = "is_unset")]
+        tp: f64,
+    },
+    ClosePosition { symbol: String, ticket: u64 },
+    CancelOrder { symbol: String, ticket: u64 },
+    /// Closes `volume` lots of a position (a multiple of the lot step,
+    /// leaving at least the minimum lot open)
+    ClosePartial { symbol: String, ticket: u64, volume: f64 },
+    /// Closes every position on the symbol (or on all of them with
+    /// [`ALL_SYMBOLS`]), or only those in profit/loss
+    CloseAll {
+        symbol: String,
+        #[serde(default)]
+        filter: CloseFilter,
+    },
+    /// Deletes every pending order on the symbol, or on all of them with
+    /// [`ALL_SYMBOLS`]
+    CancelAll { symbol: String },
+    /// Closes a position and opens the same volume on the opposite side
+    Reverse { symbol: String, ticket: u64 },
+    /// New SL/TP of an open position (0 removes it)
+    ModifyPosition { symbol: String, ticket: u64, sl: f64, tp: f64 },
+    /// New price, SL/TP and expiry (server time, 0 = good till cancelled)
+    /// of a pending order
+    ModifyOrder {
+        symbol: String,
+        ticket: u64,
+        price: f64,
+        sl: f64,
+        tp: f64,
+        #[serde(default)]
+        expiration: i64,
+    },
+    /// Snapshot query used to reconcile after a timed-out request
+    GetPositions { symbol: String },
+    DownloadHistory {
+        symbol: String,
+        timeframe: Timeframe,
+        start: String, // "yyyy.mm.dd"
+        end: String,
+        mode: HistoryMode,
+    },
+}
+
+impl Request {
+    pub fn symbol(&self) -> &str {
+        match self {
+            Request::MarketBuy { symbol, .. }
+            | Request::MarketSell { symbol, .. }
+            | Request::LimitBuy { symbol, .. }
+            | Request::LimitSell { symbol, .. }
+            | Request::StopBuy { symbol, .. }
+            | Request::StopSell { symbol, .. }
+            | Request::ClosePosition { symbol, .. }
+            | Request::CancelOrder { symbol, .. }
+            | Request::ClosePartial { symbol, .. }
+            | Request::CloseAll { symbol, .. }
+            | Request::CancelAll { symbol }
+            | Request::Reverse { symbol, .. }
+            | Request::ModifyPosition { symbol, .. }
+            | Request::ModifyOrder { symbol, .. }
+            | Request::GetPositions { symbol }
+            | Request::DownloadHistory { symbol, .. } => symbol,
+        }
+    }
+
+    /// Direction of orders that open exposure; `None` for everything else.
+    pub fn side(&self) -> Option<Side> {
+        match self {
+            Request::MarketBuy { .. } | Request::LimitBuy { .. } | Request::StopBuy { .. } => Some(Side::Buy),
+            Request::MarketSell { .. } | Request::LimitSell { .. } | Request::StopSell { .. } => Some(Side::Sell),
+            _ => None,
+        }
+    }
+
+    /// Volume of a new market or pending order; `None` for the rest.
+    pub fn volume(&self) -> Option<f64> {
+        match self {
+            Request::MarketBuy { volume, .. }
+            | Request::MarketSell { volume, .. }
+            | Request::LimitBuy { volume, .. }
+            | Request::LimitSell { volume, .. }
+            | Request::StopBuy { volume, .. }
+            | Request::StopSell { volume, .. } => Some(*volume),
+            _ => None,
+        }
+    }
+
+    pub fn is_market(&self) -> bool {
+        matches!(self, Request::MarketBuy { .. } | Request::MarketSell { .. })
+    }
+
+    /// Entry price of a pending order; `None` for market orders and the rest.
+    pub fn entry_price(&self) -> Option<f64> {
+        match self {
+            Request::LimitBuy { price, .. }
+            | Request::LimitSell { price, .. }
+            | Request::StopBuy { price, .. }
+            | Request::StopSell { price, .. } => Some(*price),
+            _ => None,
+        }
+    }
+
+    /// `(sl, tp)` of an order or modification, 0 when not set.
+    pub fn stops(&self) -> (f64, f64) {
+        match self {
+            Request::MarketBuy { sl, tp, .. }
+            | Request::MarketSell { sl, tp, .. }
+            | Request::LimitBuy { sl, tp, .. }
+            | Request::LimitSell { sl, tp, .. }
+            | Request::StopBuy { sl, tp, .. }
+            | Request::StopSell { sl, tp, .. }
+            | Request::ModifyPosition { sl, tp, .. }
+            | Request::ModifyOrder { sl, tp, .. } => (*sl, *tp),
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// Wire name of the action, as sent in the `type` field.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Request::MarketBuy { .. } => "market_buy",
+            Request::MarketSell { .. } => "market_sell",
+            Request::LimitBuy { .. } => "limit_buy",
+            Request::LimitSell { .. } => "limit_sell",
+            Request::StopBuy { .. } => "stop_buy",
+            Request::StopSell { .. } => "stop_sell",
+            Request::ClosePosition { .. } => "close_position",
+            Request::CancelOrder { .. } => "cancel_order",
+            Request::ClosePartial { .. } => "close_partial",
+            Request::CloseAll { .. } => "close_all",
+            Request::CancelAll { .. } => "cancel_all",
+            Request::Reverse { .. } => "reverse",
+            Request::ModifyPosition { .. } => "modify_position",
+            Request::ModifyOrder { .. } => "modify_order",
+            Request::GetPositions { .. } => "get_positions",
+            Request::DownloadHistory { .. } => "download_history",
+        }
+    }
+}
+
+/// Which positions `close_all` closes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseFilter {
+    #[default]
+    All,
+    /// Floating profit > 0
+    Winners,
+    /// Floating profit < 0
+    Losers,
+}
+
+impl CloseFilter {
+    pub fn matches(&self, profit: f64) -> bool {
+        match self {
+            CloseFilter::All => true,
+            CloseFilter::Winners => profit > 0.0,
+            CloseFilter::Losers => profit < 0.0,
+        }
+    }
+}
+
+/// A [`Request`] plus the correlation ID the EA echoes back.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct OrderRequest {
+    #[serde(flatten)]
+    pub request: Request,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub request_id: Option<u64>, // Unique ID, echoed back by the EA
+}
+
+impl From<Request> for OrderRequest {
+    fn from(request: Request) -> Self {
+        Self { request, request_id: None }
+    }
+}
+
+fn is_unset(value: &f64) -> bool {
+    *value == 0.0
+}
+
+// ============================================================================
+// Stop-Loss / Take-Profit
+// ============================================================================
+
+/// A stop-loss or take-profit as the user enters it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopLevel {
+    /// Absolute price
+    Price(f64),
+    /// Distance from the entry price in points (`SYMBOL_POINT`)
+    Points(f64),
+}
+
+impl StopLevel {
+    /// Absolute price for an order on `side` entering at `entry`. Stop-losses
+    /// go against the position, take-profits with it.
+    pub fn resolve(self, side: Side, entry: f64, point: f64, take_profit: bool) -> f64 {
+        match self {
+            StopLevel::Price(price) => price,
+            StopLevel::Points(points) => {
+                let distance = points * point;
+                match (side, take_profit) {
+                    (Side::Buy, false) | (Side::Sell, true) => entry - distance,
+                    (Side::Buy, true) | (Side::Sell, false) => entry + distance,
+                }
+            }
+        }
+    }
+}
+
+/// Checks `sl`/`tp` (0 = none) against the side and entry price: a buy needs
+/// sl < entry < tp, a sell tp < entry < sl.
+pub fn check_stops(side: Side, entry: f64, sl: f64, tp: f64) -> Result<(), String> {
+    if sl < 0.0 || tp < 0.0 || !sl.is_finite() || !tp.is_finite() {
+        return Err("SL/TP must be positive prices".to_string());
+    }
+    let (sl_ok, tp_ok) = match side {
+        Side::Buy => (sl == 0.0 || sl < entry, tp == 0.0 || tp > entry),
+        Side::Sell => (sl == 0.0 || sl > entry, tp == 0.0 || tp < entry),
+    };
+    let (below, above) = if side == Side::Buy { ("below", "above") } else { ("above", "below") };
+    if !sl_ok {
+        return Err(format!("{} SL {} must be {} the entry price {}", side.as_str(), sl, below, entry));
+    }
+    if !tp_ok {
+        return Err(format!("{} TP {} must be {} the entry price {}", side.as_str(), tp, above, entry));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OrderResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<u64>,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    // Numeric MT5 retcode on failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retcode: Option<u32>,
+    // Deal price and server time (epoch seconds) of a filled market order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<i64>,
+    // Filled by get_positions replies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub positions: Option<Vec<PositionData>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orders: Option<Vec<PendingOrderData>>,
+    // One entry per ticket touched by a bulk action or reverse
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<Vec<TicketResult>>,
+    // Set locally when the EA never answered
+    #[serde(skip)]
+    pub timed_out: bool,
+}
+
+/// Outcome of one ticket in a bulk or reverse response.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TicketResult {
+    pub ticket: u64,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retcode: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TicketResult {
+    pub fn ok(ticket: u64) -> Self {
+        Self { ticket, success: true, retcode: None, error: None }
+    }
+
+    pub fn failed(ticket: u64, error: TradeError) -> Self {
+        Self {
+            ticket,
+            success: false,
+            retcode: Some(error.code()),
+            error: Some(format!("Error {}: {}", error.code(), error.description())),
+        }
+    }
+}
+
+impl OrderResponse {
+    pub fn failure(error: String) -> Self {
+        Self {
+            request_id: None,
+            success: false,
+            ticket: None,
+            error: Some(error),
+            message: None,
+            retcode: None,
+            price: None,
+            time: None,
+            positions: None,
+            orders: None,
+            results: None,
+            timed_out: false,
+        }
+    }
+
+    /// Successful reply carrying only `message`, e.g. downloaded history.
+    pub fn success_message(message: String) -> Self {
+        Self {
+            request_id: None,
+            success: true,
+            ticket: None,
+            error: None,
+            message: Some(message),
+            retcode: None,
+            price: None,
+            time: None,
+            positions: None,
+            orders: None,
+            results: None,
+            timed_out: false,
+        }
+    }
+
+    /// Typed trade error of a failed response, from `retcode` or, for older
+    /// EA builds, from an "Error NNNNN: ..." message.
+    pub fn trade_error(&self) -> Option<TradeError> {
+        if self.success {
+            return None;
+        }
+        match self.retcode {
+            Some(code) => TradeError::from_code(code),
+            None => self.error.as_deref().and_then(TradeError::from_message),
+        }
+    }
+}
+
+// ============================================================================
+// History Downloads
+// ============================================================================
+
+/// Separator between the summary and the CSV body of a download reply.
+pub const CSV_DATA_MARKER: &str = "||CSV_DATA||";
+
+/// Decoded `download_history` reply.
+#[derive(Clone, Debug)]
+pub struct HistoryData {
+    /// Summary line from the EA, e.g. "3000 records"
+    pub info: String,
+    /// CSV text with real newlines
+    pub csv: String,
+}
+
+impl HistoryData {
+    /// Splits an EA message of the form `info||CSV_DATA||row|NL|row...`.
+    pub fn parse(message: &str) -> Option<Self> {
+        let (info, csv) = message.split_once(CSV_DATA_MARKER)?;
+        Some(Self {
+            info: info.to_string(),
+            // Convert |NL| placeholders back to real newlines
+            csv: csv.replace("|NL|", "\n"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xau() -> String {
+        "XAUUSDc".to_string()
+    }
+
+    /// Every variant next to the exact JSON the EA parses for it.
+    fn golden() -> Vec<(Request, &'static str)> {
+        vec![
+            (
+                Request::MarketBuy { symbol: xau(), volume: 0.01, sl: 1990.0, tp: 2010.0 },
+                r#"{"type":"market_buy","symbol":"XAUUSDc","volume":0.01,"sl":1990.0,"tp":2010.0}"#,
+            ),
+            (
+                Request::MarketSell { symbol: xau(), volume: 0.02, sl: 0.0, tp: 0.0 },
+                r#"{"type":"market_sell","symbol":"XAUUSDc","volume":0.02}"#,
+            ),
+            (
+                Request::LimitBuy { symbol: xau(), volume: 0.01, price: 1995.5, sl: 0.0, tp: 2005.0 },
+                r#"{"type":"limit_buy","symbol":"XAUUSDc","volume":0.01,"price":1995.5,"tp":2005.0}"#,
+            ),
+            (
+                Request::LimitSell { symbol: xau(), volume: 0.01, price: 2005.5, sl: 2010.0, tp: 0.0 },
+                r#"{"type":"limit_sell","symbol":"XAUUSDc","volume":0.01,"price":2005.5,"sl":2010.0}"#,
+            ),
+            (
+                Request::StopBuy { symbol: xau(), volume: 0.1, price: 2005.0, sl: 0.0, tp: 0.0 },
+                r#"{"type":"stop_buy","symbol":"XAUUSDc","volume":0.1,"price":2005.0}"#,
+            ),
+            (
+                Request::StopSell { symbol: xau(), volume: 0.1, price: 1995.0, sl: 0.0, tp: 0.0 },
+                r#"{"type":"stop_sell","symbol":"XAUUSDc","volume":0.1,"price":1995.0}"#,
+            ),
+            (
+                Request::ClosePosition { symbol: xau(), ticket: 42 },
+                r#"{"type":"close_position","symbol":"XAUUSDc","ticket":42}"#,
+            ),
+            (
+                Request::CancelOrder { symbol: xau(), ticket: 43 },
+                r#"{"type":"cancel_order","symbol":"XAUUSDc","ticket":43}"#,
+            ),
+            (
+                Request::ClosePartial { symbol: xau(), ticket: 42, volume: 0.05 },
+                r#"{"type":"close_partial","symbol":"XAUUSDc","ticket":42,"volume":0.05}"#,
+            ),
+            (
+                Request::CloseAll { symbol: ALL_SYMBOLS.to_string(), filter: CloseFilter::All },
+                r#"{"type":"close_all","symbol":"*","filter":"all"}"#,
+            ),
+            (
+                Request::CloseAll { symbol: xau(), filter: CloseFilter::Winners },
+                r#"{"type":"close_all","symbol":"XAUUSDc","filter":"winners"}"#,
+            ),
+            (
+                Request::CloseAll { symbol: xau(), filter: CloseFilter::Losers },
+                r#"{"type":"close_all","symbol":"XAUUSDc","filter":"losers"}"#,
+            ),
+            (
+                Request::CancelAll { symbol: xau() },
+                r#"{"type":"cancel_all","symbol":"XAUUSDc"}"#,
+            ),
+            (
+                Request::Reverse { symbol: xau(), ticket: 42 },
+                r#"{"type":"reverse","symbol":"XAUUSDc","ticket":42}"#,
+            ),
+            (
+                Request::ModifyPosition { symbol: xau(), ticket: 42, sl: 0.0, tp: 2020.0 },
+                r#"{"type":"modify_position","symbol":"XAUUSDc","ticket":42,"sl":0.0,"tp":2020.0}"#,
+            ),
+            (
+                Request::ModifyOrder {
+                    symbol: xau(),
+                    ticket: 43,
+                    price: 1990.0,
+                    sl: 1985.0,
+                    tp: 0.0,
+                    expiration: 1769382117,
+                },
+                r#"{"type":"modify_order","symbol":"XAUUSDc","ticket":43,"price":1990.0,"sl":1985.0,"tp":0.0,"expiration":1769382117}"#,
+            ),
+            (
+                Request::GetPositions { symbol: xau() },
+                r#"{"type":"get_positions","symbol":"XAUUSDc"}"#,
+            ),
+            (
+                Request::DownloadHistory {
+                    symbol: xau(),
+                    timeframe: Timeframe::H1,
+                    start: "2026.01.01".to_string(),
+                    end: "2026.01.31".to_string(),
+                    mode: HistoryMode::Ohlc,
+                },
+                r#"{"type":"download_history","symbol":"XAUUSDc","timeframe":"H1","start":"2026.01.01","end":"2026.01.31","mode":"OHLC"}"#,
+            ),
+            (
+                Request::DownloadHistory {
+                    symbol: xau(),
+                    timeframe: Timeframe::M15,
+                    start: "2026.01.01".to_string(),
+                    end: "2026.01.02".to_string(),
+                    mode: HistoryMode::Ticks,
+                },
+                r#"{"type":"download_history","symbol":"XAUUSDc","timeframe":"M15","start":"2026.01.01","end":"2026.01.02","mode":"TICKS"}"#,
+            ),
+        ]
+    }
+
+    #[test]
+    fn requests_serialize_to_golden_json() {
+        for (request, json) in golden() {
+            assert_eq!(serde_json::to_string(&request).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn requests_deserialize_from_golden_json() {
+        for (request, json) in golden() {
+            assert_eq!(serde_json::from_str::<Request>(json).unwrap(), request, "{}", json);
+        }
+    }
+
+    #[test]
+    fn omitted_optional_fields_take_defaults() {
+        let json = r#"{"type":"market_buy","symbol":"XAUUSDc","volume":0.01}"#;
+        assert_eq!(
+            serde_json::from_str::<Request>(json).unwrap(),
+            Request::MarketBuy { symbol: xau(), volume: 0.01, sl: 0.0, tp: 0.0 }
+        );
+
+        let json = r#"{"type":"close_all","symbol":"XAUUSDc"}"#;
+        assert_eq!(
+            serde_json::from_str::<Request>(json).unwrap(),
+            Request::CloseAll { symbol: xau(), filter: CloseFilter::All }
+        );
+
+        let json = r#"{"type":"modify_order","symbol":"XAUUSDc","ticket":43,"price":1990.0,"sl":0.0,"tp":0.0}"#;
+        assert_eq!(
+            serde_json::from_str::<Request>(json).unwrap(),
+            Request::ModifyOrder { symbol: xau(), ticket: 43, price: 1990.0, sl: 0.0, tp: 0.0, expiration: 0 }
+        );
+    }
+
+    #[test]
+    fn wire_casing_is_fixed() {
+        // Tag names are snake_case, timeframes and modes upper case,
+        // close filters lower case; anything else is rejected.
+        for bad in [
+            r#"{"type":"MarketBuy","symbol":"XAUUSDc","volume":0.01}"#,
+            r#"{"type":"close_all","symbol":"XAUUSDc","filter":"Winners"}"#,
+            r#"{"type":"download_history","symbol":"X","timeframe":"h1","start":"","end":"","mode":"OHLC"}"#,
+            r#"{"type":"download_history","symbol":"X","timeframe":"H1","start":"","end":"","mode":"ohlc"}"#,
+        ] {
+            assert!(serde_json::from_str::<Request>(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn order_request_flattens_request_id() {
+        let mut request = OrderRequest::from(Request::ClosePosition { symbol: xau(), ticket: 42 });
+        let json = r#"{"type":"close_position","symbol":"XAUUSDc","ticket":42}"#;
+        assert_eq!(serde_json::to_string(&request).unwrap(), json);
+        assert_eq!(serde_json::from_str::<OrderRequest>(json).unwrap(), request);
+
+        request.request_id = Some(7);
+        let json = r#"{"type":"close_position","symbol":"XAUUSDc","ticket":42,"request_id":7}"#;
+        assert_eq!(serde_json::to_string(&request).unwrap(), json);
+        assert_eq!(serde_json::from_str::<OrderRequest>(json).unwrap(), request);
+
+        // Field order on the wire does not matter to the reader
+        let json = r#"{"request_id":7,"ticket":42,"symbol":"XAUUSDc","type":"close_position"}"#;
+        assert_eq!(serde_json::from_str::<OrderRequest>(json).unwrap(), request);
+    }
+}